@@ -1,27 +1,40 @@
 use axum::{
     body::boxed,
-    extract::DefaultBodyLimit,
+    extract::{connect_info::IntoMakeServiceWithConnectInfo, DefaultBodyLimit},
     http::{header, HeaderValue},
-    routing::{get, post, IntoMakeService},
+    routing::{get, post},
     Router,
 };
-use std::{sync::Arc, time::Duration};
+use std::{net::SocketAddr, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{
-    cors::CorsLayer, set_header::SetResponseHeaderLayer, timeout::TimeoutLayer, trace::TraceLayer,
+    compression::{
+        predicate::{DefaultPredicate, NotForContentType, Predicate},
+        CompressionLayer,
+    },
+    cors::CorsLayer,
+    set_header::SetResponseHeaderLayer,
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
     ServiceBuilderExt,
 };
-use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::{constants::*, database::AppDatabase, handlers::*, swagger::ApiDoc};
+use crate::{constants::*, handlers::*, state::AppState, swagger::api_doc};
 
 /// Initializes the app with all routes and middlewares
-pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
-    let root_route = Router::new()
-        .route("/ping", get(ping_handler))
-        .route("/tempApiGetToken", get(temp_api_get_token))
-        .route("/tempApiGetOtp", get(temp_api_get_otp));
+pub fn build_app_routes(state: AppState) -> Router {
+    #[allow(unused_mut)]
+    let mut root_route = Router::new().route("/ping", get(ping_handler));
+    if state.config.debug_api_enabled() {
+        tracing::warn!("debugging endpoints are enabled");
+        #[cfg(feature = "debug-api")]
+        {
+            root_route = root_route
+                .route("/tempApiGetToken", get(temp_api_get_token))
+                .route("/tempApiGetOtp", get(temp_api_get_otp));
+        }
+    }
     let user_route = Router::new()
         .route("/create", post(create_user_handler))
         .route("/login", post(login_handler))
@@ -36,10 +49,17 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
         .route("/signup", post(admin_signup_handler))
         .route("/generateOtp", get(admin_generate_otp))
         .route("/login", post(admin_login_handler))
+        .route("/users", get(get_admin_users_handler))
+        .route("/users", post(create_admin_user_handler))
+        .route("/users/update", post(update_admin_user_handler))
         .route(
             "/createSpecialReferralCode",
             post(create_special_code_handler),
-        );
+        )
+        .route("/withdrawals", get(get_withdrawals_handler))
+        .route("/withdrawals/approve", post(approve_withdrawal_handler))
+        .route("/withdrawals/reject", post(reject_withdrawal_handler))
+        .route("/walletTransactions", get(admin_get_transactions_handler));
     let clip_route = Router::new()
         .route("/", get(get_clips_handler))
         .route("/", post(create_clip_handler))
@@ -57,14 +77,34 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
         .route("/", post(create_contest_handler))
         .route("/", get(get_contest_handler))
         .route("/activate", post(activate_contest_handler))
-        .route("/inActivate", post(inactivate_contest_handler));
+        .route("/inActivate", post(inactivate_contest_handler))
+        .route("/update", post(update_contest_handler))
+        .route("/clone", post(clone_contest_handler))
+        .route("/template", post(save_template_handler))
+        .route("/template", get(get_templates_handler))
+        .route("/template/schedule", post(schedule_template_handler))
+        .route("/fromTemplate", post(contest_from_template_handler))
+        .route("/invite", get(get_invited_contest_handler))
+        .route("/join", post(join_contest_handler))
+        .route("/players", get(get_contest_players_handler))
+        .route("/:id/leaderboard", get(get_contest_leaderboard_handler))
+        .route("/:id/results", get(get_contest_results_handler))
+        .route("/events", get(contest_events_handler));
     let question_route = Router::new()
         .route("/", post(create_question_handler))
         .route("/", get(get_question_handler))
         .route("/delete", post(delete_question_handler))
-        .route("/update", post(update_question_handler));
+        .route("/update", post(update_question_handler))
+        .route("/export", get(export_questions_handler));
+    let question_bank_route = Router::new()
+        .route("/", post(create_bank_question_handler))
+        .route("/", get(get_bank_questions_handler))
+        .route("/update", post(update_bank_question_handler))
+        .route("/import", post(import_questions_handler))
+        .route("/attach", post(attach_questions_handler));
     let noti_route = Router::new()
         .route("/", get(get_noti_handler))
+        .route("/stream", get(stream_noti_handler))
         .route("/clear", post(clear_noti_handler))
         .route("/clearall", post(clear_all_noti_handler))
         .route("/markRead", post(mark_read_noti_handler))
@@ -78,10 +118,11 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
         .route("/multipart/finish", post(complete_multipart_handler));
     let wallet_route = Router::new()
         .route("/getBalance", get(get_bal_handler))
+        .route("/transactions", get(get_transactions_handler))
         .route("/addBalanceInit", post(add_bal_init_handler))
         .route("/addBalanceEnd", post(add_bal_end_handler))
+        .route("/paymentWebhook", post(payment_webhook_handler))
         .route("/withdrawBalInit", post(withdraw_bal_init_handler))
-        .route("/withdrawBalanceEnd", post(withdraw_bal_end_handler))
         .route("/payContest", post(pay_contest_handler));
     let play_tracker_route = Router::new()
         .route("/", get(get_play_tracker_handler))
@@ -98,6 +139,7 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
         .nest("/clip", clip_route)
         .nest("/favourite", fav_route)
         .nest("/question", question_route)
+        .nest("/questionBank", question_bank_route)
         .nest("/contest", contest_route)
         .nest("/wallet", wallet_route)
         .nest("/playTracker", play_tracker_route)
@@ -114,6 +156,10 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
     let cors_layer = CorsLayer::permissive();
     // create the timeout layer for middleware
     let timeout_layer = TimeoutLayer::new(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+    // create the compression layer for middleware, event streams must not be buffered
+    let compression_predicate =
+        DefaultPredicate::new().and(NotForContentType::const_new("text/event-stream"));
+    let compression_layer = CompressionLayer::new().compress_when(compression_predicate);
     // combine all middlewares with ServiceBuilder
     let middleware = ServiceBuilder::new()
         .layer(timeout_layer)
@@ -121,24 +167,24 @@ pub fn build_app_routes(db_client: Arc<AppDatabase>) -> Router {
         .layer(set_response_header_layer)
         .map_response_body(boxed)
         .layer(trace_layer)
-        .compression()
+        .layer(compression_layer)
         .into_inner();
 
     // create the app instance with all routes and middleware
     let app = Router::new()
-        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", api_doc(&state.config)))
         .route("/", get(default_route_handler))
         .nest("/api/v1", api_route)
         .layer(middleware)
         .fallback(global_404_handler)
-        .with_state(db_client);
+        .with_state(state);
     app
 }
 
 /// Initializes the app instance
-pub fn build_app(db_client: Arc<AppDatabase>) -> IntoMakeService<Router> {
+pub fn build_app(state: AppState) -> IntoMakeServiceWithConnectInfo<Router, SocketAddr> {
     tracing::debug!("Initializing the app");
-    let app = build_app_routes(db_client);
-    // return the IntoMakeService instance
-    app.into_make_service()
+    let app = build_app_routes(state);
+    // return the make service with the client address which is used for the rate limits
+    app.into_make_service_with_connect_info::<SocketAddr>()
 }
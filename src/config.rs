@@ -0,0 +1,402 @@
+use serde::Deserialize;
+use std::{net::IpAddr, path::Path, str::FromStr};
+
+use crate::constants::*;
+
+/// Environment in which the app is running, set by the `APP_ENV` env variable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Development,
+    Production,
+}
+
+impl FromStr for Environment {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "development" => Ok(Self::Development),
+            "production" => Ok(Self::Production),
+            _ => Err(anyhow::anyhow!("should be development or production")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MongoConfig {
+    pub uri: String,
+    pub min_pool_size: u32,
+    pub max_pool_size: u32,
+}
+
+impl Default for MongoConfig {
+    fn default() -> Self {
+        Self {
+            uri: String::new(),
+            min_pool_size: MONGO_MIN_POOL_SIZE,
+            max_pool_size: MONGO_MAX_POOL_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub secret_key: String,
+    /// validity of the access token in seconds
+    pub expiry: u64,
+    /// validity of the refresh token in seconds
+    pub refresh_token_expiry: u64,
+    /// key of the admin tokens, the admin tokens are signed with `secret_key` when not set
+    pub admin_secret_key: Option<String>,
+    /// validity of the admin token in seconds
+    pub admin_expiry: u64,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            secret_key: DEFAULT_JWT_SECRET_KEY.to_string(),
+            expiry: JWT_EXPIRY,
+            refresh_token_expiry: REFRESH_TOKEN_EXPIRY,
+            admin_secret_key: None,
+            admin_expiry: ADMIN_JWT_EXPIRY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AwsConfig {
+    pub region: String,
+    pub bucket: String,
+}
+
+impl Default for AwsConfig {
+    fn default() -> Self {
+        Self {
+            region: AWS_REGION.to_string(),
+            bucket: AWS_BUCKET.to_string(),
+        }
+    }
+}
+
+/// Names of the providers, an empty name selects the real provider
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    pub payment_gateway: String,
+    pub payout_provider: String,
+    pub sms_provider: String,
+}
+
+/// Intervals of the background jobs in seconds
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    pub cleanup_interval: u64,
+    pub notification_interval: u64,
+    pub payout_interval: u64,
+    pub ledger_reconcile_interval: u64,
+    pub play_tracker_sweep_interval: u64,
+    pub contest_scheduler_interval: u64,
+    pub finalize_contest_interval: u64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            cleanup_interval: CLEANUP_JOB_INTERVAL,
+            notification_interval: NOTIFICATION_JOB_INTERVAL,
+            payout_interval: PAYOUT_JOB_INTERVAL,
+            ledger_reconcile_interval: LEDGER_RECONCILE_JOB_INTERVAL,
+            play_tracker_sweep_interval: PLAY_TRACKER_SWEEP_JOB_INTERVAL,
+            contest_scheduler_interval: CONTEST_SCHEDULER_JOB_INTERVAL,
+            finalize_contest_interval: FINALIZE_CONTEST_JOB_INTERVAL,
+        }
+    }
+}
+
+impl JobsConfig {
+    fn intervals(&self) -> [(&'static str, u64); 7] {
+        [
+            ("jobs.cleanup_interval", self.cleanup_interval),
+            ("jobs.notification_interval", self.notification_interval),
+            ("jobs.payout_interval", self.payout_interval),
+            (
+                "jobs.ledger_reconcile_interval",
+                self.ledger_reconcile_interval,
+            ),
+            (
+                "jobs.play_tracker_sweep_interval",
+                self.play_tracker_sweep_interval,
+            ),
+            (
+                "jobs.contest_scheduler_interval",
+                self.contest_scheduler_interval,
+            ),
+            (
+                "jobs.finalize_contest_interval",
+                self.finalize_contest_interval,
+            ),
+        ]
+    }
+}
+
+/// Bonus amounts credited when a referral code is used
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ReferralConfig {
+    /// bonus of the user who uses the referral code
+    pub referral_bonus: u64,
+    /// bonus of the user who owns the referral code
+    pub referrer_bonus: u64,
+}
+
+impl Default for ReferralConfig {
+    fn default() -> Self {
+        Self {
+            referral_bonus: REFERRAL_BONUS,
+            referrer_bonus: REFERRER_BONUS,
+        }
+    }
+}
+
+/// Settings of the app loaded at startup from an optional TOML file given by the
+/// `APP_CONFIG_FILE` env variable. The env variables override the values of the file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub environment: Environment,
+    pub port: u16,
+    /// serve the debugging endpoints, they are available only with the `debug-api` feature
+    pub debug_api: bool,
+    pub app_upi_id: Option<String>,
+    /// phone of the first super admin, the admin signup is allowed only for this phone
+    /// until a super admin exists
+    pub bootstrap_admin_phone: Option<String>,
+    /// addresses of the reverse proxies whose `X-Forwarded-For` header is trusted
+    pub trusted_proxies: Vec<IpAddr>,
+    pub mongodb: MongoConfig,
+    pub jwt: JwtConfig,
+    pub aws: AwsConfig,
+    pub providers: ProvidersConfig,
+    pub jobs: JobsConfig,
+    pub referral: ReferralConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            environment: Environment::default(),
+            port: APP_PORT,
+            debug_api: false,
+            app_upi_id: None,
+            bootstrap_admin_phone: None,
+            trusted_proxies: vec![],
+            mongodb: MongoConfig::default(),
+            jwt: JwtConfig::default(),
+            aws: AwsConfig::default(),
+            providers: ProvidersConfig::default(),
+            jobs: JobsConfig::default(),
+            referral: ReferralConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Load the config from the file and the env variables and validate it
+    pub fn load() -> anyhow::Result<Self> {
+        let mut config = match std::env::var("APP_CONFIG_FILE") {
+            Ok(path) => Self::from_file(path)?,
+            Err(_) => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| anyhow::anyhow!("Unable to read {}: {}", path.display(), err))?;
+        Self::from_toml(&content)
+            .map_err(|err| anyhow::anyhow!("Unable to parse {}: {}", path.display(), err))
+    }
+
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    fn apply_env(&mut self) -> anyhow::Result<()> {
+        set_from_env("APP_ENV", &mut self.environment)?;
+        set_from_env("PORT", &mut self.port)?;
+        set_from_env("DEBUG_API", &mut self.debug_api)?;
+        set_from_env("MONGODB_URI", &mut self.mongodb.uri)?;
+        set_from_env("MONGODB_MIN_POOL_SIZE", &mut self.mongodb.min_pool_size)?;
+        set_from_env("MONGODB_MAX_POOL_SIZE", &mut self.mongodb.max_pool_size)?;
+        set_from_env("JWT_SECRET_KEY", &mut self.jwt.secret_key)?;
+        set_from_env("JWT_EXPIRY", &mut self.jwt.expiry)?;
+        set_from_env("REFRESH_TOKEN_EXPIRY", &mut self.jwt.refresh_token_expiry)?;
+        set_from_env("ADMIN_JWT_EXPIRY", &mut self.jwt.admin_expiry)?;
+        set_from_env("AWS_REGION", &mut self.aws.region)?;
+        set_from_env("AWS_BUCKET", &mut self.aws.bucket)?;
+        set_from_env("PAYMENT_GATEWAY", &mut self.providers.payment_gateway)?;
+        set_from_env("PAYOUT_PROVIDER", &mut self.providers.payout_provider)?;
+        set_from_env("SMS_PROVIDER", &mut self.providers.sms_provider)?;
+        set_from_env("REFERRAL_BONUS", &mut self.referral.referral_bonus)?;
+        set_from_env("REFERRER_BONUS", &mut self.referral.referrer_bonus)?;
+        if let Ok(app_upi_id) = std::env::var("APP_UPI_ID") {
+            self.app_upi_id = Some(app_upi_id);
+        }
+        if let Ok(secret_key) = std::env::var("ADMIN_JWT_SECRET_KEY") {
+            self.jwt.admin_secret_key = Some(secret_key);
+        }
+        if let Ok(phone) = std::env::var("BOOTSTRAP_ADMIN_PHONE") {
+            self.bootstrap_admin_phone = Some(phone);
+        }
+        if let Ok(proxies) = std::env::var("TRUSTED_PROXIES") {
+            self.trusted_proxies = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| proxy.parse::<IpAddr>())
+                .collect::<Result<_, _>>()
+                .map_err(|err| anyhow::anyhow!("Invalid value of TRUSTED_PROXIES: {}", err))?;
+        }
+        Ok(())
+    }
+
+    /// The debugging endpoints are never served in production
+    pub fn debug_api_enabled(&self) -> bool {
+        cfg!(feature = "debug-api") && self.debug_api && self.environment != Environment::Production
+    }
+
+    /// Check the values and refuse the insecure defaults in production
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.mongodb.uri.is_empty() {
+            errors.push("MONGODB_URI is required".to_string());
+        }
+        if self.mongodb.min_pool_size > self.mongodb.max_pool_size {
+            errors.push("MONGODB_MIN_POOL_SIZE should not exceed MONGODB_MAX_POOL_SIZE".into());
+        }
+        let jwt = &self.jwt;
+        if jwt.expiry == 0 || jwt.refresh_token_expiry == 0 || jwt.admin_expiry == 0 {
+            let err = "JWT_EXPIRY, REFRESH_TOKEN_EXPIRY and ADMIN_JWT_EXPIRY should be positive";
+            errors.push(err.into());
+        }
+        for (name, interval) in self.jobs.intervals() {
+            if interval == 0 {
+                errors.push(format!("{name} should be positive"));
+            }
+        }
+        if self.environment == Environment::Production {
+            errors.extend(self.production_errors());
+        }
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow::anyhow!("Invalid config: {}", errors.join("; ")))
+    }
+
+    fn production_errors(&self) -> Vec<String> {
+        let mut errors = vec![];
+        let secret_keys = [
+            ("JWT_SECRET_KEY", Some(&self.jwt.secret_key)),
+            ("ADMIN_JWT_SECRET_KEY", self.jwt.admin_secret_key.as_ref()),
+        ];
+        for (name, secret_key) in secret_keys {
+            let Some(secret_key) = secret_key else {
+                continue;
+            };
+            if secret_key == DEFAULT_JWT_SECRET_KEY || secret_key.len() < JWT_SECRET_KEY_MIN_LEN {
+                let err = format!(
+                    "{name} should be set to a secret of at least {JWT_SECRET_KEY_MIN_LEN} characters"
+                );
+                errors.push(err);
+            }
+        }
+        if self.debug_api {
+            errors.push("DEBUG_API should not be enabled".into());
+        }
+        if self.app_upi_id.as_deref().unwrap_or_default().is_empty() {
+            errors.push("APP_UPI_ID is required".into());
+        }
+        let mock_providers = [
+            ("PAYMENT_GATEWAY", &self.providers.payment_gateway, "mock"),
+            ("PAYOUT_PROVIDER", &self.providers.payout_provider, "mock"),
+            ("SMS_PROVIDER", &self.providers.sms_provider, "log"),
+        ];
+        for (name, provider, mock) in mock_providers {
+            if provider == mock {
+                errors.push(format!("{name} should not be {mock}"));
+            }
+        }
+        errors
+    }
+}
+
+/// Override the value when the env variable is set, an unparsable value is an error
+fn set_from_env<T: FromStr>(key: &str, value: &mut T) -> anyhow::Result<()>
+where
+    T::Err: std::fmt::Display,
+{
+    let Ok(var) = std::env::var(key) else {
+        return Ok(());
+    };
+    *value = var
+        .parse::<T>()
+        .map_err(|err| anyhow::anyhow!("Invalid value of {}: {}", key, err))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        let content = r#"
+            environment = "production"
+            port = 8080
+            trusted_proxies = ["10.0.0.1"]
+
+            [mongodb]
+            uri = "mongodb://localhost:27017"
+
+            [providers]
+            sms_provider = "log"
+
+            [jobs]
+            payout_interval = 30
+        "#;
+        let mut config = AppConfig::from_toml(content).unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(
+            config.trusted_proxies,
+            vec!["10.0.0.1".parse::<IpAddr>().unwrap()]
+        );
+        assert_eq!(config.jobs.payout_interval, 30);
+        assert_eq!(config.jobs.cleanup_interval, CLEANUP_JOB_INTERVAL);
+        assert_eq!(config.jwt.secret_key, DEFAULT_JWT_SECRET_KEY);
+
+        // production refuses the default secret, the log sms sender and the missing upi id
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("JWT_SECRET_KEY"));
+        assert!(err.contains("SMS_PROVIDER"));
+        assert!(err.contains("APP_UPI_ID"));
+        config.jwt.secret_key = "x".repeat(JWT_SECRET_KEY_MIN_LEN);
+        config.providers.sms_provider = "msg91".into();
+        config.app_upi_id = Some("trailsbuddy@upi".into());
+        assert!(config.validate().is_ok());
+
+        config.environment = Environment::Development;
+        config.mongodb.uri = String::new();
+        assert!(config.validate().is_err());
+        assert!(AppConfig::from_toml("port = \"abc\"").is_err());
+    }
+}
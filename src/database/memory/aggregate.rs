@@ -0,0 +1,580 @@
+use mongodb::bson::{Bson, Document};
+use std::collections::HashMap;
+
+use super::query::{
+    as_f64, bson_eq, bson_truthy, get_path, matches, remove_path, set_path, sort_cmp,
+    sort_documents,
+};
+
+pub type Vars = HashMap<String, Bson>;
+
+/// Run an aggregation pipeline over the documents.
+/// `collection` resolves the documents of another collection for `$lookup`.
+pub fn run_pipeline(
+    mut docs: Vec<Document>,
+    pipeline: &[Document],
+    collection: &dyn Fn(&str) -> Vec<Document>,
+) -> anyhow::Result<Vec<Document>> {
+    for stage in pipeline {
+        let Some((name, spec)) = stage.iter().next() else {
+            return Err(anyhow::anyhow!("empty pipeline stage"));
+        };
+        docs = match (name.as_str(), spec) {
+            ("$match", Bson::Document(filter)) => {
+                let mut result = vec![];
+                for doc in docs {
+                    if matches(&doc, filter)? {
+                        result.push(doc);
+                    }
+                }
+                result
+            }
+            ("$addFields" | "$set", Bson::Document(fields)) => {
+                let mut result = vec![];
+                for mut doc in docs {
+                    let root = doc.clone();
+                    for (path, expr) in fields {
+                        set_path(&mut doc, path, eval(expr, &root, &Vars::new())?)?;
+                    }
+                    result.push(doc);
+                }
+                result
+            }
+            ("$unset", paths) => {
+                let paths = match paths {
+                    Bson::String(p) => vec![p.as_str()],
+                    Bson::Array(arr) => arr.iter().filter_map(|p| p.as_str()).collect(),
+                    _ => return Err(anyhow::anyhow!("invalid $unset stage")),
+                };
+                for doc in docs.iter_mut() {
+                    paths.iter().for_each(|p| remove_path(doc, p));
+                }
+                docs
+            }
+            ("$project", Bson::Document(spec)) => docs
+                .iter()
+                .map(|doc| project(doc, spec))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            ("$sort", Bson::Document(spec)) => {
+                sort_documents(&mut docs, spec)?;
+                docs
+            }
+            ("$skip", n) => {
+                let n = as_f64(n).ok_or(anyhow::anyhow!("$skip requires a number"))? as usize;
+                docs.into_iter().skip(n).collect()
+            }
+            ("$limit", n) => {
+                let n = as_f64(n).ok_or(anyhow::anyhow!("$limit requires a number"))? as usize;
+                docs.into_iter().take(n).collect()
+            }
+            ("$count", Bson::String(field)) => {
+                if docs.is_empty() {
+                    vec![]
+                } else {
+                    let mut doc = Document::new();
+                    doc.insert(field, docs.len() as i32);
+                    vec![doc]
+                }
+            }
+            ("$unwind", spec) => unwind(docs, spec)?,
+            ("$group", Bson::Document(spec)) => group(docs, spec)?,
+            ("$lookup", Bson::Document(spec)) => lookup(docs, spec, collection)?,
+            ("$replaceRoot", Bson::Document(spec)) => {
+                let expr = spec
+                    .get("newRoot")
+                    .ok_or(anyhow::anyhow!("$replaceRoot requires newRoot"))?;
+                let mut result = vec![];
+                for doc in docs {
+                    match eval(expr, &doc, &Vars::new())? {
+                        Bson::Document(d) => result.push(d),
+                        _ => return Err(anyhow::anyhow!("newRoot must be a document")),
+                    }
+                }
+                result
+            }
+            _ => return Err(anyhow::anyhow!("unsupported pipeline stage: {}", name)),
+        };
+    }
+    Ok(docs)
+}
+
+/// Apply a projection specification (inclusion, exclusion or computed fields)
+pub fn project(doc: &Document, spec: &Document) -> anyhow::Result<Document> {
+    let is_flag = |v: &Bson| {
+        matches!(
+            v,
+            Bson::Boolean(_) | Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_)
+        )
+    };
+    let exclusion = spec
+        .iter()
+        .filter(|(k, _)| k.as_str() != "_id")
+        .all(|(_, v)| is_flag(v) && !bson_truthy(v));
+    if exclusion {
+        let mut result = doc.clone();
+        for (path, value) in spec {
+            if !bson_truthy(value) {
+                remove_path(&mut result, path);
+            }
+        }
+        return Ok(result);
+    }
+    let mut result = Document::new();
+    let include_id = spec.get("_id").map(|v| !is_flag(v) || bson_truthy(v));
+    if include_id.unwrap_or(true) {
+        if let Some(id) = doc.get("_id") {
+            result.insert("_id", id.clone());
+        }
+    }
+    for (path, value) in spec {
+        if is_flag(value) {
+            if bson_truthy(value) {
+                if let Some(v) = get_path(doc, path) {
+                    set_path(&mut result, path, v.clone())?;
+                }
+            }
+            continue;
+        }
+        set_path(&mut result, path, eval(value, doc, &Vars::new())?)?;
+    }
+    Ok(result)
+}
+
+fn unwind(docs: Vec<Document>, spec: &Bson) -> anyhow::Result<Vec<Document>> {
+    let (path, preserve) = match spec {
+        Bson::String(p) => (p.clone(), false),
+        Bson::Document(d) => (
+            d.get_str("path")?.to_string(),
+            d.get_bool("preserveNullAndEmptyArrays").unwrap_or(false),
+        ),
+        _ => return Err(anyhow::anyhow!("invalid $unwind stage")),
+    };
+    let path = path
+        .strip_prefix('$')
+        .ok_or(anyhow::anyhow!("$unwind path must start with $"))?;
+    let mut result = vec![];
+    for doc in docs {
+        match get_path(&doc, path).cloned() {
+            Some(Bson::Array(items)) if !items.is_empty() => {
+                for item in items {
+                    let mut d = doc.clone();
+                    set_path(&mut d, path, item)?;
+                    result.push(d);
+                }
+            }
+            Some(Bson::Array(_)) | Some(Bson::Null) | None => {
+                if preserve {
+                    result.push(doc);
+                }
+            }
+            Some(_) => result.push(doc),
+        }
+    }
+    Ok(result)
+}
+
+fn group(docs: Vec<Document>, spec: &Document) -> anyhow::Result<Vec<Document>> {
+    let id_expr = spec
+        .get("_id")
+        .ok_or(anyhow::anyhow!("$group requires _id"))?;
+    let mut groups: Vec<(Bson, Vec<Document>)> = vec![];
+    for doc in docs {
+        let key = eval(id_expr, &doc, &Vars::new())?;
+        match groups.iter_mut().find(|(k, _)| bson_eq(k, &key)) {
+            Some((_, members)) => members.push(doc),
+            None => groups.push((key, vec![doc])),
+        }
+    }
+    let mut result = vec![];
+    for (key, members) in groups {
+        let mut out = Document::new();
+        out.insert("_id", key);
+        for (field, acc) in spec.iter().filter(|(k, _)| k.as_str() != "_id") {
+            let Some((op, expr)) = acc.as_document().and_then(|d| d.iter().next()) else {
+                return Err(anyhow::anyhow!("invalid accumulator for {}", field));
+            };
+            let values = members
+                .iter()
+                .map(|m| eval(expr, m, &Vars::new()))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let value = accumulate(op, values)?;
+            out.insert(field, value);
+        }
+        result.push(out);
+    }
+    Ok(result)
+}
+
+fn accumulate(op: &str, values: Vec<Bson>) -> anyhow::Result<Bson> {
+    let numbers = || values.iter().filter(|v| as_f64(v).is_some());
+    let value = match op {
+        "$sum" => numbers().try_fold(Bson::Int32(0), |acc, v| arith("$add", &acc, v))?,
+        "$avg" => {
+            let count = numbers().count();
+            if count == 0 {
+                Bson::Null
+            } else {
+                let sum = numbers().filter_map(as_f64).sum::<f64>();
+                Bson::Double(sum / count as f64)
+            }
+        }
+        "$min" => values
+            .iter()
+            .filter(|v| !matches!(v, Bson::Null))
+            .min_by(|a, b| sort_cmp(Some(a), Some(b)))
+            .cloned()
+            .unwrap_or(Bson::Null),
+        "$max" => values
+            .iter()
+            .max_by(|a, b| sort_cmp(Some(a), Some(b)))
+            .cloned()
+            .unwrap_or(Bson::Null),
+        "$first" => values.first().cloned().unwrap_or(Bson::Null),
+        "$last" => values.last().cloned().unwrap_or(Bson::Null),
+        "$push" => Bson::Array(values),
+        "$addToSet" => {
+            let mut set: Vec<Bson> = vec![];
+            for v in values {
+                if !set.iter().any(|s| bson_eq(s, &v)) {
+                    set.push(v);
+                }
+            }
+            Bson::Array(set)
+        }
+        _ => return Err(anyhow::anyhow!("unsupported accumulator: {}", op)),
+    };
+    Ok(value)
+}
+
+fn lookup(
+    docs: Vec<Document>,
+    spec: &Document,
+    collection: &dyn Fn(&str) -> Vec<Document>,
+) -> anyhow::Result<Vec<Document>> {
+    let from = spec.get_str("from")?;
+    let local_field = spec.get_str("localField")?;
+    let foreign_field = spec.get_str("foreignField")?;
+    let as_field = spec.get_str("as")?;
+    let foreign_docs = collection(from);
+    let mut result = vec![];
+    for mut doc in docs {
+        let local = get_path(&doc, local_field).cloned().unwrap_or(Bson::Null);
+        let mut filter = Document::new();
+        match local {
+            Bson::Array(items) => filter.insert(
+                foreign_field,
+                Document::from_iter([("$in".to_string(), Bson::Array(items))]),
+            ),
+            v => filter.insert(foreign_field, v),
+        };
+        let mut joined = vec![];
+        for fd in foreign_docs.iter() {
+            if matches(fd, &filter)? {
+                joined.push(Bson::Document(fd.clone()));
+            }
+        }
+        set_path(&mut doc, as_field, Bson::Array(joined))?;
+        result.push(doc);
+    }
+    Ok(result)
+}
+
+/// Value of a field path in aggregation semantics, arrays of documents are mapped over
+fn field_value(value: &Bson, path: &str) -> Bson {
+    let mut current = value.clone();
+    for segment in path.split('.') {
+        current = match current {
+            Bson::Document(d) => d.get(segment).cloned().unwrap_or(Bson::Null),
+            Bson::Array(arr) => Bson::Array(
+                arr.iter()
+                    .filter_map(|i| i.as_document().and_then(|d| d.get(segment)).cloned())
+                    .collect(),
+            ),
+            _ => Bson::Null,
+        };
+    }
+    current
+}
+
+/// Evaluate an aggregation expression against the document
+pub fn eval(expr: &Bson, root: &Document, vars: &Vars) -> anyhow::Result<Bson> {
+    match expr {
+        Bson::String(s) if s.starts_with("$$") => {
+            let path = &s[2..];
+            let (name, rest) = match path.split_once('.') {
+                Some((name, rest)) => (name, Some(rest)),
+                None => (path, None),
+            };
+            let base = match name {
+                "ROOT" | "CURRENT" => Bson::Document(root.clone()),
+                name => vars
+                    .get(name)
+                    .cloned()
+                    .ok_or(anyhow::anyhow!("undefined variable: {}", name))?,
+            };
+            Ok(match rest {
+                Some(rest) => field_value(&base, rest),
+                None => base,
+            })
+        }
+        Bson::String(s) if s.starts_with('$') => {
+            Ok(field_value(&Bson::Document(root.clone()), &s[1..]))
+        }
+        Bson::Array(arr) => Ok(Bson::Array(
+            arr.iter()
+                .map(|e| eval(e, root, vars))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        )),
+        Bson::Document(d) => match d.iter().next() {
+            Some((op, args)) if d.len() == 1 && op.starts_with('$') => {
+                eval_operator(op, args, root, vars)
+            }
+            _ => {
+                let mut out = Document::new();
+                for (k, v) in d {
+                    out.insert(k, eval(v, root, vars)?);
+                }
+                Ok(Bson::Document(out))
+            }
+        },
+        other => Ok(other.clone()),
+    }
+}
+
+fn eval_args(args: &Bson, root: &Document, vars: &Vars) -> anyhow::Result<Vec<Bson>> {
+    match args {
+        Bson::Array(arr) => arr.iter().map(|a| eval(a, root, vars)).collect(),
+        other => Ok(vec![eval(other, root, vars)?]),
+    }
+}
+
+fn eval_operator(op: &str, args: &Bson, root: &Document, vars: &Vars) -> anyhow::Result<Bson> {
+    let value = match op {
+        "$literal" => args.clone(),
+        "$cond" => {
+            let (cond, then, otherwise) = match args {
+                Bson::Array(arr) if arr.len() == 3 => (&arr[0], &arr[1], &arr[2]),
+                Bson::Document(d) => (
+                    d.get("if").ok_or(anyhow::anyhow!("$cond requires if"))?,
+                    d.get("then")
+                        .ok_or(anyhow::anyhow!("$cond requires then"))?,
+                    d.get("else")
+                        .ok_or(anyhow::anyhow!("$cond requires else"))?,
+                ),
+                _ => return Err(anyhow::anyhow!("invalid $cond expression")),
+            };
+            if bson_truthy(&eval(cond, root, vars)?) {
+                eval(then, root, vars)?
+            } else {
+                eval(otherwise, root, vars)?
+            }
+        }
+        "$ifNull" => {
+            let values = eval_args(args, root, vars)?;
+            let last = values.last().cloned().unwrap_or(Bson::Null);
+            values
+                .into_iter()
+                .find(|v| !matches!(v, Bson::Null | Bson::Undefined))
+                .unwrap_or(last)
+        }
+        "$eq" | "$ne" | "$gt" | "$gte" | "$lt" | "$lte" => {
+            let values = eval_args(args, root, vars)?;
+            let [a, b] = values.as_slice() else {
+                return Err(anyhow::anyhow!("{} requires two arguments", op));
+            };
+            let ord = sort_cmp(Some(a), Some(b));
+            Bson::Boolean(match op {
+                "$eq" => bson_eq(a, b),
+                "$ne" => !bson_eq(a, b),
+                "$gt" => ord.is_gt(),
+                "$gte" => ord.is_ge(),
+                "$lt" => ord.is_lt(),
+                _ => ord.is_le(),
+            })
+        }
+        "$and" => Bson::Boolean(eval_args(args, root, vars)?.iter().all(bson_truthy)),
+        "$or" => Bson::Boolean(eval_args(args, root, vars)?.iter().any(bson_truthy)),
+        "$not" => Bson::Boolean(!eval_args(args, root, vars)?.iter().all(bson_truthy)),
+        "$add" | "$subtract" | "$multiply" | "$divide" => {
+            let values = eval_args(args, root, vars)?;
+            let Some((first, rest)) = values.split_first() else {
+                return Err(anyhow::anyhow!("{} requires arguments", op));
+            };
+            let mut acc = first.clone();
+            for v in rest {
+                acc = arith(op, &acc, v)?;
+            }
+            acc
+        }
+        "$sum" | "$max" | "$min" => {
+            let values = match args {
+                Bson::Array(_) => eval_args(args, root, vars)?,
+                other => match eval(other, root, vars)? {
+                    Bson::Array(arr) => arr,
+                    v => vec![v],
+                },
+            };
+            accumulate(op, values)?
+        }
+        "$size" => {
+            let value = match args {
+                Bson::Array(arr) if arr.len() == 1 => eval(&arr[0], root, vars)?,
+                other => eval(other, root, vars)?,
+            };
+            let Bson::Array(arr) = value else {
+                return Err(anyhow::anyhow!("$size requires an array"));
+            };
+            Bson::Int32(arr.len() as i32)
+        }
+        "$arrayElemAt" => {
+            let values = eval_args(args, root, vars)?;
+            let [Bson::Array(arr), idx] = values.as_slice() else {
+                return Err(anyhow::anyhow!("invalid $arrayElemAt expression"));
+            };
+            let idx = as_f64(idx).ok_or(anyhow::anyhow!("$arrayElemAt index"))? as i64;
+            let idx = if idx < 0 { arr.len() as i64 + idx } else { idx };
+            arr.get(idx as usize).cloned().unwrap_or(Bson::Null)
+        }
+        "$in" => {
+            let values = eval_args(args, root, vars)?;
+            let [v, Bson::Array(arr)] = values.as_slice() else {
+                return Err(anyhow::anyhow!("invalid $in expression"));
+            };
+            Bson::Boolean(arr.iter().any(|a| bson_eq(a, v)))
+        }
+        "$toString" => match eval(args, root, vars)? {
+            Bson::String(s) => Bson::String(s),
+            Bson::ObjectId(oid) => Bson::String(oid.to_hex()),
+            Bson::Int32(v) => Bson::String(v.to_string()),
+            Bson::Int64(v) => Bson::String(v.to_string()),
+            Bson::Double(v) => Bson::String(v.to_string()),
+            Bson::Boolean(v) => Bson::String(v.to_string()),
+            Bson::Null => Bson::Null,
+            v => return Err(anyhow::anyhow!("unsupported $toString value: {:?}", v)),
+        },
+        "$concat" => {
+            let mut out = String::new();
+            for v in eval_args(args, root, vars)? {
+                match v {
+                    Bson::String(s) => out.push_str(&s),
+                    Bson::Null => return Ok(Bson::Null),
+                    _ => return Err(anyhow::anyhow!("$concat requires strings")),
+                }
+            }
+            Bson::String(out)
+        }
+        "$filter" | "$map" => {
+            let Bson::Document(spec) = args else {
+                return Err(anyhow::anyhow!("{} requires a document", op));
+            };
+            let input = spec
+                .get("input")
+                .ok_or(anyhow::anyhow!("{} requires input", op))?;
+            let name = spec.get_str("as").unwrap_or("this");
+            let body = match op {
+                "$filter" => spec.get("cond"),
+                _ => spec.get("in"),
+            }
+            .ok_or(anyhow::anyhow!("invalid {} expression", op))?;
+            let items = match eval(input, root, vars)? {
+                Bson::Array(arr) => arr,
+                Bson::Null => return Ok(Bson::Null),
+                _ => return Err(anyhow::anyhow!("{} input must be an array", op)),
+            };
+            let mut out = vec![];
+            for item in items {
+                let mut vars = vars.clone();
+                vars.insert(name.to_string(), item.clone());
+                let value = eval(body, root, &vars)?;
+                if op == "$map" {
+                    out.push(value);
+                } else if bson_truthy(&value) {
+                    out.push(item);
+                }
+            }
+            Bson::Array(out)
+        }
+        _ => return Err(anyhow::anyhow!("unsupported expression operator: {}", op)),
+    };
+    Ok(value)
+}
+
+/// Arithmetic keeping integer types when possible
+pub fn arith(op: &str, a: &Bson, b: &Bson) -> anyhow::Result<Bson> {
+    if matches!(a, Bson::Null) || matches!(b, Bson::Null) {
+        return Ok(Bson::Null);
+    }
+    let int = |v: &Bson| match v {
+        Bson::Int32(v) => Some(*v as i64),
+        Bson::Int64(v) => Some(*v),
+        _ => None,
+    };
+    if let (Some(x), Some(y), true) = (int(a), int(b), op != "$divide") {
+        let result = match op {
+            "$add" => x.checked_add(y),
+            "$subtract" => x.checked_sub(y),
+            _ => x.checked_mul(y),
+        }
+        .ok_or(anyhow::anyhow!("integer overflow"))?;
+        if let (Bson::Int32(_), Bson::Int32(_), Ok(r)) = (a, b, i32::try_from(result)) {
+            return Ok(Bson::Int32(r));
+        }
+        return Ok(Bson::Int64(result));
+    }
+    let (Some(x), Some(y)) = (as_f64(a), as_f64(b)) else {
+        return Err(anyhow::anyhow!("{} requires numeric arguments", op));
+    };
+    let result = match op {
+        "$add" => x + y,
+        "$subtract" => x - y,
+        "$multiply" => x * y,
+        _ => x / y,
+    };
+    Ok(Bson::Double(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{doc, oid::ObjectId};
+
+    #[test]
+    fn test_run_pipeline() {
+        let oid = ObjectId::new();
+        let docs = vec![
+            doc! {"_id": oid, "name": "a", "likes": [{"userId": 1, "isRemoved": false}, {"userId": 2, "isRemoved": true}]},
+            doc! {"_id": ObjectId::new(), "name": "b"},
+        ];
+        let pipeline = vec![
+            doc! {"$match": {"name": {"$in": ["a", "b"]}}},
+            doc! {"$addFields": {
+                "_id": {"$toString": "$_id"},
+                "likeCount": {"$size": {"$ifNull": [{"$filter": {"input": "$likes", "as": "l", "cond": {"$eq": ["$$l.isRemoved", false]}}}, []]}}
+            }},
+            doc! {"$sort": {"likeCount": -1}},
+            doc! {"$project": {"likes": 0}},
+            doc! {"$limit": 1},
+        ];
+        let result = run_pipeline(docs, &pipeline, &|_| vec![]).unwrap();
+        assert_eq!(
+            result,
+            vec![doc! {"_id": oid.to_hex(), "name": "a", "likeCount": 1}]
+        );
+    }
+
+    #[test]
+    fn test_group() {
+        let docs = vec![
+            doc! {"userId": 1, "amount": 10_i64},
+            doc! {"userId": 2, "amount": 5_i64},
+            doc! {"userId": 1, "amount": 7_i64},
+        ];
+        let pipeline = vec![
+            doc! {"$group": {"_id": "$userId", "total": {"$sum": "$amount"}, "count": {"$sum": 1}}},
+            doc! {"$sort": {"_id": 1}},
+        ];
+        let result = run_pipeline(docs, &pipeline, &|_| vec![]).unwrap();
+        assert_eq!(result[0], doc! {"_id": 1, "total": 17_i64, "count": 2});
+        assert_eq!(result[1], doc! {"_id": 2, "total": 5_i64, "count": 1});
+    }
+}
@@ -0,0 +1,637 @@
+mod aggregate;
+mod query;
+mod update;
+
+use axum::async_trait;
+use mongodb::{
+    bson::{oid::ObjectId, Bson, Document},
+    options::*,
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use self::{
+    aggregate::{project, run_pipeline},
+    query::{bson_eq, get_path, matches, sort_documents},
+    update::{apply_update, upsert_seed},
+};
+use super::{storage::Storage, DbSession, UpdateResult};
+
+#[derive(Debug, Clone, Default)]
+struct MemoryData {
+    collections: HashMap<String, Vec<Document>>,
+    unique_indexes: HashMap<String, Vec<Vec<String>>>,
+}
+
+/// Storage backend keeping all the documents in process memory, used in tests.
+/// A transaction holds the data lock until it is committed or aborted,
+/// so operations without the session must not be awaited inside a transaction.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    data: Arc<Mutex<MemoryData>>,
+}
+
+struct Transaction {
+    guard: OwnedMutexGuard<MemoryData>,
+    snapshot: MemoryData,
+}
+
+pub struct MemorySession {
+    transaction: Option<Transaction>,
+}
+
+struct UpdateSpec<'a> {
+    upsert: bool,
+    array_filters: &'a [Document],
+    sort: Option<&'a Document>,
+    multi: bool,
+}
+
+#[derive(Default)]
+struct FindSpec {
+    sort: Option<Document>,
+    skip: Option<u64>,
+    limit: Option<i64>,
+    projection: Option<Document>,
+}
+
+fn coll_key(db: &str, coll: &str) -> String {
+    format!("{}.{}", db, coll)
+}
+
+fn id_to_string(id: &Bson) -> String {
+    match id {
+        Bson::ObjectId(oid) => oid.to_hex(),
+        Bson::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl MemoryData {
+    fn documents(&self, key: &str) -> Vec<Document> {
+        self.collections.get(key).cloned().unwrap_or_default()
+    }
+
+    fn find(&self, key: &str, filter: &Document, spec: FindSpec) -> anyhow::Result<Vec<Document>> {
+        let mut docs = vec![];
+        for doc in self.collections.get(key).into_iter().flatten() {
+            if matches(doc, filter)? {
+                docs.push(doc.clone());
+            }
+        }
+        if let Some(sort) = spec.sort.as_ref() {
+            sort_documents(&mut docs, sort)?;
+        }
+        let skip = spec.skip.unwrap_or(0) as usize;
+        let limit = match spec.limit {
+            Some(limit) if limit != 0 => limit.unsigned_abs() as usize,
+            _ => usize::MAX,
+        };
+        docs.into_iter()
+            .skip(skip)
+            .take(limit)
+            .map(|doc| match spec.projection.as_ref() {
+                Some(projection) => project(&doc, projection),
+                None => Ok(doc),
+            })
+            .collect()
+    }
+
+    fn check_unique(&self, key: &str, docs: &[Document]) -> anyhow::Result<()> {
+        let mut indexes = vec![vec!["_id".to_string()]];
+        indexes.extend(self.unique_indexes.get(key).cloned().unwrap_or_default());
+        for fields in indexes.iter() {
+            let values = docs
+                .iter()
+                .map(|doc| {
+                    fields
+                        .iter()
+                        .map(|f| get_path(doc, f).cloned().unwrap_or(Bson::Null))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            for (i, a) in values.iter().enumerate() {
+                let duplicate = values[i + 1..]
+                    .iter()
+                    .any(|b| a.iter().zip(b.iter()).all(|(x, y)| bson_eq(x, y)));
+                if duplicate {
+                    return Err(anyhow::anyhow!(
+                        "E11000 duplicate key error collection: {} index: {}",
+                        key,
+                        fields.join("_")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, key: &str, new_docs: Vec<Document>) -> anyhow::Result<Vec<String>> {
+        let mut docs = self.documents(key);
+        let mut ids = vec![];
+        for mut doc in new_docs {
+            let id = match doc.get("_id") {
+                Some(id) => id.clone(),
+                None => {
+                    let id = Bson::ObjectId(ObjectId::new());
+                    let mut with_id = Document::new();
+                    with_id.insert("_id", id.clone());
+                    with_id.extend(doc);
+                    doc = with_id;
+                    id
+                }
+            };
+            ids.push(id_to_string(&id));
+            docs.push(doc);
+        }
+        self.check_unique(key, &docs)?;
+        self.collections.insert(key.to_string(), docs);
+        Ok(ids)
+    }
+
+    /// Update the matching documents, returns the update result
+    /// along with the document before and after update of the first match
+    fn update(
+        &mut self,
+        key: &str,
+        filter: &Document,
+        update: &UpdateModifications,
+        spec: UpdateSpec,
+    ) -> anyhow::Result<(UpdateResult, Option<(Document, Document)>)> {
+        let mut docs = self.documents(key);
+        let mut positions = vec![];
+        for (idx, doc) in docs.iter().enumerate() {
+            if matches(doc, filter)? {
+                positions.push(idx);
+            }
+        }
+        if let Some(sort) = spec.sort {
+            let mut matched = positions
+                .iter()
+                .map(|i| docs[*i].clone())
+                .collect::<Vec<_>>();
+            sort_documents(&mut matched, sort)?;
+            if let Some(first) = matched.first() {
+                let first_id = first.get("_id").cloned().unwrap_or(Bson::Null);
+                positions.retain(|i| {
+                    docs[*i]
+                        .get("_id")
+                        .map(|id| bson_eq(id, &first_id))
+                        .unwrap_or(false)
+                });
+            }
+        }
+        if !spec.multi {
+            positions.truncate(1);
+        }
+        let mut result = UpdateResult {
+            matched_count: positions.len() as u64,
+            modified_count: 0,
+            upserted_id: None,
+        };
+        let mut first_change = None;
+        for idx in positions {
+            let before = docs[idx].clone();
+            let mut after = before.clone();
+            apply_update(&mut after, update, spec.array_filters, false)?;
+            if after != before {
+                result.modified_count += 1;
+            }
+            if first_change.is_none() {
+                first_change = Some((before, after.clone()));
+            }
+            docs[idx] = after;
+        }
+        if result.matched_count == 0 && spec.upsert {
+            let mut doc = upsert_seed(filter)?;
+            apply_update(&mut doc, update, spec.array_filters, true)?;
+            let mut ids = self.insert(key, vec![doc.clone()])?;
+            let inserted = self
+                .collections
+                .get(key)
+                .and_then(|docs| docs.last().cloned())
+                .unwrap_or(doc);
+            result.upserted_id = ids.pop();
+            return Ok((result, Some((Document::new(), inserted))));
+        }
+        self.check_unique(key, &docs)?;
+        self.collections.insert(key.to_string(), docs);
+        Ok((result, first_change))
+    }
+
+    fn delete(&mut self, key: &str, filter: &Document) -> anyhow::Result<u64> {
+        let docs = self.documents(key);
+        let mut kept = vec![];
+        let mut deleted = 0;
+        for doc in docs {
+            if matches(&doc, filter)? {
+                deleted += 1;
+            } else {
+                kept.push(doc);
+            }
+        }
+        self.collections.insert(key.to_string(), kept);
+        Ok(deleted)
+    }
+
+    fn aggregate(
+        &self,
+        db: &str,
+        key: &str,
+        pipeline: &[Document],
+    ) -> anyhow::Result<Vec<Document>> {
+        let collection = |name: &str| self.documents(&coll_key(db, name));
+        run_pipeline(self.documents(key), pipeline, &collection)
+    }
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject writes which would create duplicate values for the fields in the collection
+    pub fn with_unique_index(self, db: &str, coll: &str, fields: &[&str]) -> Self {
+        {
+            let mut data = self
+                .data
+                .try_lock()
+                .expect("unique index must be created before the storage is used");
+            let fields = fields.iter().map(|f| f.to_string()).collect();
+            data.unique_indexes
+                .entry(coll_key(db, coll))
+                .or_default()
+                .push(fields);
+        }
+        self
+    }
+
+    async fn with_data<R, F>(&self, session: Option<&mut DbSession>, f: F) -> anyhow::Result<R>
+    where
+        F: FnOnce(&mut MemoryData) -> anyhow::Result<R> + Send,
+    {
+        let session = match session {
+            None => None,
+            Some(DbSession::Memory(session)) => Some(session),
+            Some(_) => {
+                return Err(anyhow::anyhow!("session was not created by memory storage"));
+            }
+        };
+        match session.and_then(|s| s.transaction.as_mut()) {
+            Some(transaction) => f(&mut transaction.guard),
+            None => {
+                let mut data = self.data.lock().await;
+                f(&mut data)
+            }
+        }
+    }
+}
+
+fn memory_session(session: &mut DbSession) -> anyhow::Result<&mut MemorySession> {
+    match session {
+        DbSession::Memory(session) => Ok(session),
+        _ => Err(anyhow::anyhow!("session was not created by memory storage")),
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn find_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOneOptions>,
+    ) -> anyhow::Result<Option<Document>> {
+        let key = coll_key(db, coll);
+        let filter = filter.unwrap_or_default();
+        let options = options.unwrap_or_default();
+        let spec = FindSpec {
+            sort: options.sort,
+            skip: options.skip,
+            limit: Some(1),
+            projection: options.projection,
+        };
+        let docs = self
+            .with_data(session, |data| data.find(&key, &filter, spec))
+            .await?;
+        Ok(docs.into_iter().next())
+    }
+
+    async fn find(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        let key = coll_key(db, coll);
+        let filter = filter.unwrap_or_default();
+        let options = options.unwrap_or_default();
+        let spec = FindSpec {
+            sort: options.sort,
+            skip: options.skip,
+            limit: options.limit,
+            projection: options.projection,
+        };
+        self.with_data(session, |data| data.find(&key, &filter, spec))
+            .await
+    }
+
+    async fn count_documents(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<CountOptions>,
+    ) -> anyhow::Result<u64> {
+        let key = coll_key(db, coll);
+        let filter = filter.unwrap_or_default();
+        let options = options.unwrap_or_default();
+        let spec = FindSpec {
+            skip: options.skip,
+            limit: options.limit.map(|limit| limit as i64),
+            ..Default::default()
+        };
+        self.with_data(session, |data| {
+            data.find(&key, &filter, spec).map(|docs| docs.len() as u64)
+        })
+        .await
+    }
+
+    async fn find_one_and_update(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Document,
+        update: UpdateModifications,
+        options: Option<FindOneAndUpdateOptions>,
+    ) -> anyhow::Result<Option<Document>> {
+        let key = coll_key(db, coll);
+        let options = options.unwrap_or_default();
+        let upsert = options.upsert.unwrap_or(false);
+        let array_filters = options.array_filters.unwrap_or_default();
+        let return_after = matches!(options.return_document, Some(ReturnDocument::After));
+        let (result, change) = self
+            .with_data(session, |data| {
+                let spec = UpdateSpec {
+                    upsert,
+                    array_filters: &array_filters,
+                    sort: options.sort.as_ref(),
+                    multi: false,
+                };
+                data.update(&key, &filter, &update, spec)
+            })
+            .await?;
+        let Some((before, after)) = change else {
+            return Ok(None);
+        };
+        let doc = match (return_after, result.upserted_id.is_some()) {
+            (true, _) => after,
+            (false, true) => return Ok(None),
+            (false, false) => before,
+        };
+        match options.projection.as_ref() {
+            Some(projection) => Ok(Some(project(&doc, projection)?)),
+            None => Ok(Some(doc)),
+        }
+    }
+
+    async fn insert_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        doc: Document,
+        _options: Option<InsertOneOptions>,
+    ) -> anyhow::Result<String> {
+        let key = coll_key(db, coll);
+        let mut ids = self
+            .with_data(session, |data| data.insert(&key, vec![doc]))
+            .await?;
+        ids.pop()
+            .ok_or(anyhow::anyhow!("Not able to get the inserted id"))
+    }
+
+    async fn insert_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        docs: Vec<Document>,
+        _options: Option<InsertManyOptions>,
+    ) -> anyhow::Result<Vec<String>> {
+        let key = coll_key(db, coll);
+        self.with_data(session, |data| data.insert(&key, docs))
+            .await
+    }
+
+    async fn update_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        let key = coll_key(db, coll);
+        let options = options.unwrap_or_default();
+        let upsert = options.upsert.unwrap_or(false);
+        let array_filters = options.array_filters.unwrap_or_default();
+        let (result, _) = self
+            .with_data(session, |data| {
+                let spec = UpdateSpec {
+                    upsert,
+                    array_filters: &array_filters,
+                    sort: None,
+                    multi: false,
+                };
+                data.update(&key, &query, &update, spec)
+            })
+            .await?;
+        Ok(result)
+    }
+
+    async fn update_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        let key = coll_key(db, coll);
+        let options = options.unwrap_or_default();
+        let upsert = options.upsert.unwrap_or(false);
+        let array_filters = options.array_filters.unwrap_or_default();
+        let (result, _) = self
+            .with_data(session, |data| {
+                let spec = UpdateSpec {
+                    upsert,
+                    array_filters: &array_filters,
+                    sort: None,
+                    multi: true,
+                };
+                data.update(&key, &query, &update, spec)
+            })
+            .await?;
+        Ok(result)
+    }
+
+    async fn delete_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        _options: Option<DeleteOptions>,
+    ) -> anyhow::Result<u64> {
+        let key = coll_key(db, coll);
+        self.with_data(session, |data| data.delete(&key, &query))
+            .await
+    }
+
+    async fn aggregate(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        _options: Option<AggregateOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        let key = coll_key(db, coll);
+        self.with_data(session, |data| data.aggregate(db, &key, &pipeline))
+            .await
+    }
+
+    async fn start_session(&self, _options: Option<SessionOptions>) -> anyhow::Result<DbSession> {
+        let session = MemorySession { transaction: None };
+        Ok(DbSession::Memory(session))
+    }
+
+    async fn start_transaction(
+        &self,
+        session: &mut DbSession,
+        _options: Option<TransactionOptions>,
+    ) -> anyhow::Result<()> {
+        let session = memory_session(session)?;
+        if session.transaction.is_some() {
+            return Err(anyhow::anyhow!("transaction already in progress"));
+        }
+        let guard = self.data.clone().lock_owned().await;
+        let snapshot = guard.clone();
+        session.transaction = Some(Transaction { guard, snapshot });
+        Ok(())
+    }
+
+    async fn commit_transaction(&self, session: &mut DbSession) -> anyhow::Result<()> {
+        let session = memory_session(session)?;
+        session
+            .transaction
+            .take()
+            .ok_or(anyhow::anyhow!("no transaction started"))?;
+        Ok(())
+    }
+
+    async fn abort_transaction(&self, session: &mut DbSession) -> anyhow::Result<()> {
+        let session = memory_session(session)?;
+        let mut transaction = session
+            .transaction
+            .take()
+            .ok_or(anyhow::anyhow!("no transaction started"))?;
+        *transaction.guard = transaction.snapshot;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+    use mongodb::bson::doc;
+
+    use super::*;
+    use crate::database::AppDatabase;
+
+    #[tokio::test]
+    async fn test_transaction_abort() {
+        let db = AppDatabase::in_memory();
+        db.insert_one(DB, COLL, &doc! {"id": 1, "value": 1}, None)
+            .await
+            .unwrap();
+        let result: anyhow::Result<()> = db
+            .execute_transaction(None, None, |db, session| {
+                async move {
+                    let update = doc! {"$inc": {"value": 1}};
+                    db.update_one_with_session(session, DB, COLL, doc! {"id": 1}, update, None)
+                        .await?;
+                    let doc = doc! {"id": 2, "value": 1};
+                    db.insert_one_with_session(session, DB, COLL, &doc, None)
+                        .await?;
+                    Err(anyhow::anyhow!("abort"))
+                }
+                .boxed()
+            })
+            .await;
+        assert!(result.is_err());
+        let docs = db.find::<Document>(DB, COLL, None, None).await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].get_i32("value").unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_return_document() {
+        let db = AppDatabase::in_memory();
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(Some(true))
+            .return_document(Some(ReturnDocument::After))
+            .build();
+        let update = doc! {"$inc": {"balance.real": 10_i64}, "$setOnInsert": {"createdTs": 1}};
+        let doc = db
+            .find_one_and_update::<Document>(DB, COLL, doc! {"userId": 5}, update, Some(options))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(doc.get_i32("userId").unwrap(), 5);
+        assert_eq!(
+            doc.get_document("balance")
+                .unwrap()
+                .get_i64("real")
+                .unwrap(),
+            10
+        );
+        let update = doc! {"$inc": {"balance.real": 5_i64}, "$setOnInsert": {"createdTs": 2}};
+        let doc = db
+            .find_one_and_update::<Document>(DB, COLL, doc! {"userId": 5}, update, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            doc.get_document("balance")
+                .unwrap()
+                .get_i64("real")
+                .unwrap(),
+            10
+        );
+        assert_eq!(doc.get_i32("createdTs").unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unique_index() {
+        let storage = MemoryStorage::new().with_unique_index(DB, COLL, &["phone"]);
+        let db = AppDatabase::with_storage(storage);
+        db.insert_one(DB, COLL, &doc! {"phone": "1"}, None)
+            .await
+            .unwrap();
+        let result = db.insert_one(DB, COLL, &doc! {"phone": "1"}, None).await;
+        assert!(result.is_err());
+    }
+
+    const DB: &str = "test";
+    const COLL: &str = "items";
+}
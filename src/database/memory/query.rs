@@ -0,0 +1,428 @@
+use mongodb::bson::{Bson, Document};
+use std::cmp::Ordering;
+
+/// Check whether the document satisfies a mongodb style query filter
+pub fn matches(doc: &Document, filter: &Document) -> anyhow::Result<bool> {
+    for (key, cond) in filter {
+        let is_match = match key.as_str() {
+            "$and" => logical_operands(cond)?
+                .iter()
+                .map(|f| matches(doc, f))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .into_iter()
+                .all(|m| m),
+            "$or" => logical_operands(cond)?
+                .iter()
+                .map(|f| matches(doc, f))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .into_iter()
+                .any(|m| m),
+            "$nor" => !logical_operands(cond)?
+                .iter()
+                .map(|f| matches(doc, f))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .into_iter()
+                .any(|m| m),
+            k if k.starts_with('$') => {
+                return Err(anyhow::anyhow!("unsupported query operator: {}", k));
+            }
+            path => matches_path(doc, path, cond)?,
+        };
+        if !is_match {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn logical_operands(cond: &Bson) -> anyhow::Result<Vec<&Document>> {
+    let Bson::Array(arr) = cond else {
+        return Err(anyhow::anyhow!("logical operator requires an array"));
+    };
+    arr.iter()
+        .map(|f| match f {
+            Bson::Document(d) => Ok(d),
+            _ => Err(anyhow::anyhow!("logical operator requires documents")),
+        })
+        .collect()
+}
+
+fn is_operator_doc(cond: &Bson) -> bool {
+    match cond {
+        Bson::Document(d) => !d.is_empty() && d.keys().all(|k| k.starts_with('$')),
+        _ => false,
+    }
+}
+
+fn matches_path(doc: &Document, path: &str, cond: &Bson) -> anyhow::Result<bool> {
+    let segments = path.split('.').collect::<Vec<_>>();
+    let mut values = vec![];
+    lookup_in_doc(doc, &segments, true, &mut values);
+    if let (true, Bson::Document(ops)) = (is_operator_doc(cond), cond) {
+        for (op, arg) in ops {
+            if !matches_operator(doc, &segments, &values, op, arg)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+    Ok(matches_eq(&values, cond))
+}
+
+fn matches_eq(values: &[&Bson], cond: &Bson) -> bool {
+    if matches!(cond, Bson::Null) && values.is_empty() {
+        return true;
+    }
+    values.iter().any(|v| bson_eq(v, cond))
+}
+
+fn matches_operator(
+    doc: &Document,
+    segments: &[&str],
+    values: &[&Bson],
+    op: &str,
+    arg: &Bson,
+) -> anyhow::Result<bool> {
+    let cmp_any = |f: fn(Ordering) -> bool| {
+        values
+            .iter()
+            .any(|v| bson_cmp(v, arg).map(f).unwrap_or(false))
+    };
+    let result = match op {
+        "$eq" => matches_eq(values, arg),
+        "$ne" => !matches_eq(values, arg),
+        "$gt" => cmp_any(|o| o == Ordering::Greater),
+        "$gte" => cmp_any(|o| o != Ordering::Less),
+        "$lt" => cmp_any(|o| o == Ordering::Less),
+        "$lte" => cmp_any(|o| o != Ordering::Greater),
+        "$in" | "$nin" => {
+            let Bson::Array(arr) = arg else {
+                return Err(anyhow::anyhow!("{} requires an array", op));
+            };
+            let found = arr.iter().any(|a| matches_eq(values, a));
+            if op == "$in" {
+                found
+            } else {
+                !found
+            }
+        }
+        "$exists" => {
+            let exists = !values.is_empty();
+            exists == bson_truthy(arg)
+        }
+        "$not" => {
+            let Bson::Document(ops) = arg else {
+                return Err(anyhow::anyhow!("$not requires a document"));
+            };
+            let mut all = true;
+            for (op, arg) in ops {
+                all = all && matches_operator(doc, segments, values, op, arg)?;
+            }
+            !all
+        }
+        "$size" => {
+            let size = arg
+                .as_i64()
+                .or_else(|| arg.as_i32().map(|s| s as i64))
+                .ok_or(anyhow::anyhow!("$size requires a number"))?;
+            direct_values(doc, segments)
+                .iter()
+                .any(|v| matches!(v, Bson::Array(arr) if arr.len() as i64 == size))
+        }
+        "$elemMatch" => {
+            let Bson::Document(sub) = arg else {
+                return Err(anyhow::anyhow!("$elemMatch requires a document"));
+            };
+            let mut found = false;
+            for value in direct_values(doc, segments) {
+                let Bson::Array(arr) = value else {
+                    continue;
+                };
+                for item in arr {
+                    let is_match = match (item, is_operator_doc(arg)) {
+                        (_, true) => matches_path(&doc_with("v", item), "v", arg)?,
+                        (Bson::Document(d), false) => matches(d, sub)?,
+                        _ => false,
+                    };
+                    if is_match {
+                        found = true;
+                        break;
+                    }
+                }
+            }
+            found
+        }
+        _ => return Err(anyhow::anyhow!("unsupported query operator: {}", op)),
+    };
+    Ok(result)
+}
+
+fn doc_with(key: &str, value: &Bson) -> Document {
+    let mut doc = Document::new();
+    doc.insert(key, value.clone());
+    doc
+}
+
+/// Values reached by following the path, descending into arrays.
+/// When `expand` is set a terminal array contributes its elements as well.
+fn lookup_in_doc<'a>(doc: &'a Document, path: &[&str], expand: bool, out: &mut Vec<&'a Bson>) {
+    let Some((head, rest)) = path.split_first() else {
+        return;
+    };
+    if let Some(value) = doc.get(*head) {
+        lookup(value, rest, expand, out);
+    }
+}
+
+fn lookup<'a>(value: &'a Bson, path: &[&str], expand: bool, out: &mut Vec<&'a Bson>) {
+    let Some((head, rest)) = path.split_first() else {
+        out.push(value);
+        if let (true, Bson::Array(arr)) = (expand, value) {
+            out.extend(arr.iter());
+        }
+        return;
+    };
+    match value {
+        Bson::Document(d) => {
+            if let Some(v) = d.get(*head) {
+                lookup(v, rest, expand, out);
+            }
+        }
+        Bson::Array(arr) => {
+            if let Ok(idx) = head.parse::<usize>() {
+                if let Some(v) = arr.get(idx) {
+                    lookup(v, rest, expand, out);
+                }
+            }
+            for item in arr.iter().filter(|i| matches!(i, Bson::Document(_))) {
+                lookup(item, path, expand, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Values reached by following the path without expanding a terminal array
+fn direct_values<'a>(doc: &'a Document, path: &[&str]) -> Vec<&'a Bson> {
+    let mut out = vec![];
+    lookup_in_doc(doc, path, false, &mut out);
+    out
+}
+
+/// Get a single value by dotted path without any array expansion
+pub fn get_path<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut segments = path.split('.');
+    let mut value = doc.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Bson::Document(d) => d.get(segment)?,
+            Bson::Array(arr) => arr.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// Set a value by dotted path creating the intermediate documents
+pub fn set_path(doc: &mut Document, path: &str, value: Bson) -> anyhow::Result<()> {
+    let Some((head, rest)) = path.split_once('.') else {
+        doc.insert(path, value);
+        return Ok(());
+    };
+    let child = doc
+        .entry(head.to_string())
+        .or_insert_with(|| Bson::Document(Document::new()));
+    match child {
+        Bson::Document(d) => set_path(d, rest, value),
+        Bson::Null => {
+            let mut d = Document::new();
+            set_path(&mut d, rest, value)?;
+            *child = Bson::Document(d);
+            Ok(())
+        }
+        _ => Err(anyhow::anyhow!("cannot create field {} in {}", rest, head)),
+    }
+}
+
+/// Remove a value by dotted path
+pub fn remove_path(doc: &mut Document, path: &str) {
+    let Some((head, rest)) = path.split_once('.') else {
+        doc.remove(path);
+        return;
+    };
+    if let Some(Bson::Document(d)) = doc.get_mut(head) {
+        remove_path(d, rest);
+    }
+}
+
+pub fn as_f64(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(v) => Some(*v as f64),
+        Bson::Int64(v) => Some(*v as f64),
+        Bson::Double(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn as_i64(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(v) => Some(*v as i64),
+        Bson::Int64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+pub fn bson_truthy(value: &Bson) -> bool {
+    match value {
+        Bson::Boolean(b) => *b,
+        Bson::Null | Bson::Undefined => false,
+        v => as_f64(v).map(|n| n != 0.0).unwrap_or(true),
+    }
+}
+
+/// Equality with numbers of different types considered equal by value
+pub fn bson_eq(a: &Bson, b: &Bson) -> bool {
+    if let (Some(x), Some(y)) = (as_i64(a), as_i64(b)) {
+        return x == y;
+    }
+    if let (Some(x), Some(y)) = (as_f64(a), as_f64(b)) {
+        return x == y;
+    }
+    match (a, b) {
+        (Bson::Document(x), Bson::Document(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y.iter())
+                    .all(|((k1, v1), (k2, v2))| k1 == k2 && bson_eq(v1, v2))
+        }
+        (Bson::Array(x), Bson::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(v1, v2)| bson_eq(v1, v2))
+        }
+        _ => a == b,
+    }
+}
+
+/// Compare two values of the same kind, None when they are not comparable
+pub fn bson_cmp(a: &Bson, b: &Bson) -> Option<Ordering> {
+    if let (Some(x), Some(y)) = (as_i64(a), as_i64(b)) {
+        return Some(x.cmp(&y));
+    }
+    if let (Some(x), Some(y)) = (as_f64(a), as_f64(b)) {
+        return x.partial_cmp(&y);
+    }
+    match (a, b) {
+        (Bson::String(x), Bson::String(y)) => Some(x.cmp(y)),
+        (Bson::Boolean(x), Bson::Boolean(y)) => Some(x.cmp(y)),
+        (Bson::ObjectId(x), Bson::ObjectId(y)) => Some(x.bytes().cmp(&y.bytes())),
+        (Bson::DateTime(x), Bson::DateTime(y)) => Some(x.cmp(y)),
+        (Bson::Timestamp(x), Bson::Timestamp(y)) => {
+            Some((x.time, x.increment).cmp(&(y.time, y.increment)))
+        }
+        (Bson::Null, Bson::Null) => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn type_rank(value: &Bson) -> u8 {
+    match value {
+        Bson::Null | Bson::Undefined => 1,
+        Bson::Int32(_) | Bson::Int64(_) | Bson::Double(_) | Bson::Decimal128(_) => 2,
+        Bson::String(_) | Bson::Symbol(_) => 3,
+        Bson::Document(_) => 4,
+        Bson::Array(_) => 5,
+        Bson::Binary(_) => 6,
+        Bson::ObjectId(_) => 7,
+        Bson::Boolean(_) => 8,
+        Bson::DateTime(_) => 9,
+        Bson::Timestamp(_) => 10,
+        _ => 11,
+    }
+}
+
+/// Total order across all types, used for sorting.
+/// Missing values are sorted like null.
+pub fn sort_cmp(a: Option<&Bson>, b: Option<&Bson>) -> Ordering {
+    let a = a.unwrap_or(&Bson::Null);
+    let b = b.unwrap_or(&Bson::Null);
+    match type_rank(a).cmp(&type_rank(b)) {
+        Ordering::Equal => bson_cmp(a, b).unwrap_or(Ordering::Equal),
+        other => other,
+    }
+}
+
+/// Sort documents according to a mongodb sort specification
+pub fn sort_documents(docs: &mut [Document], sort: &Document) -> anyhow::Result<()> {
+    let mut keys = vec![];
+    for (key, direction) in sort {
+        let desc = match as_i64(direction) {
+            Some(1) => false,
+            Some(-1) => true,
+            _ => return Err(anyhow::anyhow!("invalid sort direction for {}", key)),
+        };
+        keys.push((key.clone(), desc));
+    }
+    docs.sort_by(|a, b| {
+        for (key, desc) in keys.iter() {
+            let ord = sort_cmp(get_path(a, key), get_path(b, key));
+            let ord = if *desc { ord.reverse() } else { ord };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn test_matches() {
+        let d = doc! {
+            "id": 1_i64,
+            "status": "ACTIVE",
+            "balance": {"real": 100_i64, "bonus": 0},
+            "tags": ["a", "b"],
+            "answers": [{"questionNo": 1, "isCorrect": true}, {"questionNo": 2}],
+        };
+        assert!(matches(&d, &doc! {"id": 1}).unwrap());
+        assert!(matches(&d, &doc! {"balance.real": {"$gte": 100.0}}).unwrap());
+        assert!(!matches(&d, &doc! {"balance.real": {"$gt": 100}}).unwrap());
+        assert!(matches(&d, &doc! {"tags": "b"}).unwrap());
+        assert!(matches(&d, &doc! {"answers.questionNo": 2}).unwrap());
+        assert!(matches(&d, &doc! {"status": {"$in": ["CREATED", "ACTIVE"]}}).unwrap());
+        assert!(matches(&d, &doc! {"missing": null}).unwrap());
+        assert!(matches(&d, &doc! {"missing": {"$exists": false}}).unwrap());
+        assert!(matches(&d, &doc! {"$or": [{"id": 2}, {"status": "ACTIVE"}]}).unwrap());
+        assert!(matches(
+            &d,
+            &doc! {"answers": {"$elemMatch": {"questionNo": 1, "isCorrect": true}}}
+        )
+        .unwrap());
+        assert!(!matches(
+            &d,
+            &doc! {"answers": {"$elemMatch": {"questionNo": 2, "isCorrect": true}}}
+        )
+        .unwrap());
+        assert!(matches(&d, &doc! {"$where": "1"}).is_err());
+    }
+
+    #[test]
+    fn test_sort_documents() {
+        let mut docs = vec![
+            doc! {"score": 2, "ts": 5},
+            doc! {"score": 3, "ts": 9},
+            doc! {"score": 2, "ts": 1},
+            doc! {"ts": 0},
+        ];
+        sort_documents(&mut docs, &doc! {"score": -1, "ts": 1}).unwrap();
+        let ts = docs
+            .iter()
+            .map(|d| d.get_i32("ts").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ts, vec![9, 1, 5, 0]);
+    }
+}
@@ -0,0 +1,304 @@
+use mongodb::{
+    bson::{Bson, Document},
+    options::UpdateModifications,
+};
+
+use super::{
+    aggregate::{arith, eval, Vars},
+    query::{bson_eq, matches, remove_path, set_path, sort_cmp},
+};
+
+/// Apply update modifications to the document.
+/// `is_insert` is set when the document is being created by an upsert.
+pub fn apply_update(
+    doc: &mut Document,
+    update: &UpdateModifications,
+    array_filters: &[Document],
+    is_insert: bool,
+) -> anyhow::Result<()> {
+    match update {
+        UpdateModifications::Document(update) => {
+            apply_operators(doc, update, array_filters, is_insert)
+        }
+        UpdateModifications::Pipeline(pipeline) => apply_pipeline(doc, pipeline),
+        _ => Err(anyhow::anyhow!("unsupported update modification")),
+    }
+}
+
+fn apply_operators(
+    doc: &mut Document,
+    update: &Document,
+    array_filters: &[Document],
+    is_insert: bool,
+) -> anyhow::Result<()> {
+    for (op, fields) in update {
+        let Bson::Document(fields) = fields else {
+            return Err(anyhow::anyhow!(
+                "update operator {} requires a document",
+                op
+            ));
+        };
+        for (path, value) in fields {
+            if path == "_id" && !is_insert && op != "$setOnInsert" {
+                return Err(anyhow::anyhow!("the field _id is immutable"));
+            }
+            match op.as_str() {
+                "$set" => modify(doc, path, array_filters, |slot| {
+                    *slot = value.clone();
+                    Ok(())
+                })?,
+                "$setOnInsert" => {
+                    if is_insert {
+                        modify(doc, path, array_filters, |slot| {
+                            *slot = value.clone();
+                            Ok(())
+                        })?
+                    }
+                }
+                "$unset" => remove_path(doc, path),
+                "$inc" => modify(doc, path, array_filters, |slot| {
+                    let current = match &*slot {
+                        Bson::Null => Bson::Int32(0),
+                        v => v.clone(),
+                    };
+                    *slot = arith("$add", &current, value)?;
+                    Ok(())
+                })?,
+                "$min" | "$max" => modify(doc, path, array_filters, |slot| {
+                    let replace = match &*slot {
+                        Bson::Null => true,
+                        v => {
+                            let ord = sort_cmp(Some(value), Some(v));
+                            (op == "$min" && ord.is_lt()) || (op == "$max" && ord.is_gt())
+                        }
+                    };
+                    if replace {
+                        *slot = value.clone();
+                    }
+                    Ok(())
+                })?,
+                "$push" | "$addToSet" => modify(doc, path, array_filters, |slot| {
+                    let items = match value {
+                        Bson::Document(d) if d.contains_key("$each") => match d.get("$each") {
+                            Some(Bson::Array(items)) => items.clone(),
+                            _ => return Err(anyhow::anyhow!("$each requires an array")),
+                        },
+                        v => vec![v.clone()],
+                    };
+                    if let Bson::Null = slot {
+                        *slot = Bson::Array(vec![]);
+                    }
+                    let Bson::Array(arr) = slot else {
+                        return Err(anyhow::anyhow!("{} requires an array field {}", op, path));
+                    };
+                    for item in items {
+                        if op == "$push" || !arr.iter().any(|a| bson_eq(a, &item)) {
+                            arr.push(item);
+                        }
+                    }
+                    Ok(())
+                })?,
+                "$pull" => modify(doc, path, array_filters, |slot| {
+                    let Bson::Array(arr) = slot else {
+                        return Ok(());
+                    };
+                    let mut kept = vec![];
+                    for item in arr.drain(..) {
+                        let remove = match (value, &item) {
+                            (Bson::Document(cond), Bson::Document(d)) => matches(d, cond)?,
+                            (v, item) => bson_eq(v, item),
+                        };
+                        if !remove {
+                            kept.push(item);
+                        }
+                    }
+                    *arr = kept;
+                    Ok(())
+                })?,
+                _ => return Err(anyhow::anyhow!("unsupported update operator: {}", op)),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_pipeline(doc: &mut Document, pipeline: &[Document]) -> anyhow::Result<()> {
+    for stage in pipeline {
+        for (name, spec) in stage {
+            match (name.as_str(), spec) {
+                ("$set" | "$addFields", Bson::Document(fields)) => {
+                    let root = doc.clone();
+                    for (path, expr) in fields {
+                        let value = eval(expr, &root, &Vars::new())?;
+                        set_path(doc, path, value)?;
+                    }
+                }
+                ("$unset", Bson::String(path)) => remove_path(doc, path),
+                ("$unset", Bson::Array(paths)) => {
+                    for path in paths.iter().filter_map(|p| p.as_str()) {
+                        remove_path(doc, path);
+                    }
+                }
+                _ => return Err(anyhow::anyhow!("unsupported update stage: {}", name)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Call `f` with every slot addressed by the path.
+/// Missing slots are created as null, `$[]` and `$[<identifier>]` address array elements.
+fn modify<F>(
+    doc: &mut Document,
+    path: &str,
+    array_filters: &[Document],
+    mut f: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&mut Bson) -> anyhow::Result<()>,
+{
+    let segments = path.split('.').collect::<Vec<_>>();
+    let mut root = Bson::Document(std::mem::take(doc));
+    let result = modify_value(&mut root, &segments, array_filters, &mut f);
+    if let Bson::Document(d) = root {
+        *doc = d;
+    }
+    result
+}
+
+fn modify_value(
+    value: &mut Bson,
+    path: &[&str],
+    array_filters: &[Document],
+    f: &mut dyn FnMut(&mut Bson) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let Some((head, rest)) = path.split_first() else {
+        return f(value);
+    };
+    if let Bson::Null = value {
+        *value = Bson::Document(Document::new());
+    }
+    match value {
+        Bson::Document(d) => {
+            let child = d.entry(head.to_string()).or_insert(Bson::Null);
+            modify_value(child, rest, array_filters, f)
+        }
+        Bson::Array(arr) => {
+            let indexes = if *head == "$[]" {
+                (0..arr.len()).collect::<Vec<_>>()
+            } else if head.starts_with("$[") && head.ends_with(']') {
+                let ident = &head[2..head.len() - 1];
+                let filter = array_filter(array_filters, ident)?;
+                let mut indexes = vec![];
+                for (idx, item) in arr.iter().enumerate() {
+                    let mut wrapper = Document::new();
+                    wrapper.insert(ident, item.clone());
+                    if matches(&wrapper, filter)? {
+                        indexes.push(idx);
+                    }
+                }
+                indexes
+            } else if let Ok(idx) = head.parse::<usize>() {
+                while arr.len() <= idx {
+                    arr.push(Bson::Null);
+                }
+                vec![idx]
+            } else {
+                return Err(anyhow::anyhow!(
+                    "cannot use the part {} to traverse an array",
+                    head
+                ));
+            };
+            for idx in indexes {
+                modify_value(&mut arr[idx], rest, array_filters, f)?;
+            }
+            Ok(())
+        }
+        _ => Err(anyhow::anyhow!(
+            "cannot create field {} in a non document value",
+            head
+        )),
+    }
+}
+
+fn array_filter<'a>(array_filters: &'a [Document], ident: &str) -> anyhow::Result<&'a Document> {
+    let prefix = format!("{}.", ident);
+    array_filters
+        .iter()
+        .find(|f| f.keys().any(|k| k == ident || k.starts_with(&prefix)))
+        .ok_or(anyhow::anyhow!(
+            "no array filter found for identifier {}",
+            ident
+        ))
+}
+
+/// Build the document inserted by an upsert from the equality conditions of the filter
+pub fn upsert_seed(filter: &Document) -> anyhow::Result<Document> {
+    let mut doc = Document::new();
+    for (key, cond) in filter {
+        if key.starts_with('$') {
+            if key == "$and" {
+                if let Bson::Array(items) = cond {
+                    for item in items.iter().filter_map(|i| i.as_document()) {
+                        for (k, v) in upsert_seed(item)? {
+                            set_path(&mut doc, &k, v)?;
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+        match cond {
+            Bson::Document(d) if d.keys().any(|k| k.starts_with('$')) => {
+                if let Some(v) = d.get("$eq") {
+                    set_path(&mut doc, key, v.clone())?;
+                }
+            }
+            v => set_path(&mut doc, key, v.clone())?,
+        }
+    }
+    Ok(doc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::memory::query::get_path;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn test_apply_operators() {
+        let mut d = doc! {"id": 1, "balance": {"real": 10_i64}, "answers": [{"no": 1}, {"no": 2}]};
+        let update = doc! {
+            "$set": {"answers.$[elem].ok": true, "status": "DONE"},
+            "$inc": {"balance.real": 5_i64, "balance.bonus": 3},
+            "$push": {"tags": "x"},
+        };
+        let filters = vec![doc! {"elem.no": 2}];
+        apply_update(&mut d, &update.into(), &filters, false).unwrap();
+        assert_eq!(get_path(&d, "balance.real"), Some(&Bson::Int64(15)));
+        assert_eq!(get_path(&d, "balance.bonus"), Some(&Bson::Int32(3)));
+        assert_eq!(get_path(&d, "answers.1.ok"), Some(&Bson::Boolean(true)));
+        assert_eq!(get_path(&d, "answers.0.ok"), None);
+        assert_eq!(d.get_array("tags").unwrap().len(), 1);
+        assert_eq!(d.get_str("status").unwrap(), "DONE");
+    }
+
+    #[test]
+    fn test_apply_pipeline() {
+        let mut d = doc! {"balance": {"real": 10_i64, "withdrawable": 8_i64}};
+        let update = vec![
+            doc! {"$set": {"balance.real": {"$cond": [{"$gte": ["$balance.real", 4_i64]}, {"$subtract": ["$balance.real", 4_i64]}, "$balance.real"]}}},
+            doc! {"$set": {"balance.withdrawable": {"$cond": [{"$gt": ["$balance.withdrawable", "$balance.real"]}, "$balance.real", "$balance.withdrawable"]}}},
+        ];
+        apply_update(&mut d, &update.into(), &[], false).unwrap();
+        assert_eq!(get_path(&d, "balance.real"), Some(&Bson::Int64(6)));
+        assert_eq!(get_path(&d, "balance.withdrawable"), Some(&Bson::Int64(6)));
+    }
+
+    #[test]
+    fn test_upsert_seed() {
+        let seed = upsert_seed(&doc! {"userId": 5, "status": {"$in": ["A"]}}).unwrap();
+        assert_eq!(seed, doc! {"userId": 5});
+    }
+}
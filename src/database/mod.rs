@@ -0,0 +1,364 @@
+mod memory;
+mod mongo;
+mod storage;
+
+use futures::future::BoxFuture;
+use mongodb::{
+    bson::{from_document, to_document, Document},
+    options::*,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::config::MongoConfig;
+
+pub use memory::{MemorySession, MemoryStorage};
+pub use mongo::MongoStorage;
+pub use storage::{DbSession, Storage};
+
+/// Application database handle.
+/// Typed operations are (de)serialized here and executed by the underlying storage backend.
+pub struct AppDatabase(Box<dyn Storage>);
+
+#[derive(Debug)]
+pub struct UpdateResult {
+    pub matched_count: u64,
+    pub modified_count: u64,
+    pub upserted_id: Option<String>,
+}
+
+/// The write was refused by a unique index.
+/// MongoDB and the memory storage both report it with the `E11000` error message.
+pub fn is_duplicate_key_error(err: &anyhow::Error) -> bool {
+    err.to_string().contains("E11000")
+}
+
+fn from_documents<T>(docs: Vec<Document>) -> anyhow::Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    docs.into_iter()
+        .map(|doc| from_document::<T>(doc).map_err(|e| e.into()))
+        .collect()
+}
+
+fn from_optional_document<T>(doc: Option<Document>) -> anyhow::Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    let Some(doc) = doc else {
+        return Ok(None);
+    };
+    Ok(Some(from_document::<T>(doc)?))
+}
+
+impl AppDatabase {
+    /// Connect to mongodb with the parameters of the config
+    pub async fn new(config: &MongoConfig) -> anyhow::Result<Self> {
+        let storage = MongoStorage::new(config).await?;
+        Ok(Self::with_storage(storage))
+    }
+
+    /// Create a database which keeps all the data in memory
+    pub fn in_memory() -> Self {
+        Self::with_storage(MemoryStorage::new())
+    }
+
+    pub fn with_storage(storage: impl Storage + 'static) -> Self {
+        Self(Box::new(storage))
+    }
+
+    pub async fn find_one<T>(
+        &self,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOneOptions>,
+    ) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + 'static,
+    {
+        let doc = self.0.find_one(None, db, coll, filter, options).await?;
+        from_optional_document(doc)
+    }
+
+    pub async fn find<T>(
+        &self,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> anyhow::Result<Vec<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + 'static,
+    {
+        let docs = self.0.find(None, db, coll, filter, options).await?;
+        from_documents(docs)
+    }
+
+    pub async fn count_documents(
+        &self,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<CountOptions>,
+    ) -> anyhow::Result<u64> {
+        self.0
+            .count_documents(None, db, coll, filter, options)
+            .await
+    }
+
+    pub async fn find_one_and_update<T>(
+        &self,
+        db: &str,
+        coll: &str,
+        filter: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<FindOneAndUpdateOptions>,
+    ) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let doc = self
+            .0
+            .find_one_and_update(None, db, coll, filter, update.into(), options)
+            .await?;
+        from_optional_document(doc)
+    }
+
+    pub async fn insert_one<T>(
+        &self,
+        db: &str,
+        coll: &str,
+        doc: &T,
+        options: Option<InsertOneOptions>,
+    ) -> anyhow::Result<String>
+    where
+        T: Serialize + 'static,
+    {
+        let doc = to_document(doc)?;
+        self.0.insert_one(None, db, coll, doc, options).await
+    }
+
+    pub async fn insert_many<T>(
+        &self,
+        db: &str,
+        coll: &str,
+        doc: &[T],
+        options: Option<InsertManyOptions>,
+    ) -> anyhow::Result<Vec<String>>
+    where
+        T: Serialize + 'static,
+    {
+        let docs = doc
+            .iter()
+            .map(|d| to_document(d).map_err(|e| e.into()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.0.insert_many(None, db, coll, docs, options).await
+    }
+
+    pub async fn update_one(
+        &self,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        self.0
+            .update_one(None, db, coll, query, update.into(), options)
+            .await
+    }
+
+    pub async fn update_many(
+        &self,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        self.0
+            .update_many(None, db, coll, query, update.into(), options)
+            .await
+    }
+
+    pub async fn delete_many(
+        &self,
+        db: &str,
+        coll: &str,
+        query: Document,
+        options: Option<DeleteOptions>,
+    ) -> anyhow::Result<u64> {
+        self.0.delete_many(None, db, coll, query, options).await
+    }
+
+    pub async fn aggregate(
+        &self,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        options: Option<AggregateOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        self.0.aggregate(None, db, coll, pipeline, options).await
+    }
+
+    /// Run `f` inside a transaction and return its result.
+    /// The transaction is aborted when `f` returns an error.
+    pub async fn execute_transaction<F, T>(
+        &self,
+        session_options: Option<SessionOptions>,
+        transaction_options: Option<TransactionOptions>,
+        f: F,
+    ) -> anyhow::Result<T>
+    where
+        F: for<'a> Fn(&'a AppDatabase, &'a mut DbSession) -> BoxFuture<'a, anyhow::Result<T>>,
+    {
+        let mut session = self.0.start_session(session_options).await?;
+        self.0
+            .start_transaction(&mut session, transaction_options)
+            .await?;
+        let value = match f(self, &mut session).await {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::debug!("Abort transaction due to error: {:?}", err);
+                self.0.abort_transaction(&mut session).await?;
+                return Err(err);
+            }
+        };
+        self.0.commit_transaction(&mut session).await?;
+        Ok(value)
+    }
+
+    pub async fn insert_one_with_session<T>(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        doc: &T,
+        options: Option<InsertOneOptions>,
+    ) -> anyhow::Result<String>
+    where
+        T: Serialize + 'static,
+    {
+        let doc = to_document(doc)?;
+        self.0
+            .insert_one(Some(session), db, coll, doc, options)
+            .await
+    }
+
+    pub async fn insert_many_with_session<T>(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        doc: &[T],
+        options: Option<InsertManyOptions>,
+    ) -> anyhow::Result<Vec<String>>
+    where
+        T: Serialize + 'static,
+    {
+        let docs = doc
+            .iter()
+            .map(|d| to_document(d).map_err(|e| e.into()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        self.0
+            .insert_many(Some(session), db, coll, docs, options)
+            .await
+    }
+
+    pub async fn find_with_session<T>(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> anyhow::Result<Vec<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + 'static,
+    {
+        let docs = self
+            .0
+            .find(Some(session), db, coll, filter, options)
+            .await?;
+        from_documents(docs)
+    }
+
+    pub async fn find_one_with_session<T>(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOneOptions>,
+    ) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned + Unpin + Send + Sync + 'static,
+    {
+        let doc = self
+            .0
+            .find_one(Some(session), db, coll, filter, options)
+            .await?;
+        from_optional_document(doc)
+    }
+
+    pub async fn find_one_and_update_with_session<T>(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        filter: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<FindOneAndUpdateOptions>,
+    ) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let doc = self
+            .0
+            .find_one_and_update(Some(session), db, coll, filter, update.into(), options)
+            .await?;
+        from_optional_document(doc)
+    }
+
+    pub async fn update_one_with_session(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        self.0
+            .update_one(Some(session), db, coll, query, update.into(), options)
+            .await
+    }
+
+    pub async fn update_many_with_session(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: impl Into<UpdateModifications>,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        self.0
+            .update_many(Some(session), db, coll, query, update.into(), options)
+            .await
+    }
+
+    pub async fn aggregate_with_session(
+        &self,
+        session: &mut DbSession,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        options: Option<AggregateOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        self.0
+            .aggregate(Some(session), db, coll, pipeline, options)
+            .await
+    }
+}
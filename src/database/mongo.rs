@@ -0,0 +1,329 @@
+use axum::async_trait;
+use futures::stream::StreamExt;
+use mongodb::{
+    bson::{Bson, Document},
+    error::UNKNOWN_TRANSACTION_COMMIT_RESULT,
+    options::*,
+    results::UpdateResult as MongoUpdateResult,
+    Client, ClientSession, Collection,
+};
+use std::time::Duration;
+
+use super::{storage::Storage, DbSession, UpdateResult};
+use crate::{config::MongoConfig, constants::*};
+
+/// Storage backend talking to a mongodb deployment
+pub struct MongoStorage(pub Client);
+
+impl MongoStorage {
+    pub async fn new(config: &MongoConfig) -> anyhow::Result<Self> {
+        let timeout = Duration::from_secs(MONGO_CONN_TIMEOUT);
+        // create the mongodb client options
+        let mut client_options = ClientOptions::parse(&config.uri).await?;
+        client_options.max_pool_size = Some(config.max_pool_size);
+        client_options.min_pool_size = Some(config.min_pool_size);
+        client_options.connect_timeout = Some(timeout);
+        // create the client and return Result object
+        let client = Client::with_options(client_options)?;
+        Ok(Self(client))
+    }
+
+    fn collection(&self, db: &str, coll: &str) -> Collection<Document> {
+        self.0.database(db).collection::<Document>(coll)
+    }
+}
+
+fn client_session(session: &mut DbSession) -> anyhow::Result<&mut ClientSession> {
+    match session {
+        DbSession::Mongo(session) => Ok(session.as_mut()),
+        _ => Err(anyhow::anyhow!(
+            "session was not created by mongodb storage"
+        )),
+    }
+}
+
+fn object_id_hex(id: Bson) -> anyhow::Result<String> {
+    let Bson::ObjectId(oid) = id else {
+        tracing::debug!("Invalid inserted id: {:?}", id);
+        let err = anyhow::anyhow!("Not able to get the ObjectId value in string format");
+        return Err(err);
+    };
+    Ok(oid.to_hex())
+}
+
+fn to_update_result(result: MongoUpdateResult) -> anyhow::Result<UpdateResult> {
+    let upserted_id = match result.upserted_id {
+        None => None,
+        Some(uid) => Some(object_id_hex(uid)?),
+    };
+    let update_result = UpdateResult {
+        modified_count: result.modified_count,
+        matched_count: result.matched_count,
+        upserted_id,
+    };
+    Ok(update_result)
+}
+
+#[async_trait]
+impl Storage for MongoStorage {
+    async fn find_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOneOptions>,
+    ) -> anyhow::Result<Option<Document>> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.find_one_with_session(filter, options, session).await?
+            }
+            None => coll.find_one(filter, options).await?,
+        };
+        Ok(result)
+    }
+
+    async fn find(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        let coll = self.collection(db, coll);
+        let mut data = vec![];
+        match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                let mut cursor = coll.find_with_session(filter, options, session).await?;
+                while let Some(doc) = cursor.next(session).await {
+                    data.push(doc?);
+                }
+            }
+            None => {
+                let mut cursor = coll.find(filter, options).await?;
+                while let Some(doc) = cursor.next().await {
+                    data.push(doc?);
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    async fn count_documents(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<CountOptions>,
+    ) -> anyhow::Result<u64> {
+        let coll = self.collection(db, coll);
+        let count = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.count_documents_with_session(filter, options, session)
+                    .await?
+            }
+            None => coll.count_documents(filter, options).await?,
+        };
+        Ok(count)
+    }
+
+    async fn find_one_and_update(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Document,
+        update: UpdateModifications,
+        options: Option<FindOneAndUpdateOptions>,
+    ) -> anyhow::Result<Option<Document>> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.find_one_and_update_with_session(filter, update, options, session)
+                    .await?
+            }
+            None => coll.find_one_and_update(filter, update, options).await?,
+        };
+        Ok(result)
+    }
+
+    async fn insert_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        doc: Document,
+        options: Option<InsertOneOptions>,
+    ) -> anyhow::Result<String> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.insert_one_with_session(doc, options, session).await?
+            }
+            None => coll.insert_one(doc, options).await?,
+        };
+        object_id_hex(result.inserted_id)
+    }
+
+    async fn insert_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        docs: Vec<Document>,
+        options: Option<InsertManyOptions>,
+    ) -> anyhow::Result<Vec<String>> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.insert_many_with_session(docs, options, session)
+                    .await?
+            }
+            None => coll.insert_many(docs, options).await?,
+        };
+        let mut inserted_ids = result.inserted_ids.into_iter().collect::<Vec<_>>();
+        inserted_ids.sort_by_key(|(idx, _)| *idx);
+        Ok(inserted_ids
+            .into_iter()
+            .map(|(_, id)| match id {
+                Bson::ObjectId(oid) => oid.to_hex(),
+                _ => String::new(),
+            })
+            .collect())
+    }
+
+    async fn update_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.update_one_with_session(query, update, options, session)
+                    .await?
+            }
+            None => coll.update_one(query, update, options).await?,
+        };
+        to_update_result(result)
+    }
+
+    async fn update_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.update_many_with_session(query, update, options, session)
+                    .await?
+            }
+            None => coll.update_many(query, update, options).await?,
+        };
+        to_update_result(result)
+    }
+
+    async fn delete_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        options: Option<DeleteOptions>,
+    ) -> anyhow::Result<u64> {
+        let coll = self.collection(db, coll);
+        let result = match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                coll.delete_many_with_session(query, options, session)
+                    .await?
+            }
+            None => coll.delete_many(query, options).await?,
+        };
+        Ok(result.deleted_count)
+    }
+
+    async fn aggregate(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        options: Option<AggregateOptions>,
+    ) -> anyhow::Result<Vec<Document>> {
+        let coll = self.collection(db, coll);
+        let mut data = vec![];
+        match session {
+            Some(session) => {
+                let session = client_session(session)?;
+                let mut cursor = coll
+                    .aggregate_with_session(pipeline, options, session)
+                    .await?;
+                while let Some(doc) = cursor.next(session).await {
+                    data.push(doc?);
+                }
+            }
+            None => {
+                let mut cursor = coll.aggregate(pipeline, options).await?;
+                while let Some(doc) = cursor.next().await {
+                    data.push(doc?);
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    async fn start_session(&self, options: Option<SessionOptions>) -> anyhow::Result<DbSession> {
+        let session = self.0.start_session(options).await?;
+        Ok(DbSession::Mongo(Box::new(session)))
+    }
+
+    async fn start_transaction(
+        &self,
+        session: &mut DbSession,
+        options: Option<TransactionOptions>,
+    ) -> anyhow::Result<()> {
+        client_session(session)?.start_transaction(options).await?;
+        Ok(())
+    }
+
+    async fn commit_transaction(&self, session: &mut DbSession) -> anyhow::Result<()> {
+        let session = client_session(session)?;
+        loop {
+            let commit_result = session.commit_transaction().await;
+            if let Err(error) = commit_result.as_ref() {
+                if error.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) {
+                    continue;
+                }
+            }
+            commit_result?;
+            break;
+        }
+        Ok(())
+    }
+
+    async fn abort_transaction(&self, session: &mut DbSession) -> anyhow::Result<()> {
+        client_session(session)?.abort_transaction().await?;
+        Ok(())
+    }
+}
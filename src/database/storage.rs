@@ -0,0 +1,121 @@
+use axum::async_trait;
+use mongodb::{bson::Document, options::*, ClientSession};
+
+use super::{memory::MemorySession, UpdateResult};
+
+/// Session handle returned by a storage backend.
+/// A session must only be used with the backend which created it.
+pub enum DbSession {
+    Mongo(Box<ClientSession>),
+    Memory(MemorySession),
+}
+
+/// Document level storage operations used by `AppDatabase`.
+/// All methods optionally take a session so that they can be part of a transaction.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn find_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOneOptions>,
+    ) -> anyhow::Result<Option<Document>>;
+
+    async fn find(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<FindOptions>,
+    ) -> anyhow::Result<Vec<Document>>;
+
+    async fn count_documents(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Option<Document>,
+        options: Option<CountOptions>,
+    ) -> anyhow::Result<u64>;
+
+    async fn find_one_and_update(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        filter: Document,
+        update: UpdateModifications,
+        options: Option<FindOneAndUpdateOptions>,
+    ) -> anyhow::Result<Option<Document>>;
+
+    async fn insert_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        doc: Document,
+        options: Option<InsertOneOptions>,
+    ) -> anyhow::Result<String>;
+
+    async fn insert_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        docs: Vec<Document>,
+        options: Option<InsertManyOptions>,
+    ) -> anyhow::Result<Vec<String>>;
+
+    async fn update_one(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult>;
+
+    async fn update_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        update: UpdateModifications,
+        options: Option<UpdateOptions>,
+    ) -> anyhow::Result<UpdateResult>;
+
+    async fn delete_many(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        query: Document,
+        options: Option<DeleteOptions>,
+    ) -> anyhow::Result<u64>;
+
+    async fn aggregate(
+        &self,
+        session: Option<&mut DbSession>,
+        db: &str,
+        coll: &str,
+        pipeline: Vec<Document>,
+        options: Option<AggregateOptions>,
+    ) -> anyhow::Result<Vec<Document>>;
+
+    async fn start_session(&self, options: Option<SessionOptions>) -> anyhow::Result<DbSession>;
+
+    async fn start_transaction(
+        &self,
+        session: &mut DbSession,
+        options: Option<TransactionOptions>,
+    ) -> anyhow::Result<()>;
+
+    async fn commit_transaction(&self, session: &mut DbSession) -> anyhow::Result<()>;
+
+    async fn abort_transaction(&self, session: &mut DbSession) -> anyhow::Result<()>;
+}
@@ -0,0 +1,142 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use crate::{
+    constants::EVENT_BUS_CAPACITY,
+    models::{Money, Notifications},
+    utils::get_epoch_ts,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum ContestEventType {
+    CONTEST_ACTIVATED,
+    CONTEST_STARTING_SOON,
+    CONTEST_ENDED,
+    RESULTS_READY,
+    PRIZE_CREDITED,
+    LEADERBOARD_DELTA,
+}
+
+/// Event of a contest pushed to the connected clients
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestEvent {
+    pub event_type: ContestEventType,
+    pub contest_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// player of `PRIZE_CREDITED` and `LEADERBOARD_DELTA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u32>,
+    /// prize amount of `PRIZE_CREDITED`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Money>,
+    /// total score of the player for `LEADERBOARD_DELTA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_answered: Option<u32>,
+    pub ts: u64,
+    /// the event is sent only to this user when set
+    #[serde(skip)]
+    pub recipient: Option<u32>,
+    /// event of a private contest, sent only to the players who joined the contest
+    #[serde(skip)]
+    pub is_private: bool,
+}
+
+impl ContestEvent {
+    pub fn new(event_type: ContestEventType, contest_id: &str) -> Self {
+        Self {
+            event_type,
+            contest_id: contest_id.to_string(),
+            title: None,
+            user_id: None,
+            amount: None,
+            score: None,
+            total_answered: None,
+            ts: get_epoch_ts(),
+            recipient: None,
+            is_private: false,
+        }
+    }
+
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_private(mut self, is_private: bool) -> Self {
+        self.is_private = is_private;
+        self
+    }
+
+    pub fn is_visible_to(&self, user_id: u32) -> bool {
+        self.recipient.is_none() || self.recipient == Some(user_id)
+    }
+}
+
+/// In-app notification delivered to a user
+#[derive(Debug, Clone)]
+pub struct NotificationEvent {
+    pub user_id: u32,
+    pub notification: Notifications,
+}
+
+/// In-process bus where the handlers and the jobs publish the contest events
+/// and the in-app notifications
+pub struct EventBus {
+    sender: broadcast::Sender<ContestEvent>,
+    notification_sender: broadcast::Sender<NotificationEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        let (notification_sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self {
+            sender,
+            notification_sender,
+        }
+    }
+}
+
+impl EventBus {
+    /// Publish the event to all the subscribers, the event is dropped when nobody listens
+    pub fn publish(&self, event: ContestEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ContestEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish_notification(&self, event: NotificationEvent) {
+        let _ = self.notification_sender.send(event);
+    }
+
+    pub fn subscribe_notifications(&self) -> broadcast::Receiver<NotificationEvent> {
+        self.notification_sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_bus() {
+        let events = EventBus::default();
+        events.publish(ContestEvent::new(ContestEventType::CONTEST_ENDED, "c1"));
+        let mut receiver = events.subscribe();
+        let mut prize = ContestEvent::new(ContestEventType::PRIZE_CREDITED, "c1");
+        prize.recipient = Some(2);
+        events.publish(prize);
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.event_type, ContestEventType::PRIZE_CREDITED);
+        assert!(event.is_visible_to(2));
+        assert!(!event.is_visible_to(1));
+    }
+}
@@ -21,7 +21,8 @@ use crate::{
     request_body = CreateClipReqBody,
     responses(
         (status = StatusCode::OK, description = "Clip created", body = ClipResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -30,6 +31,7 @@ pub async fn create_clip_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<CreateClipReqBody>,
 ) -> Result<Json<ClipResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
     check_duplicate_name(&db, &body.name).await?;
     let ts = get_epoch_ts();
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use crate::{
     constants::*,
     database::AppDatabase,
+    events::{ContestEvent, ContestEventType, EventBus},
     jwt::JwtClaimsAdmin,
     models::*,
     utils::{get_epoch_ts, parse_object_id, AppError},
@@ -19,15 +20,18 @@ use crate::{
     request_body = ContestIdRequest,
     responses(
         (status = StatusCode::OK, description = "Contest activated", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
 pub async fn activate_contest_handler(
     claims: JwtClaimsAdmin,
     State(db): State<Arc<AppDatabase>>,
+    State(events): State<Arc<EventBus>>,
     Json(body): Json<ContestIdRequest>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
     let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
     let filter = doc! {"_id": contest_id};
@@ -67,6 +71,9 @@ pub async fn activate_contest_handler(
         }
     };
     update_contest(&db, filter, update).await?;
+    let event = ContestEvent::new(ContestEventType::CONTEST_ACTIVATED, &body.contest_id)
+        .with_private(contest.props.is_private);
+    events.publish(event.with_title(&contest.props.title));
     let res = GenericResponse {
         success: true,
         message: "Updated successfully".to_owned(),
@@ -83,7 +90,8 @@ pub async fn activate_contest_handler(
     request_body = ContestIdRequest,
     responses(
         (status = StatusCode::OK, description = "Contest inactivated", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -92,6 +100,7 @@ pub async fn inactivate_contest_handler(
     State(db): State<Arc<AppDatabase>>,
     Json(body): Json<ContestIdRequest>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
     let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
     let filter = doc! {"_id": contest_id};
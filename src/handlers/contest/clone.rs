@@ -0,0 +1,281 @@
+use axum::{extract::State, Json};
+use mongodb::{bson::doc, options::FindOptions};
+use std::sync::Arc;
+
+use super::create::validate_body;
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{get_epoch_ts, parse_object_id, AppError, ValidatedBody},
+};
+
+/// Contest clone
+///
+/// Create a new contest in CREATED status with the props and the active questions of an
+/// existing contest. `sourceId` is the contest id.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/clone",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = CopyContestReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Contest created", body = ContestResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn clone_contest_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<CopyContestReqBody>,
+) -> Result<Json<ContestResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let contest = get_contest_with_questions(&db, &body.source_id).await?;
+    let questions = contest.questions.unwrap_or_default();
+    let contest = insert_contest_copy(&db, contest.props, questions, &body, claims.data.id).await?;
+    let res = ContestResponse {
+        success: true,
+        data: contest,
+    };
+    Ok(Json(res))
+}
+
+/// Save contest template
+///
+/// Save the props and the active questions of a contest as a template
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/template",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = SaveTemplateReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Template saved", body = ContestTemplateResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn save_template_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<SaveTemplateReqBody>,
+) -> Result<Json<ContestTemplateResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let filter = doc! {"name": &body.name};
+    let duplicate = db
+        .find_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), None)
+        .await?;
+    if duplicate.is_some() {
+        let err = AppError::BadRequestErr("Duplicate template name".into());
+        return Err(err);
+    }
+    let contest = get_contest_with_questions(&db, &body.contest_id).await?;
+    let questions = contest.questions.unwrap_or_default();
+    let mut template = ContestTemplate {
+        _id: None,
+        name: body.name,
+        contest: contest.props,
+        questions: questions.into_iter().filter(|q| q.is_active).collect(),
+        schedule: None,
+        created_ts: Some(get_epoch_ts()),
+        created_by: Some(claims.data.id),
+    };
+    let inserted_id = db
+        .insert_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, &template, None)
+        .await?;
+    template._id = Some(inserted_id);
+    let res = ContestTemplateResponse {
+        success: true,
+        data: template,
+    };
+    Ok(Json(res))
+}
+
+/// Get contest templates
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/template",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Template list", body = GetContestTemplatesResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn get_templates_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+) -> Result<Json<GetContestTemplatesResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let options = FindOptions::builder().sort(Some(doc! {"name": 1})).build();
+    let data = db
+        .find::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, None, Some(options))
+        .await?;
+    let res = GetContestTemplatesResponse {
+        success: true,
+        data,
+    };
+    Ok(Json(res))
+}
+
+/// Create contest from template
+///
+/// Create a new contest in CREATED status from a template. `sourceId` is the template id.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/fromTemplate",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = CopyContestReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Contest created", body = ContestResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Template not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn contest_from_template_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<CopyContestReqBody>,
+) -> Result<Json<ContestResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let template_id = parse_object_id(&body.source_id, "Not able to parse templateId")?;
+    let filter = doc! {"_id": template_id};
+    let template = db
+        .find_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("template not found".into()))?;
+    let contest = insert_contest_copy(
+        &db,
+        template.contest,
+        template.questions,
+        &body,
+        claims.data.id,
+    )
+    .await?;
+    let res = ContestResponse {
+        success: true,
+        data: contest,
+    };
+    Ok(Json(res))
+}
+
+async fn get_contest_with_questions(
+    db: &Arc<AppDatabase>,
+    contest_id: &str,
+) -> Result<ContestWithQuestion, AppError> {
+    let contest_id = parse_object_id(contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": contest_id};
+    let contest = db
+        .find_one::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    Ok(contest)
+}
+
+/// Insert a new contest with the given props and the active questions. The title and the
+/// times are taken from the request and the contest is not scheduled for activation.
+pub async fn insert_contest_copy(
+    db: &Arc<AppDatabase>,
+    mut props: ContestProps,
+    questions: Vec<Question>,
+    body: &CopyContestReqBody,
+    user_id: u32,
+) -> Result<Contest, AppError> {
+    props.title = body.title.clone();
+    props.start_time = body.start_time;
+    props.end_time = body.end_time;
+    props.activate_at = None;
+    validate_body(db, &props, None).await?;
+    let mut contest = Contest::new(&props, user_id);
+    let questions = questions.into_iter().filter(|q| q.is_active).collect();
+    let contest_with_questions = ContestWithQuestion {
+        _id: None,
+        props,
+        status: contest.status.clone(),
+        invite_code: contest.invite_code.clone(),
+        joined_count: 0,
+        questions: Some(questions),
+        created_ts: contest.created_ts,
+        created_by: contest.created_by,
+        updated_ts: None,
+        updated_by: None,
+    };
+    let inserted_id = db
+        .insert_one::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, &contest_with_questions, None)
+        .await?;
+    contest._id = Some(inserted_id);
+    Ok(contest)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use chrono::Duration;
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_clone_and_templates() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        let mut props = contest_props(10, 100);
+        props.category = ContestCategory::Others;
+        let contest_id = insert_contest(&db, &props, ContestStatus::FINISHED, 3).await;
+        let start_time = props.start_time + Duration::days(7);
+        let end_time = props.end_time + Duration::days(7);
+        let copy_body = |source_id: &str, title: &str| {
+            json!({
+                "sourceId": source_id,
+                "title": title,
+                "startTime": start_time.timestamp(),
+                "endTime": end_time.timestamp()
+            })
+        };
+
+        let uri = "/api/v1/contest/clone";
+        let body = copy_body(&contest_id, &props.title);
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body = copy_body(&contest_id, "weekly quiz 2");
+        let (status, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["status"], "CREATED");
+        assert_eq!(res["data"]["startTime"], start_time.timestamp());
+        let clone_id = res["data"]["_id"].as_str().unwrap();
+        let uri = format!("/api/v1/question?contestId={clone_id}");
+        let (_, res) = send_admin_request(&db, Method::GET, &uri, 100, None).await;
+        assert_eq!(res["data"].as_array().unwrap().len(), 3);
+
+        let uri = "/api/v1/contest/template";
+        let body = json!({"contestId": contest_id, "name": "weekly quiz"});
+        let (status, res) =
+            send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        let template_id = res["data"]["_id"].as_str().unwrap().to_string();
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (_, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        assert_eq!(res["data"][0]["name"], "weekly quiz");
+
+        let uri = "/api/v1/contest/fromTemplate";
+        let body = copy_body(&template_id, "weekly quiz 3");
+        let (status, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["title"], "weekly quiz 3");
+        assert_eq!(res["data"]["entryFee"], 10);
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{extract::State, Json};
-use mongodb::bson::{doc, Document};
+use mongodb::bson::{doc, oid::ObjectId, Document};
 use std::sync::Arc;
 
 use crate::{
@@ -21,7 +21,8 @@ use crate::{
     request_body = ContestProps,
     responses(
         (status = StatusCode::OK, description = "Contest created", body = ContestResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -30,8 +31,9 @@ pub async fn create_contest_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<ContestProps>,
 ) -> Result<Json<ContestResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
-    validate_body(&db, &body).await?;
+    validate_body(&db, &body, None).await?;
     let mut contest = Contest::new(&body, claims.id);
     let inserted_id = db
         .insert_one::<Contest>(DB_NAME, COLL_CONTESTS, &contest, None)
@@ -44,9 +46,14 @@ pub async fn create_contest_handler(
     Ok(Json(res))
 }
 
-async fn validate_body(db: &Arc<AppDatabase>, body: &ContestProps) -> Result<(), AppError> {
+/// Validate the contest props, `contest_id` is the contest being updated if any
+pub async fn validate_body(
+    db: &Arc<AppDatabase>,
+    body: &ContestProps,
+    contest_id: Option<&ObjectId>,
+) -> Result<(), AppError> {
     let (duplicate_check, movie_id_check) = tokio::join!(
-        check_duplicate_title(&db, &body.title),
+        check_duplicate_title(db, &body.title, contest_id),
         validate_movie_id(&db, &body)
     );
     duplicate_check?;
@@ -54,12 +61,21 @@ async fn validate_body(db: &Arc<AppDatabase>, body: &ContestProps) -> Result<(),
     validate_entry_fee(&body)?;
     validate_prize_selection(&body)?;
     validate_prize_value(&body)?;
+    validate_prize_table(body)?;
     validate_start_end_time(&body)?;
+    validate_max_players(body)?;
     Ok(())
 }
 
-async fn check_duplicate_title(db: &Arc<AppDatabase>, title: &str) -> Result<(), AppError> {
-    let filter = doc! {"title": title};
+async fn check_duplicate_title(
+    db: &Arc<AppDatabase>,
+    title: &str,
+    contest_id: Option<&ObjectId>,
+) -> Result<(), AppError> {
+    let mut filter = doc! {"title": title};
+    if let Some(contest_id) = contest_id {
+        filter.insert("_id", doc! {"$ne": contest_id});
+    }
     let result = db
         .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
         .await?;
@@ -131,7 +147,10 @@ fn validate_prize_selection(body: &ContestProps) -> Result<(), AppError> {
 }
 
 fn validate_prize_value(body: &ContestProps) -> Result<(), AppError> {
-    if body.prize_value_real_money == 0 && body.prize_value_bonus_money == 0 {
+    if body.prize_table.is_empty()
+        && body.prize_value_real_money == 0
+        && body.prize_value_bonus_money == 0
+    {
         let msg = "prizeValueRealMoney & prizeValueBonusMoney both cannot be zero";
         let err = AppError::BadRequestErr(msg.into());
         return Err(err);
@@ -140,12 +159,105 @@ fn validate_prize_value(body: &ContestProps) -> Result<(), AppError> {
     Ok(())
 }
 
+fn validate_prize_table(body: &ContestProps) -> Result<(), AppError> {
+    let mut tiers = body.prize_table.iter().collect::<Vec<_>>();
+    tiers.sort_by_key(|tier| tier.rank_from);
+    let mut last_rank = 0;
+    let mut total_percentage = 0;
+    for tier in tiers {
+        if tier.rank_from > tier.rank_to {
+            let msg = "rankFrom should not be greater than rankTo in prizeTable";
+            return Err(AppError::BadRequestErr(msg.into()));
+        }
+        if tier.rank_from <= last_rank {
+            let msg = "rank ranges in prizeTable should not overlap";
+            return Err(AppError::BadRequestErr(msg.into()));
+        }
+        if tier.prize_value_real_money == 0
+            && tier.prize_value_bonus_money == 0
+            && tier.pool_percentage.is_none()
+        {
+            let msg = "prizeTable entry should have a prize value or a poolPercentage";
+            return Err(AppError::BadRequestErr(msg.into()));
+        }
+        last_rank = tier.rank_to;
+        // the percentages are admin input, an overflow is more than 100 percent anyway
+        total_percentage = tier
+            .pool_percentage
+            .unwrap_or_default()
+            .checked_mul(tier.ranks_count())
+            .and_then(|percentage| percentage.checked_add(total_percentage))
+            .unwrap_or(u32::MAX);
+    }
+    if total_percentage > 100 {
+        let msg = "prizeTable pays out more than 100 percent of the pool";
+        return Err(AppError::BadRequestErr(msg.into()));
+    }
+    if body.prize_selection == PrizeSelection::TOP_WINNERS
+        && last_rank > body.top_winners_count.unwrap_or_default()
+    {
+        let msg = "prizeTable ranks should not exceed topWinnersCount";
+        return Err(AppError::BadRequestErr(msg.into()));
+    }
+
+    Ok(())
+}
+
 fn validate_start_end_time(body: &ContestProps) -> Result<(), AppError> {
     if body.start_time >= body.end_time {
         let msg = "startTime should be less than endTime";
         let err = AppError::BadRequestErr(msg.into());
         return Err(err);
     }
+    if body
+        .activate_at
+        .is_some_and(|activate_at| activate_at >= body.end_time)
+    {
+        let msg = "activateAt should be less than endTime";
+        let err = AppError::BadRequestErr(msg.into());
+        return Err(err);
+    }
 
     Ok(())
 }
+
+fn validate_max_players(body: &ContestProps) -> Result<(), AppError> {
+    if body
+        .max_players
+        .is_some_and(|max_players| max_players < body.min_required_players)
+    {
+        let msg = "maxPlayers should not be less than minRequiredPlayers";
+        let err = AppError::BadRequestErr(msg.into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[test]
+    fn test_validate_prize_table() {
+        let tier = |rank_from, rank_to, pool_percentage| PrizeTier {
+            rank_from,
+            rank_to,
+            prize_value_real_money: 0,
+            prize_value_bonus_money: 0,
+            pool_percentage: Some(pool_percentage),
+        };
+        let mut props = contest_props(10, 0);
+        props.top_winners_count = Some(u32::MAX);
+        props.prize_table = vec![tier(1, 1, 50), tier(2, 6, 10)];
+        assert!(validate_prize_table(&props).is_ok());
+        props.prize_table = vec![tier(1, 1, 50), tier(2, 7, 10)];
+        assert!(validate_prize_table(&props).is_err());
+        // the percentages of a huge rank range do not wrap around to a small total
+        props.prize_table = vec![tier(1, 1, 50), tier(2, u32::MAX, 100)];
+        assert!(validate_prize_table(&props).is_err());
+        props.prize_table = vec![tier(1, 1 << 30, 4), tier((1 << 30) + 1, u32::MAX, 4)];
+        assert!(validate_prize_table(&props).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::Response,
+};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::leaderboard::get_visible_contest;
+use crate::{
+    database::AppDatabase,
+    events::{ContestEvent, ContestEventType, EventBus},
+    jwt::JwtClaims,
+    models::*,
+    utils::AppError,
+};
+
+/// Contest events channel
+///
+/// WebSocket channel pushing the lifecycle events of the contests (activated, starting soon,
+/// ended, results ready, prize credited) as JSON text messages. The leaderboard deltas and
+/// the events of a private contest are pushed only for the contest mentioned in `contestId`,
+/// which must be visible to the user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/events",
+    params(ContestEventsParams, ("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::SWITCHING_PROTOCOLS, description = "Stream of contest events", body = ContestEvent),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn contest_events_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    State(events): State<Arc<EventBus>>,
+    Query(params): Query<ContestEventsParams>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    if let Some(contest_id) = params.contest_id.as_deref() {
+        get_visible_contest(&db, contest_id, claims.id).await?;
+    }
+    let res =
+        ws.on_upgrade(move |socket| send_events(socket, events, claims.id, params.contest_id));
+    Ok(res)
+}
+
+async fn send_events(
+    mut socket: WebSocket,
+    events: Arc<EventBus>,
+    user_id: u32,
+    contest_id: Option<String>,
+) {
+    let mut receiver = events.subscribe();
+    loop {
+        tokio::select! {
+            event = receiver.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::debug!("contest events skipped for user {}: {}", user_id, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if !should_send(&event, user_id, contest_id.as_deref()) {
+                    continue;
+                }
+                let Ok(text) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            msg = socket.recv() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// `contest_id` is checked to be visible to the user when the socket is opened
+fn should_send(event: &ContestEvent, user_id: u32, contest_id: Option<&str>) -> bool {
+    if !event.is_visible_to(user_id) {
+        return false;
+    }
+    if event.recipient.is_some() {
+        return true;
+    }
+    let is_scoped = event.is_private || event.event_type == ContestEventType::LEADERBOARD_DELTA;
+    !is_scoped || contest_id == Some(event.contest_id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_send() {
+        let ended = ContestEvent::new(ContestEventType::CONTEST_ENDED, "c1");
+        assert!(should_send(&ended, 1, None));
+        let mut delta = ContestEvent::new(ContestEventType::LEADERBOARD_DELTA, "c1");
+        delta.user_id = Some(2);
+        assert!(!should_send(&delta, 1, None));
+        assert!(!should_send(&delta, 1, Some("c2")));
+        assert!(should_send(&delta, 1, Some("c1")));
+        let mut prize = ContestEvent::new(ContestEventType::PRIZE_CREDITED, "c1");
+        prize.recipient = Some(2);
+        assert!(!should_send(&prize, 1, Some("c1")));
+        assert!(should_send(&prize, 2, None));
+        let activated =
+            ContestEvent::new(ContestEventType::CONTEST_ACTIVATED, "c1").with_private(true);
+        assert!(!should_send(&activated, 1, None));
+        assert!(!should_send(&activated, 1, Some("c2")));
+        assert!(should_send(&activated, 1, Some("c1")));
+    }
+}
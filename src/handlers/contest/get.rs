@@ -43,7 +43,8 @@ pub async fn get_contest_handler(
 }
 
 fn get_query(params: &GetContestParams) -> Result<Document, AppError> {
-    let mut find_by = doc! {};
+    // private contests are reachable only with the invite code
+    let mut find_by = doc! {"isPrivate": {"$ne": true}};
     if let Some(id) = &params._id {
         let id = parse_object_id(id, "Not able to parse _id")?;
         find_by.insert("_id", id);
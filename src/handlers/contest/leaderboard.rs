@@ -0,0 +1,294 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use mongodb::{bson::doc, options::FindOneOptions};
+use std::{collections::HashMap, sync::Arc};
+use validator::Validate;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jobs::finalize_contest::finish_contest::{play_tracker_filter, rank_play_trackers},
+    jwt::JwtClaims,
+    models::*,
+    utils::{parse_object_id, AppError},
+};
+
+/// Get contest leaderboard
+///
+/// Ranks of the players of a contest. The ranks are live while the contest is ACTIVE and
+/// final once the contest is ENDED. `myRank` is the rank of the caller.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{id}/leaderboard",
+    params(
+        ("id" = String, Path, description = "Contest id"),
+        ContestRankParams,
+        ("authorization" = String, Header, description = "JWT token")
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest leaderboard", body = ContestLeaderboardResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Leaderboard not available or invalid pageSize or pageIndex", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn get_contest_leaderboard_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    Path(contest_id): Path<String>,
+    Query(params): Query<ContestRankParams>,
+) -> Result<Json<ContestLeaderboardResponse>, AppError> {
+    params
+        .validate()
+        .map_err(|err| AppError::BadRequestErr(err.to_string()))?;
+    let contest = get_visible_contest(&db, &contest_id, claims.id).await?;
+    let (is_final, play_trackers, prizes) = match contest.status {
+        ContestStatus::ENDED => {
+            let standings = get_final_standings(&db, &contest_id).await?;
+            let prizes = get_prizes(&standings.winners);
+            (true, standings.all_play_trackers, prizes)
+        }
+        ContestStatus::ACTIVE | ContestStatus::INACTIVE => {
+            let play_trackers = get_live_standings(&db, &contest).await?;
+            (false, play_trackers, HashMap::new())
+        }
+        _ => {
+            let err = AppError::BadRequestErr("leaderboard is not available".into());
+            return Err(err);
+        }
+    };
+    let my_rank = play_trackers.iter().find(|pt| pt.user_id == claims.id);
+    let page = get_page(&play_trackers, &params);
+    let mut entries = to_entries(&db, my_rank.into_iter().chain(page), &prizes).await?;
+    let my_rank = my_rank.map(|_| entries.remove(0));
+    let res = ContestLeaderboardResponse {
+        success: true,
+        is_final,
+        total_players: play_trackers.len() as u32,
+        my_rank,
+        data: entries,
+    };
+    Ok(Json(res))
+}
+
+/// Get contest results
+///
+/// Final ranks, scores, time taken and prizes of the players of an ENDED contest
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/{id}/results",
+    params(
+        ("id" = String, Path, description = "Contest id"),
+        ContestRankParams,
+        ("authorization" = String, Header, description = "JWT token")
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest results", body = ContestResultsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Contest is not ended or invalid pageSize or pageIndex", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn get_contest_results_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    Path(contest_id): Path<String>,
+    Query(params): Query<ContestRankParams>,
+) -> Result<Json<ContestResultsResponse>, AppError> {
+    params
+        .validate()
+        .map_err(|err| AppError::BadRequestErr(err.to_string()))?;
+    let contest = get_visible_contest(&db, &contest_id, claims.id).await?;
+    if contest.status != ContestStatus::ENDED {
+        let err = AppError::BadRequestErr("contest is not ended yet".into());
+        return Err(err);
+    }
+    let standings = get_final_standings(&db, &contest_id).await?;
+    let prizes = get_prizes(&standings.winners);
+    let play_trackers = standings.all_play_trackers;
+    let winners = to_entries(&db, standings.winners.iter(), &prizes).await?;
+    let my_result = play_trackers.iter().find(|pt| pt.user_id == claims.id);
+    let page = get_page(&play_trackers, &params);
+    let mut entries = to_entries(&db, my_result.into_iter().chain(page), &prizes).await?;
+    let my_result = my_result.map(|_| entries.remove(0));
+    let res = ContestResultsResponse {
+        success: true,
+        total_players: play_trackers.len() as u32,
+        winners,
+        my_result,
+        data: entries,
+    };
+    Ok(Json(res))
+}
+
+/// A private contest is visible only to the players who joined it
+pub async fn get_visible_contest(
+    db: &Arc<AppDatabase>,
+    contest_id: &str,
+    user_id: u32,
+) -> Result<Contest, AppError> {
+    let oid = parse_object_id(contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": oid};
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    if contest.props.is_private {
+        let filter = doc! {"contestId": contest_id, "userId": user_id};
+        db.find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await?
+            .ok_or(AppError::NotFound("contest not found".into()))?;
+    }
+    Ok(contest)
+}
+
+async fn get_final_standings(
+    db: &Arc<AppDatabase>,
+    contest_id: &str,
+) -> Result<ContestStandings, AppError> {
+    let oid = parse_object_id(contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": oid};
+    let options = FindOneOptions::builder()
+        .projection(Some(doc! {"allPlayTrackers": 1, "winners": 1}))
+        .build();
+    let standings = db
+        .find_one::<ContestStandings>(DB_NAME, COLL_CONTESTS, Some(filter), Some(options))
+        .await?
+        .unwrap_or_default();
+    Ok(standings)
+}
+
+/// Rank the players as per their score so far, time taken is known only for finished plays
+async fn get_live_standings(
+    db: &Arc<AppDatabase>,
+    contest: &Contest,
+) -> Result<Vec<PlayTracker>, AppError> {
+    let contest_id = contest._id.as_deref().unwrap_or_default();
+    let filter = play_tracker_filter(contest_id)?;
+    let mut play_trackers = db
+        .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+        .await?;
+    for pt in play_trackers.iter_mut() {
+        pt.time_taken = pt
+            .start_ts
+            .zip(pt.finish_ts)
+            .map(|(start, finish)| finish.saturating_sub(start) as u32);
+    }
+    rank_play_trackers(&mut play_trackers, contest.props.tie_policy);
+    Ok(play_trackers)
+}
+
+fn get_prizes(winners: &[PlayTracker]) -> HashMap<u32, Money> {
+    winners
+        .iter()
+        .filter_map(|pt| Some((pt.user_id, pt.prize_amount?)))
+        .collect()
+}
+
+fn get_page<'a>(
+    play_trackers: &'a [PlayTracker],
+    params: &ContestRankParams,
+) -> impl Iterator<Item = &'a PlayTracker> {
+    let page_index = params.page_index.unwrap_or(0) as usize;
+    let page_size = params.page_size.unwrap_or(MAX_PAGE_SIZE) as usize;
+    play_trackers
+        .iter()
+        .skip(page_index.saturating_mul(page_size))
+        .take(page_size)
+}
+
+async fn to_entries<'a>(
+    db: &Arc<AppDatabase>,
+    play_trackers: impl Iterator<Item = &'a PlayTracker>,
+    prizes: &HashMap<u32, Money>,
+) -> Result<Vec<ContestRankEntry>, AppError> {
+    let play_trackers = play_trackers.collect::<Vec<_>>();
+    let user_ids = play_trackers
+        .iter()
+        .map(|pt| pt.user_id)
+        .collect::<Vec<_>>();
+    let filter = doc! {"id": {"$in": user_ids}};
+    let names = db
+        .find::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.name))
+        .collect::<HashMap<_, _>>();
+    let entries = play_trackers
+        .into_iter()
+        .map(|pt| ContestRankEntry {
+            rank: pt.rank,
+            user_id: pt.user_id,
+            name: names.get(&pt.user_id).cloned(),
+            score: pt.score.unwrap_or_default(),
+            total_answered: pt.total_answered.unwrap_or_default(),
+            time_taken: pt.time_taken,
+            prize: prizes.get(&pt.user_id).copied(),
+        })
+        .collect();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use super::*;
+    use crate::{
+        events::EventBus,
+        jobs::finalize_contest::finish_contest::finish_contest,
+        utils::{get_epoch_ts, test_helper::*},
+    };
+
+    #[tokio::test]
+    async fn test_contest_leaderboard_and_results() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let ts = get_epoch_ts();
+        for (user_id, score) in [(1, 2), (2, 3), (3, 1)] {
+            let mut play_tracker = PlayTracker::new(user_id, &contest_id, 3);
+            play_tracker.status = PlayTrackerStatus::FINISHED;
+            play_tracker.score = Some(score);
+            play_tracker.start_ts = Some(ts - 100);
+            play_tracker.finish_ts = Some(ts - 50);
+            db.insert_one(DB_NAME, COLL_PLAY_TRACKERS, &play_tracker, None)
+                .await
+                .unwrap();
+        }
+
+        let uri = format!("/api/v1/contest/{contest_id}/results");
+        let (status, _) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let uri = format!("/api/v1/contest/{contest_id}/leaderboard?pageSize=2");
+        let (status, res) = send_request(&db, Method::GET, &uri, 3, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["isFinal"], false);
+        assert_eq!(res["totalPlayers"], 3);
+        assert_eq!(res["data"][0]["userId"], 2);
+        assert_eq!(res["data"].as_array().unwrap().len(), 2);
+        assert_eq!(res["myRank"]["rank"], 3);
+        assert_eq!(res["myRank"]["timeTaken"], 50);
+        let page_uri = format!("/api/v1/contest/{contest_id}/leaderboard?pageSize=100000");
+        let (status, _) = send_request(&db, Method::GET, &page_uri, 3, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let contest = get_contest(&db, &contest_id).await;
+        finish_contest(&db, &EventBus::default(), &contest).await;
+        let (_, res) = send_request(&db, Method::GET, &uri, 2, None).await;
+        assert_eq!(res["isFinal"], true);
+        assert_eq!(res["myRank"]["rank"], 1);
+        let uri = format!("/api/v1/contest/{contest_id}/results?pageIndex=1&pageSize=2");
+        let (status, res) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["winners"].as_array().unwrap().len(), 1);
+        assert_eq!(res["winners"][0]["userId"], 2);
+        assert_eq!(res["winners"][0]["prize"]["real"], 100);
+        assert_eq!(res["myResult"]["rank"], 2);
+        assert_eq!(res["data"][0]["userId"], 3);
+    }
+}
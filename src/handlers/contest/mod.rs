@@ -1,3 +1,9 @@
 pub mod activate;
+pub mod clone;
 pub mod create;
+pub mod events;
 pub mod get;
+pub mod leaderboard;
+pub mod private;
+pub mod schedule;
+pub mod update;
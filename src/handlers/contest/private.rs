@@ -0,0 +1,220 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use mongodb::{bson::doc, options::FindOptions};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    handlers::play_tracker::get::{insert_new_play_tracker, validate_contest},
+    jwt::{JwtClaims, JwtClaimsAdmin},
+    models::*,
+    utils::{parse_object_id, AppError, ValidatedBody},
+};
+
+/// Get private contest
+///
+/// Get a private contest by the invite code
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/invite",
+    params(InviteCodeReq, ("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest", body = ContestResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn get_invited_contest_handler(
+    _claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<InviteCodeReq>,
+) -> Result<Json<ContestResponse>, AppError> {
+    let filter = doc! {"inviteCode": &params.invite_code};
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    let res = ContestResponse {
+        success: true,
+        data: contest,
+    };
+    Ok(Json(res))
+}
+
+/// Join private contest
+///
+/// Join an active private contest with the invite code and get the PlayTracker
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/join",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    request_body = InviteCodeReq,
+    responses(
+        (status = StatusCode::OK, description = "Contest joined", body = PlayTrackerResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Contest is full or already joined", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn join_contest_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<InviteCodeReq>,
+) -> Result<Json<PlayTrackerResponse>, AppError> {
+    let filter = doc! {"inviteCode": &body.invite_code};
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    let contest_id = contest._id.unwrap_or_default();
+    let filter = doc! {"contestId": &contest_id, "userId": claims.id};
+    let joined = db
+        .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+        .await?;
+    if joined.is_some() {
+        let err = AppError::BadRequestErr("contest already joined".into());
+        return Err(err);
+    }
+    let contest_id = parse_object_id(&contest_id, "Not able to parse contestId")?;
+    let contest = validate_contest(&db, &contest_id).await?;
+    let play_tracker =
+        insert_new_play_tracker(claims.id, &contest, Some(&body.invite_code), &db).await?;
+    let res = PlayTrackerResponse {
+        success: true,
+        data: play_tracker,
+    };
+    Ok(Json(res))
+}
+
+/// Get contest players
+///
+/// List the players who joined a contest. Only the creator of the contest can see the list.
+#[utoipa::path(
+    get,
+    path = "/api/v1/contest/players",
+    params(ContestIdRequest, ("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Contest players", body = ContestPlayersResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn get_contest_players_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<ContestIdRequest>,
+) -> Result<Json<ContestPlayersResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let contest_id = parse_object_id(&params.contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": contest_id, "createdBy": claims.data.id};
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    let filter = doc! {"contestId": &params.contest_id};
+    let options = FindOptions::builder()
+        .sort(Some(doc! {"initTs": 1}))
+        .build();
+    let play_trackers = db
+        .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), Some(options))
+        .await?;
+    let user_ids = play_trackers
+        .iter()
+        .map(|pt| pt.user_id)
+        .collect::<Vec<_>>();
+    let filter = doc! {"id": {"$in": user_ids}};
+    let names = db
+        .find::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.name))
+        .collect::<HashMap<_, _>>();
+    let data = play_trackers
+        .into_iter()
+        .map(|pt| ContestPlayer {
+            user_id: pt.user_id,
+            name: names.get(&pt.user_id).cloned(),
+            status: pt.status,
+            joined_ts: pt.init_ts,
+            paid_ts: pt.paid_ts,
+        })
+        .collect();
+    let res = ContestPlayersResponse {
+        success: true,
+        invite_code: contest.invite_code,
+        joined_count: contest.joined_count,
+        max_players: contest.props.max_players,
+        data,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::{models::*, utils::test_helper::*};
+
+    #[tokio::test]
+    async fn test_private_contest() {
+        let db = test_db();
+        insert_admin(&db, 1).await;
+        let mut props = contest_props(0, 100);
+        props.is_private = true;
+        props.max_players = Some(2);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 2).await;
+        let invite_code = get_contest(&db, &contest_id).await.invite_code.unwrap();
+
+        // private contest is not listed and cannot be played without the invite code
+        let (_, res) = send_request(&db, Method::GET, "/api/v1/contest", 1, None).await;
+        assert_eq!(res["data"].as_array().unwrap().len(), 0);
+        let uri = format!("/api/v1/playTracker?contestId={contest_id}");
+        let (status, _) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let uri = format!("/api/v1/contest/invite?inviteCode={invite_code}");
+        let (status, res) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["_id"], contest_id);
+
+        let uri = "/api/v1/contest/join";
+        let body = json!({"inviteCode": invite_code});
+        for user_id in 1..=3 {
+            let (status, res) =
+                send_request(&db, Method::POST, uri, user_id, Some(body.clone())).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(res["data"]["status"], "INIT");
+        }
+        let (status, _) = send_request(&db, Method::POST, uri, 2, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // the slot is taken when the player starts, the players who only joined do not count
+        let uri = "/api/v1/playTracker/start";
+        let start = json!({"contestId": &contest_id});
+        for user_id in 1..=2 {
+            let (status, _) =
+                send_request(&db, Method::POST, uri, user_id, Some(start.clone())).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, res) = send_request(&db, Method::POST, uri, 3, Some(start)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(res["message"], "contest is full");
+        let uri = "/api/v1/contest/join";
+        let (status, res) = send_request(&db, Method::POST, uri, 4, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(res["message"], "contest is full");
+
+        let uri = format!("/api/v1/contest/players?contestId={contest_id}");
+        let (status, res) = send_admin_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["joinedCount"], 2);
+        assert_eq!(res["data"].as_array().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,82 @@
+use axum::{extract::State, Json};
+use mongodb::bson::{doc, to_bson};
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{parse_object_id, AppError, ValidatedBody},
+};
+
+/// Schedule contest template
+///
+/// Set or remove the schedule of a template. Contests are created from a scheduled template
+/// and activated automatically by the contest scheduler job.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/template/schedule",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = ScheduleTemplateReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Schedule updated", body = ContestTemplateResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Template not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn schedule_template_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<ScheduleTemplateReqBody>,
+) -> Result<Json<ContestTemplateResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let template_id = parse_object_id(&body.template_id, "Not able to parse templateId")?;
+    let filter = doc! {"_id": template_id};
+    let template = db
+        .find_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter.clone()), None)
+        .await?
+        .ok_or(AppError::NotFound("template not found".into()))?;
+    let update = match body.schedule.as_ref() {
+        Some(schedule) => {
+            validate_schedule(&template, schedule)?;
+            doc! {"$set": {"schedule": to_bson(schedule)?}}
+        }
+        None => doc! {"$unset": {"schedule": ""}},
+    };
+    db.update_one(
+        DB_NAME,
+        COLL_CONTEST_TEMPLATES,
+        filter.clone(),
+        update,
+        None,
+    )
+    .await?;
+    let template = db
+        .find_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("template not found".into()))?;
+    let res = ContestTemplateResponse {
+        success: true,
+        data: template,
+    };
+    Ok(Json(res))
+}
+
+fn validate_schedule(
+    template: &ContestTemplate,
+    schedule: &ContestSchedule,
+) -> Result<(), AppError> {
+    if template.questions.is_empty() {
+        let err = "template should contain some questions";
+        return Err(AppError::BadRequestErr(err.into()));
+    }
+    if schedule.activate_before > schedule.create_before() {
+        let err = "activateBefore should not be greater than createBefore";
+        return Err(AppError::BadRequestErr(err.into()));
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use axum::{extract::State, Json};
+use mongodb::bson::{doc, to_document};
+use std::sync::Arc;
+
+use super::create::validate_body;
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{generate_invite_code, get_epoch_ts, parse_object_id, AppError, ValidatedBody},
+};
+
+/// Contest update
+///
+/// Update a contest in CREATED or INACTIVE status. The entry fee of a contest which was
+/// already activated cannot be changed.
+#[utoipa::path(
+    post,
+    path = "/api/v1/contest/update",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = UpdateContestReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Contest updated", body = ContestResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_contest_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<UpdateContestReqBody>,
+) -> Result<Json<ContestResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let contest_id = parse_object_id(&body._id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": contest_id};
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter.clone()), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    if contest.status != ContestStatus::CREATED && contest.status != ContestStatus::INACTIVE {
+        let err = "contest status must be CREATED or INACTIVE";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
+    if contest.status == ContestStatus::INACTIVE
+        && (contest.props.entry_fee != body.props.entry_fee
+            || contest.props.entry_fee_max_bonus_money != body.props.entry_fee_max_bonus_money)
+    {
+        let err = "entryFee cannot be changed after the contest is activated";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
+    if body
+        .props
+        .max_players
+        .is_some_and(|max_players| max_players < contest.joined_count)
+    {
+        let err = "maxPlayers cannot be less than the players already joined";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
+    validate_body(&db, &body.props, Some(&contest_id)).await?;
+    let mut set_obj = to_document(&body.props)?;
+    if body.props.is_private && contest.invite_code.is_none() {
+        set_obj.insert("inviteCode", generate_invite_code());
+    }
+    set_obj.insert("updatedTs", get_epoch_ts() as i64);
+    set_obj.insert("updatedBy", claims.data.id);
+    let update_filter = doc! {"_id": contest_id, "status": contest.status.to_bson()?};
+    let update = doc! {"$set": set_obj};
+    let result = db
+        .update_one(DB_NAME, COLL_CONTESTS, update_filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        let err = AppError::BadRequestErr("contest status is changed".into());
+        return Err(err);
+    }
+    let contest = db
+        .find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("contest not found".into()))?;
+    let res = ContestResponse {
+        success: true,
+        data: contest,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_update_contest() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        let mut props = contest_props(10, 100);
+        props.category = ContestCategory::Others;
+        props.start_time = props.end_time - chrono::Duration::minutes(30);
+        let contest_id = insert_contest(&db, &props, ContestStatus::CREATED, 1).await;
+        let mut other = contest_props(10, 100);
+        other.title = "other contest".into();
+        insert_contest(&db, &other, ContestStatus::CREATED, 1).await;
+
+        let uri = "/api/v1/contest/update";
+        let mut body = serde_json::to_value(&props).unwrap();
+        body["_id"] = json!(contest_id);
+        body["sponsoredBy"] = json!("new sponsor");
+        let (status, res) =
+            send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["sponsoredBy"], "new sponsor");
+        // title of another contest cannot be reused
+        let mut duplicate = body.clone();
+        duplicate["title"] = json!("other contest");
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(duplicate)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // entry fee is locked once the contest was activated
+        let filter = doc! {"_id": parse_object_id(&contest_id, "").unwrap()};
+        let update = doc! {"$set": {"status": ContestStatus::INACTIVE.to_bson().unwrap()}};
+        db.update_one(DB_NAME, COLL_CONTESTS, filter.clone(), update, None)
+            .await
+            .unwrap();
+        body["entryFee"] = json!(20);
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        body["entryFee"] = json!(10);
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let update = doc! {"$set": {"status": ContestStatus::ACTIVE.to_bson().unwrap()}};
+        db.update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await
+            .unwrap();
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
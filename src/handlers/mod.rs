@@ -8,6 +8,8 @@ pub mod notification;
 pub mod ping;
 pub mod play_tracker;
 pub mod question;
+pub mod question_bank;
+#[cfg(feature = "debug-api")]
 pub mod temp_api;
 pub mod upload;
 pub mod user;
@@ -19,8 +21,20 @@ pub use clip::get_clip::get_clips_handler;
 
 pub use contest::activate::activate_contest_handler;
 pub use contest::activate::inactivate_contest_handler;
+pub use contest::clone::clone_contest_handler;
+pub use contest::clone::contest_from_template_handler;
+pub use contest::clone::get_templates_handler;
+pub use contest::clone::save_template_handler;
 pub use contest::create::create_contest_handler;
+pub use contest::events::contest_events_handler;
 pub use contest::get::get_contest_handler;
+pub use contest::leaderboard::get_contest_leaderboard_handler;
+pub use contest::leaderboard::get_contest_results_handler;
+pub use contest::private::get_contest_players_handler;
+pub use contest::private::get_invited_contest_handler;
+pub use contest::private::join_contest_handler;
+pub use contest::schedule::schedule_template_handler;
+pub use contest::update::update_contest_handler;
 
 pub use default::default_route_handler;
 
@@ -41,6 +55,7 @@ pub use notification::create_broadcast_noti::create_broadcast_noti_handler;
 pub use notification::get_noti::get_noti_handler;
 pub use notification::mark_read::mark_all_read_noti_handler;
 pub use notification::mark_read::mark_read_noti_handler;
+pub use notification::stream_noti::stream_noti_handler;
 
 pub use ping::ping_handler;
 
@@ -52,10 +67,19 @@ pub use play_tracker::start::start_play_tracker_handler;
 
 pub use question::create::create_question_handler;
 pub use question::delete::delete_question_handler;
+pub use question::export::export_questions_handler;
 pub use question::get::get_question_handler;
 pub use question::update::update_question_handler;
 
+pub use question_bank::attach::attach_questions_handler;
+pub use question_bank::create::create_bank_question_handler;
+pub use question_bank::create::update_bank_question_handler;
+pub use question_bank::get::get_bank_questions_handler;
+pub use question_bank::import::import_questions_handler;
+
+#[cfg(feature = "debug-api")]
 pub use temp_api::temp_api_get_otp;
+#[cfg(feature = "debug-api")]
 pub use temp_api::temp_api_get_token;
 
 pub use upload::multipart::complete_multipart_handler;
@@ -66,6 +90,9 @@ pub use upload::single::upload_handler;
 pub use user::admin_login::admin_generate_otp;
 pub use user::admin_login::admin_login_handler;
 pub use user::admin_login::admin_signup_handler;
+pub use user::admin_users::create_admin_user_handler;
+pub use user::admin_users::get_admin_users_handler;
+pub use user::admin_users::update_admin_user_handler;
 pub use user::check_otp::check_otp_handler;
 pub use user::create::create_user_handler;
 pub use user::get_leaderboard::get_leaderboard_handler;
@@ -82,5 +109,10 @@ pub use wallet::add_bal::add_bal_init_handler;
 pub use wallet::get_bal::get_bal_handler;
 pub use wallet::helper::*;
 pub use wallet::pay_contest::pay_contest_handler;
-pub use wallet::withdraw_bal::withdraw_bal_end_handler;
+pub use wallet::payment_webhook::payment_webhook_handler;
+pub use wallet::transactions::admin_get_transactions_handler;
+pub use wallet::transactions::get_transactions_handler;
 pub use wallet::withdraw_bal::withdraw_bal_init_handler;
+pub use wallet::withdrawals::approve_withdrawal_handler;
+pub use wallet::withdrawals::get_withdrawals_handler;
+pub use wallet::withdrawals::reject_withdrawal_handler;
@@ -19,6 +19,7 @@ use crate::{
     request_body = CreateBroadcastReq,
     responses(
         (status = StatusCode::OK, description = "Saved successfully", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -27,6 +28,7 @@ pub async fn create_broadcast_noti_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<CreateBroadcastReq>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::BROADCASTS)?;
     let ts = get_epoch_ts();
     let random = get_random_num(101, 1000);
     let event_name = format!("BROADCAST_MESSAGE_{ts}_{random}");
@@ -2,3 +2,4 @@ pub mod clear_noti;
 pub mod create_broadcast_noti;
 pub mod get_noti;
 pub mod mark_read;
+pub mod stream_noti;
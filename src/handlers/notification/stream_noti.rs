@@ -0,0 +1,143 @@
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{stream, Stream, StreamExt};
+use mongodb::bson::doc;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    constants::*, database::AppDatabase, events::EventBus, jwt::JwtClaims, models::*,
+    utils::AppError,
+};
+
+/// notification stream
+///
+/// Server-Sent Events stream of the in-app notifications. An `unread` event with the
+/// unread count is sent on connect and a `notification` event for every new notification.
+#[utoipa::path(
+    get,
+    path = "/api/v1/notification/stream",
+    params(("authorization" = String, Header, description = "JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "text/event-stream of notifications", body = NotiStreamData),
+    ),
+    tag = "App User API"
+)]
+pub async fn stream_noti_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    State(events): State<Arc<EventBus>>,
+) -> Result<Sse<impl Stream<Item = Result<Event, serde_json::Error>>>, AppError> {
+    let user_id = claims.id;
+    // subscribe before counting so that no notification is missed in between
+    let receiver = events.subscribe_notifications();
+    let unread_count = get_unread_count(&db, user_id).await?;
+    let data = NotiStreamData {
+        unread_count,
+        data: None,
+    };
+    let initial = stream::once(async move { Event::default().event("unread").json_data(data) });
+    let notifications = stream::unfold(receiver, move |mut receiver| {
+        let db = db.clone();
+        async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) if event.user_id == user_id => event,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::debug!("notifications skipped for user {}: {}", user_id, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                let unread_count = get_unread_count(&db, user_id).await.unwrap_or_else(|e| {
+                    tracing::debug!("{:?}", e);
+                    0
+                });
+                let data = NotiStreamData {
+                    unread_count,
+                    data: Some(event.notification),
+                };
+                let sse_event = Event::default().event("notification").json_data(data);
+                return Some((sse_event, receiver));
+            }
+        }
+    });
+    Ok(Sse::new(initial.chain(notifications)).keep_alive(KeepAlive::default()))
+}
+
+async fn get_unread_count(db: &AppDatabase, user_id: u32) -> Result<u64, AppError> {
+    let push_message = NotificationType::PUSH_MESSAGE.to_bson()?;
+    let filter = doc! {
+        "userId": user_id,
+        "isRead": false,
+        "isCleared": false,
+        "notificationType": push_message
+    };
+    let unread_count = db
+        .count_documents(DB_NAME, COLL_NOTIFICATIONS, Some(filter), None)
+        .await?;
+    Ok(unread_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, HttpBody},
+        http::{header, Request},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{events::NotificationEvent, jwt::JWT_KEYS, utils::test_helper::*};
+
+    async fn insert_notification(db: &AppDatabase, user_id: u32) -> Notifications {
+        let notification = Notifications::new_push(user_id, "TEST_EVENT", "hello");
+        let id = db
+            .insert_one::<Notifications>(DB_NAME, COLL_NOTIFICATIONS, &notification, None)
+            .await
+            .unwrap();
+        notification.with_id(id)
+    }
+
+    #[tokio::test]
+    async fn test_notification_stream() {
+        let db = test_db();
+        let events = Arc::new(EventBus::default());
+        insert_notification(&db, 1).await;
+        let token = JWT_KEYS.generate_token(1, None).unwrap();
+        let req = Request::get("/api/v1/notification/stream")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let res = test_app(&db, events.clone()).oneshot(req).await.unwrap();
+        // compression would buffer the events of the stream
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        let mut body = res.into_body();
+        let chunk = body.data().await.unwrap().unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.starts_with("event:unread\n"));
+        assert!(chunk.contains(r#""unreadCount":1"#));
+
+        // notification of another user is not sent
+        let notification = insert_notification(&db, 2).await;
+        events.publish_notification(NotificationEvent {
+            user_id: 2,
+            notification,
+        });
+        let notification = insert_notification(&db, 1).await;
+        events.publish_notification(NotificationEvent {
+            user_id: 1,
+            notification,
+        });
+        let chunk = body.data().await.unwrap().unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.starts_with("event:notification\n"));
+        assert!(chunk.contains(r#""unreadCount":2"#));
+        assert!(chunk.contains(r#""message":"hello""#));
+    }
+}
@@ -11,7 +11,7 @@ use crate::models::GenericResponse;
     responses(
         (status = 200, description = "Get success response from server", body=GenericResponse)
     ),
-    tag = "Debugging API"
+    tag = "Server API"
 )]
 pub async fn ping_handler() -> Json<GenericResponse> {
     let res = GenericResponse {
@@ -5,10 +5,11 @@ use mongodb::{
 };
 use std::sync::Arc;
 
-use super::start::get_question;
+use super::start::{check_play_deadline, serve_question};
 use crate::{
     constants::*,
     database::AppDatabase,
+    events::{ContestEvent, ContestEventType, EventBus},
     handlers::play_tracker::get::validate_contest,
     jwt::JwtClaims,
     models::*,
@@ -31,6 +32,7 @@ use crate::{
 pub async fn answer_play_tracker_handler(
     claims: JwtClaims,
     State(db): State<Arc<AppDatabase>>,
+    State(events): State<Arc<EventBus>>,
     ValidatedBody(body): ValidatedBody<AnswerPlayTrackerReqBody>,
 ) -> Result<Json<PlayTrackerQuesRes>, AppError> {
     let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
@@ -40,18 +42,33 @@ pub async fn answer_play_tracker_handler(
     );
     let contest = contest_result?;
     let play_tracker = play_tracker_result?;
-    let (given_answer, is_correct) = check_if_correct(&contest, &body)?;
+    check_play_deadline(&play_tracker)?;
+    let served_question = check_served_question(&play_tracker, body.question_no)?;
+    let ts = get_epoch_ts();
+    let response_time = ts.saturating_sub(served_question.served_ts);
+    let mut given_answer = check_if_correct(&contest, &body, response_time)?;
     let is_finished = check_if_finished(&contest, &play_tracker, body.question_no)?;
-    let mut play_tracker = update_play_tracker(
-        &db,
-        &body.contest_id,
-        claims.id,
-        is_correct,
-        is_finished,
-        &given_answer,
-    )
-    .await?;
-    let question = get_question_not_finished(&contest, &play_tracker, is_finished)?;
+    let is_late = ts > served_question.deadline_ts + QUESTION_DEADLINE_GRACE_SECS;
+    let is_rejected = is_late && contest.props.late_answer_policy == LateAnswerPolicy::REJECT;
+    given_answer.is_late = is_late;
+    if is_late {
+        given_answer.score = Some(0);
+        given_answer.speed_bonus = None;
+    }
+    if is_rejected {
+        given_answer.selected_option_id = 0;
+        given_answer.selected_option_ids = None;
+        given_answer.numeric_answer = None;
+    }
+    let mut play_tracker =
+        update_play_tracker(&db, &body.contest_id, claims.id, is_finished, &given_answer).await?;
+    events.publish(leaderboard_delta(&play_tracker).with_private(contest.props.is_private));
+    if is_rejected {
+        let err = "answer submitted after the question deadline";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
+    let question = get_question_not_finished(&db, &contest, &mut play_tracker, is_finished).await?;
     if play_tracker.status != PlayTrackerStatus::FINISHED {
         play_tracker.answers = None;
     }
@@ -64,6 +81,18 @@ pub async fn answer_play_tracker_handler(
     Ok(Json(res))
 }
 
+/// score change of the player pushed to the live leaderboard
+fn leaderboard_delta(play_tracker: &PlayTracker) -> ContestEvent {
+    let mut event = ContestEvent::new(
+        ContestEventType::LEADERBOARD_DELTA,
+        &play_tracker.contest_id,
+    );
+    event.user_id = Some(play_tracker.user_id);
+    event.score = Some(play_tracker.score.unwrap_or_default());
+    event.total_answered = play_tracker.total_answered;
+    event
+}
+
 pub async fn check_play_tracker(
     db: &Arc<AppDatabase>,
     contest_id: &str,
@@ -82,10 +111,26 @@ pub async fn check_play_tracker(
     Ok(play_tracker)
 }
 
+/// the answered question must be the one served to the player
+fn check_served_question(
+    play_tracker: &PlayTracker,
+    question_no: u32,
+) -> Result<ServedQuestion, AppError> {
+    play_tracker
+        .current_question
+        .clone()
+        .filter(|served_question| served_question.question_no == question_no)
+        .ok_or(AppError::BadRequestErr(format!(
+            "question {question_no} is not served"
+        )))
+}
+
+/// Grade the answer as per the question type and return it with the weighted score set
 fn check_if_correct(
     contest: &ContestWithQuestion,
     body: &AnswerPlayTrackerReqBody,
-) -> Result<(ChosenAnswer, bool), AppError> {
+    response_time: u64,
+) -> Result<ChosenAnswer, AppError> {
     let questions = contest
         .questions
         .as_ref()
@@ -94,15 +139,118 @@ fn check_if_correct(
         .into_iter()
         .find(|q| q.props.question_no == body.question_no)
         .ok_or(AppError::BadRequestErr("Invalid questionNo".into()))?;
-    let is_correct = question
-        .options
-        .iter()
-        .any(|opt| opt.props.option_id == body.selected_option_id && opt.is_correct);
+    let missing = |field: &str| AppError::BadRequestErr(format!("{field} required"));
+    let credit = match question.props.question_type {
+        QuestionType::SINGLE_CHOICE | QuestionType::TRUE_FALSE | QuestionType::IMAGE_CHOICE => {
+            let selected_option_id = body.selected_option_id.ok_or(missing("selectedOptionId"))?;
+            let is_correct = question
+                .options
+                .iter()
+                .any(|opt| opt.props.option_id == selected_option_id && opt.is_correct);
+            if is_correct {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        QuestionType::MULTI_SELECT => {
+            let selected_option_ids = body
+                .selected_option_ids
+                .as_ref()
+                .filter(|ids| !ids.is_empty())
+                .ok_or(missing("selectedOptionIds"))?;
+            multi_select_credit(question, selected_option_ids)
+        }
+        QuestionType::NUMERIC => {
+            let value = body.numeric_answer.ok_or(missing("numericAnswer"))?;
+            let is_correct = question
+                .numeric_answer
+                .as_ref()
+                .map(|ans| (value - ans.value).abs() <= ans.tolerance)
+                .unwrap_or(false);
+            if is_correct {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    };
+    let (score, speed_bonus) = weighted_score(&contest.props, question, credit, response_time);
     let answer = ChosenAnswer {
         question: question.clone(),
-        selected_option_id: body.selected_option_id,
+        selected_option_id: body.selected_option_id.unwrap_or_default(),
+        selected_option_ids: body.selected_option_ids.clone(),
+        numeric_answer: body.numeric_answer,
+        score: Some(score),
+        speed_bonus,
+        response_time: Some(response_time),
+        is_late: false,
     };
-    Ok((answer, is_correct))
+    Ok(answer)
+}
+
+/// Score of the answer from the credit earned. A wrong answer loses the negative marks and
+/// a fully correct answer gets the speed bonus reduced linearly over the question time limit.
+fn weighted_score(
+    props: &ContestProps,
+    question: &Question,
+    credit: f64,
+    response_time: u64,
+) -> (i32, Option<u32>) {
+    if credit <= 0.0 {
+        let negative_marks = question
+            .props
+            .negative_marks
+            .or(props.negative_marks)
+            .unwrap_or(0);
+        return (-i32::try_from(negative_marks).unwrap_or(i32::MAX), None);
+    }
+    let score = (credit * question.points() as f64).round() as i32;
+    let speed_bonus = props
+        .speed_bonus_points
+        .filter(|_| credit >= 1.0)
+        .map(|points| {
+            let time_limit = question.props.time_limit() as u64;
+            let remaining = time_limit.saturating_sub(response_time);
+            (points as u64 * remaining / time_limit.max(1)) as u32
+        });
+    let bonus = i32::try_from(speed_bonus.unwrap_or(0)).unwrap_or(i32::MAX);
+    (score.saturating_add(bonus), speed_bonus)
+}
+
+/// fraction of the multi-select question answered correctly
+fn multi_select_credit(question: &Question, selected_option_ids: &[u32]) -> f64 {
+    let mut selected_option_ids = selected_option_ids.to_vec();
+    selected_option_ids.sort_unstable();
+    selected_option_ids.dedup();
+    let is_correct = |id: &u32| {
+        question
+            .options
+            .iter()
+            .any(|opt| opt.props.option_id == *id && opt.is_correct)
+    };
+    let correct_count = question.options.iter().filter(|opt| opt.is_correct).count();
+    let correct_selected = selected_option_ids
+        .iter()
+        .filter(|id| is_correct(id))
+        .count();
+    let wrong_selected = selected_option_ids.len() - correct_selected;
+    if correct_count == 0 {
+        return 0.0;
+    }
+    match question.props.multi_select_scoring.unwrap_or_default() {
+        MultiSelectScoring::ALL_OR_NOTHING => {
+            if correct_selected == correct_count && wrong_selected == 0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        MultiSelectScoring::PARTIAL => {
+            let net = correct_selected.saturating_sub(wrong_selected);
+            net as f64 / correct_count as f64
+        }
+    }
 }
 
 fn check_if_finished(
@@ -137,16 +285,18 @@ async fn update_play_tracker(
     db: &Arc<AppDatabase>,
     contest_id: &str,
     user_id: u32,
-    is_correct: bool,
     is_finished: bool,
     answer: &ChosenAnswer,
 ) -> Result<PlayTracker, AppError> {
-    let score = if is_correct { 1 } else { 0u32 };
+    let score = answer.score.unwrap_or_default();
     let ts = get_epoch_ts() as i64;
+    let question_no = answer.question.props.question_no;
+    let response_time = answer.response_time.unwrap_or_default() as i64;
     let filter = doc! {
         "contestId": contest_id,
         "userId": user_id,
         "status": PlayTrackerStatus::STARTED.to_bson()?,
+        "currentQuestion.questionNo": question_no,
     };
     let mut set_obj = doc! {"updatedTs": ts, "updatedBy": user_id};
     if is_finished {
@@ -155,8 +305,9 @@ async fn update_play_tracker(
     }
     let update = doc! {
         "$push": {"answers": answer.to_bson()?},
-        "$inc": {"score": score, "totalAnswered": 1},
-        "$set": set_obj
+        "$inc": {"score": score, "totalAnswered": 1, "totalResponseTime": response_time},
+        "$set": set_obj,
+        "$unset": {"currentQuestion": ""}
     };
     let options = FindOneAndUpdateOptions::builder()
         .return_document(Some(ReturnDocument::After))
@@ -170,19 +321,267 @@ async fn update_play_tracker(
             Some(options),
         )
         .await?
-        .ok_or(AppError::unknown_error())?;
+        .ok_or(AppError::BadRequestErr(format!(
+            "question {question_no} is not served"
+        )))?;
 
     Ok(play_tracker)
 }
 
-fn get_question_not_finished(
+async fn get_question_not_finished(
+    db: &Arc<AppDatabase>,
     contest: &ContestWithQuestion,
-    play_tracker: &PlayTracker,
+    play_tracker: &mut PlayTracker,
     is_finished: bool,
 ) -> Result<Option<QuestionWithoutCorrectFlag>, AppError> {
     if is_finished {
         return Ok(None);
     }
-    let question = get_question(contest, play_tracker)?;
+    let question = serve_question(db, contest, play_tracker).await?;
     Ok(Some(question))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::{json, Value};
+    use validator::Validate;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_play_contest_flow() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let uri = format!("/api/v1/playTracker?contestId={contest_id}");
+        let (status, res) = send_request(&db, Method::GET, &uri, 7, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["status"], "INIT");
+
+        let body = json!({"contestId": contest_id});
+        let uri = "/api/v1/playTracker/start";
+        let (status, mut res) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["status"], "STARTED");
+
+        let uri = "/api/v1/playTracker/answer";
+        for count in 1..=3 {
+            // only the first two answers are correct
+            let selected_option_id = if count < 3 { 1 } else { 2 };
+            let body = json!({
+                "contestId": contest_id,
+                "questionNo": res["question"]["questionNo"],
+                "selectedOptionId": selected_option_id
+            });
+            let (status, next) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+            assert_eq!(status, StatusCode::OK);
+            res = next;
+            if count == 3 {
+                assert_eq!(res["data"]["status"], "FINISHED");
+                assert_eq!(res["data"]["score"], 2);
+                assert_eq!(res["data"]["totalAnswered"], 3);
+            }
+        }
+        let body = json!({"contestId": contest_id, "questionNo": 1, "selectedOptionId": 1});
+        let (status, _) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_check_if_correct() {
+        let mut multi = question(2);
+        multi.props.question_type = QuestionType::MULTI_SELECT;
+        multi.options[1].is_correct = true;
+        multi.options[2].is_correct = true;
+        let mut partial = multi.clone();
+        partial.props.question_no = 3;
+        partial.props.multi_select_scoring = Some(MultiSelectScoring::PARTIAL);
+        let mut numeric = question(4);
+        numeric.props.question_type = QuestionType::NUMERIC;
+        numeric.options = vec![];
+        numeric.numeric_answer = Some(NumericAnswer {
+            value: 9.8,
+            tolerance: 0.1,
+        });
+        let contest = ContestWithQuestion {
+            _id: None,
+            props: contest_props(0, 100),
+            status: ContestStatus::ACTIVE,
+            invite_code: None,
+            joined_count: 0,
+            questions: Some(vec![question(1), multi, partial, numeric]),
+            created_ts: None,
+            created_by: None,
+            updated_ts: None,
+            updated_by: None,
+        };
+        let score = |question_no: u32, option_id, option_ids: Option<Vec<u32>>, value| {
+            let body = AnswerPlayTrackerReqBody {
+                contest_id: String::new(),
+                question_no,
+                selected_option_id: option_id,
+                selected_option_ids: option_ids,
+                numeric_answer: value,
+            };
+            check_if_correct(&contest, &body, 0).map(|ans| ans.score.unwrap())
+        };
+        assert_eq!(score(1, Some(1), None, None).unwrap(), 1);
+        assert_eq!(score(1, Some(2), None, None).unwrap(), 0);
+        assert!(score(1, None, Some(vec![1]), None).is_err());
+        // all or nothing
+        assert_eq!(score(2, None, Some(vec![1, 2, 3]), None).unwrap(), 1);
+        assert_eq!(score(2, None, Some(vec![1, 2]), None).unwrap(), 0);
+        assert_eq!(score(2, None, Some(vec![1, 2, 3, 4]), None).unwrap(), 0);
+        // partial credit of one point per correct option less the wrong ones
+        assert_eq!(score(3, None, Some(vec![1, 2, 3]), None).unwrap(), 3);
+        assert_eq!(score(3, None, Some(vec![1, 2]), None).unwrap(), 2);
+        assert_eq!(score(3, None, Some(vec![1, 2, 4]), None).unwrap(), 1);
+        assert_eq!(score(3, None, Some(vec![4]), None).unwrap(), 0);
+        // numeric within tolerance
+        assert_eq!(score(4, None, None, Some(9.75)).unwrap(), 1);
+        assert_eq!(score(4, None, None, Some(10.0)).unwrap(), 0);
+        assert!(score(4, Some(1), None, None).is_err());
+    }
+
+    #[test]
+    fn test_weighted_score() {
+        let mut props = contest_props(0, 100);
+        props.negative_marks = Some(1);
+        props.speed_bonus_points = Some(10);
+        let mut question = question(1);
+        question.props.points = Some(4);
+        question.props.time_limit = Some(20);
+        assert_eq!(weighted_score(&props, &question, 1.0, 0), (14, Some(10)));
+        assert_eq!(weighted_score(&props, &question, 1.0, 5), (11, Some(7)));
+        assert_eq!(weighted_score(&props, &question, 1.0, 25), (4, Some(0)));
+        // partial credit does not earn the speed bonus
+        assert_eq!(weighted_score(&props, &question, 0.5, 0), (2, None));
+        assert_eq!(weighted_score(&props, &question, 0.0, 0), (-1, None));
+        question.props.negative_marks = Some(2);
+        assert_eq!(weighted_score(&props, &question, 0.0, 0), (-2, None));
+        // marks saved before the validation do not wrap around
+        question.props.negative_marks = Some(u32::MAX);
+        assert_eq!(weighted_score(&props, &question, 0.0, 0), (-i32::MAX, None));
+        question.props.negative_marks = Some(2);
+        assert!(question.props.validate().is_ok());
+        question.props.negative_marks = Some(101);
+        assert!(question.props.validate().is_err());
+        question.props.negative_marks = Some(2);
+        props.negative_marks = None;
+        props.speed_bonus_points = None;
+        question.props.negative_marks = None;
+        assert_eq!(weighted_score(&props, &question, 0.0, 0), (0, None));
+        assert_eq!(weighted_score(&props, &question, 1.0, 0), (4, None));
+    }
+
+    /// start the contest and move the deadline of the served question to the past
+    async fn start_and_expire(db: &Arc<AppDatabase>, contest_id: &str) -> Value {
+        let uri = format!("/api/v1/playTracker?contestId={contest_id}");
+        send_request(db, Method::GET, &uri, 7, None).await;
+        let body = json!({"contestId": contest_id});
+        let uri = "/api/v1/playTracker/start";
+        let (status, res) = send_request(db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(res["data"]["currentQuestion"]["deadlineTs"]
+            .as_u64()
+            .is_some());
+        let filter = doc! {"contestId": contest_id, "userId": 7};
+        let update = doc! {"$set": {"currentQuestion.deadlineTs": 1_i64}};
+        db.update_one(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await
+            .unwrap();
+        res
+    }
+
+    #[tokio::test]
+    async fn test_question_deadline() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let res = start_and_expire(&db, &contest_id).await;
+        let question_no = res["question"]["questionNo"].as_u64().unwrap();
+
+        // the pending question is served again with the same deadline
+        let uri = format!("/api/v1/playTracker/getNextQues?contestId={contest_id}");
+        let (status, res) = send_request(&db, Method::GET, &uri, 7, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["question"]["questionNo"], question_no);
+        assert_eq!(res["data"]["currentQuestion"]["deadlineTs"], 1);
+
+        // only the served question can be answered
+        let uri = "/api/v1/playTracker/answer";
+        let other_no = question_no % 3 + 1;
+        let body = json!({"contestId": contest_id, "questionNo": other_no, "selectedOptionId": 1});
+        let (status, _) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // late correct answer is scored zero
+        let body =
+            json!({"contestId": contest_id, "questionNo": question_no, "selectedOptionId": 1});
+        let (status, res) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["score"], 0);
+        assert_eq!(res["data"]["totalAnswered"], 1);
+        assert!(res["data"]["totalResponseTime"].as_u64().is_some());
+        assert_ne!(res["question"]["questionNo"], question_no);
+    }
+
+    #[tokio::test]
+    async fn test_serve_question_once() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let res = start_and_expire(&db, &contest_id).await;
+        let question_no = res["question"]["questionNo"].as_u64().unwrap() as u32;
+
+        // a request which read the play tracker before the question was served
+        let filter = doc! {"contestId": &contest_id, "userId": 7};
+        let mut play_tracker = db
+            .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        play_tracker.current_question = None;
+        let filter = doc! {"_id": parse_object_id(&contest_id, "").unwrap()};
+        let contest = db
+            .find_one::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        let question = serve_question(&db, &contest, &mut play_tracker)
+            .await
+            .unwrap();
+        assert_eq!(question.props.question_no, question_no);
+        let served = play_tracker.current_question.unwrap();
+        assert_eq!(served.question_no, question_no);
+        assert_eq!(served.deadline_ts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_late_answer_rejected() {
+        let db = test_db();
+        let mut props = contest_props(0, 100);
+        props.late_answer_policy = LateAnswerPolicy::REJECT;
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let res = start_and_expire(&db, &contest_id).await;
+        let question_no = res["question"]["questionNo"].as_u64().unwrap();
+
+        let uri = "/api/v1/playTracker/answer";
+        let body =
+            json!({"contestId": contest_id, "questionNo": question_no, "selectedOptionId": 1});
+        let (status, _) = send_request(&db, Method::POST, uri, 7, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let filter = doc! {"contestId": &contest_id, "userId": 7};
+        let play_tracker = db
+            .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        let answers = play_tracker.answers.unwrap();
+        assert_eq!(answers[0].selected_option_id, 0);
+        assert!(answers[0].is_late);
+        assert_eq!(play_tracker.score, Some(0));
+    }
+}
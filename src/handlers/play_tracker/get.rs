@@ -7,7 +7,7 @@ use std::sync::Arc;
 
 use crate::{
     constants::*,
-    database::AppDatabase,
+    database::{is_duplicate_key_error, AppDatabase},
     jwt::JwtClaims,
     models::*,
     utils::{get_epoch_ts, parse_object_id, AppError},
@@ -46,7 +46,7 @@ pub async fn get_play_tracker_handler(
         return Ok(Json(res));
     }
     let contest = contest_result?;
-    let play_tracker = insert_new_play_tracker(claims.id, &contest, &db).await?;
+    let play_tracker = insert_new_play_tracker(claims.id, &contest, None, &db).await?;
     let res = PlayTrackerResponse {
         success: true,
         data: play_tracker,
@@ -54,12 +54,23 @@ pub async fn get_play_tracker_handler(
     Ok(Json(res))
 }
 
+/// Insert the play tracker of a player joining the contest. A private contest can be joined
+/// only with its invite code. The player slot is reserved later when the player pays or
+/// starts the contest, so that the players who never pay do not fill the contest.
 pub async fn insert_new_play_tracker(
     user_id: u32,
     contest: &ContestWithQuestion,
+    invite_code: Option<&str>,
     db: &Arc<AppDatabase>,
 ) -> Result<PlayTracker, AppError> {
     let contest_id = get_contest_id(contest)?;
+    if contest.props.is_private
+        && (invite_code.is_none() || contest.invite_code.as_deref() != invite_code)
+    {
+        let err = "private contest can be joined only with the invite code";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
     let total_questions = contest
         .questions
         .as_ref()
@@ -69,10 +80,49 @@ pub async fn insert_new_play_tracker(
         let err = anyhow::anyhow!("total_questions must be greater than zero at this point");
         return Err(err.into());
     }
+    if contest
+        .props
+        .max_players
+        .is_some_and(|max_players| contest.joined_count >= max_players)
+    {
+        return Err(AppError::BadRequestErr("contest is full".into()));
+    }
     let play_tracker = PlayTracker::new(user_id, contest_id, total_questions);
-    db.insert_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, &play_tracker, None)
+    // the unique index on contestId and userId refuses a concurrent join of the same player
+    let result = db
+        .insert_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, &play_tracker, None)
+        .await;
+    match result {
+        Ok(_) => Ok(play_tracker),
+        Err(err) if is_duplicate_key_error(&err) => {
+            let err = AppError::BadRequestErr("contest already joined".into());
+            Err(err)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Add `inc` to the joined count of the contest. No player is added when the contest is full.
+pub async fn update_joined_count(
+    db: &Arc<AppDatabase>,
+    contest: &ContestWithQuestion,
+    contest_id: &str,
+    inc: i32,
+) -> Result<(), AppError> {
+    let contest_id = parse_object_id(contest_id, "Not able to parse contestId")?;
+    let mut filter = doc! {"_id": contest_id};
+    if let Some(max_players) = contest.props.max_players.filter(|_| inc > 0) {
+        filter.insert("joinedCount", doc! {"$not": {"$gte": max_players}});
+    }
+    let update = doc! {"$inc": {"joinedCount": inc}};
+    let result = db
+        .update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
         .await?;
-    Ok(play_tracker)
+    if result.matched_count == 0 {
+        let err = AppError::BadRequestErr("contest is full".into());
+        return Err(err);
+    }
+    Ok(())
 }
 
 pub async fn validate_contest(
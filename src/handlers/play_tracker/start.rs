@@ -11,7 +11,7 @@ use std::sync::Arc;
 use crate::{
     constants::*,
     database::AppDatabase,
-    handlers::play_tracker::get::validate_contest,
+    handlers::play_tracker::get::{update_joined_count, validate_contest},
     jwt::JwtClaims,
     models::*,
     utils::{get_epoch_ts, get_random_num, parse_object_id, AppError},
@@ -47,9 +47,8 @@ pub async fn start_play_tracker_handler(
         let err = AppError::BadRequestErr(err.into());
         return Err(err);
     }
-    let mut play_tracker =
-        update_play_tracker(&db, &body.contest_id, claims.id, &play_tracker).await?;
-    let question = get_question(&contest, &play_tracker)?;
+    let mut play_tracker = update_play_tracker(&db, &contest, claims.id, &play_tracker).await?;
+    let question = serve_question(&db, &contest, &mut play_tracker).await?;
     if play_tracker.status != PlayTrackerStatus::FINISHED {
         play_tracker.answers = None;
     }
@@ -86,7 +85,7 @@ pub async fn get_next_ques_handler(
     );
     let contest = contest_result?;
     let mut play_tracker = play_tracker_result?;
-    let question = get_question(&contest, &play_tracker)?;
+    let question = serve_question(&db, &contest, &mut play_tracker).await?;
     if play_tracker.status != PlayTrackerStatus::FINISHED {
         play_tracker.answers = None;
     }
@@ -99,6 +98,98 @@ pub async fn get_next_ques_handler(
     Ok(Json(res))
 }
 
+/// Serve the pending question again or pick the next question and record its deadline.
+/// The deadline of a pending question is never extended. The next question is recorded only
+/// when the current question is unchanged since the play tracker was read, so that concurrent
+/// requests are all served the same question.
+pub async fn serve_question(
+    db: &Arc<AppDatabase>,
+    contest: &ContestWithQuestion,
+    play_tracker: &mut PlayTracker,
+) -> Result<QuestionWithoutCorrectFlag, AppError> {
+    check_play_deadline(play_tracker)?;
+    if let Some(question) = get_pending_question(contest, play_tracker) {
+        return Ok(question);
+    }
+    let question = get_question(contest, play_tracker)?;
+    let served_question = ServedQuestion::new(&question.props);
+    let mut filter = doc! {
+        "contestId": &play_tracker.contest_id,
+        "userId": play_tracker.user_id,
+        "status": PlayTrackerStatus::STARTED.to_bson()?
+    };
+    match play_tracker.current_question.as_ref() {
+        Some(current) => filter.insert("currentQuestion.questionNo", current.question_no),
+        None => filter.insert("currentQuestion", doc! {"$exists": false}),
+    };
+    let update = doc! {"$set": {"currentQuestion": served_question.to_bson()?}};
+    let result = db
+        .update_one(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        return get_served_question(db, contest, play_tracker).await;
+    }
+    play_tracker.current_question = Some(served_question);
+    Ok(question)
+}
+
+/// question recorded by a concurrent request
+async fn get_served_question(
+    db: &Arc<AppDatabase>,
+    contest: &ContestWithQuestion,
+    play_tracker: &mut PlayTracker,
+) -> Result<QuestionWithoutCorrectFlag, AppError> {
+    let filter = doc! {
+        "contestId": &play_tracker.contest_id,
+        "userId": play_tracker.user_id,
+        "status": PlayTrackerStatus::STARTED.to_bson()?
+    };
+    let stored = db
+        .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+        .await?
+        .ok_or(AppError::BadRequestErr("playTracker is not started".into()))?;
+    let question = get_pending_question(contest, &stored)
+        .ok_or(AppError::BadRequestErr("question is already served".into()))?;
+    *play_tracker = stored;
+    Ok(question)
+}
+
+/// the play can not continue once the contest time budget is over
+pub fn check_play_deadline(play_tracker: &PlayTracker) -> Result<(), AppError> {
+    // plays started before the time budget was introduced have no deadline
+    let Some(deadline_ts) = play_tracker.deadline_ts else {
+        return Ok(());
+    };
+    if get_epoch_ts() > deadline_ts {
+        let err = "contest time budget is over";
+        let err = AppError::BadRequestErr(err.into());
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// served question which is not answered yet
+fn get_pending_question(
+    contest: &ContestWithQuestion,
+    play_tracker: &PlayTracker,
+) -> Option<QuestionWithoutCorrectFlag> {
+    let served_question = play_tracker.current_question.as_ref()?;
+    let is_answered = play_tracker
+        .answers
+        .iter()
+        .flatten()
+        .any(|ans| ans.question.props.question_no == served_question.question_no);
+    if is_answered {
+        return None;
+    }
+    contest
+        .questions
+        .as_ref()?
+        .iter()
+        .find(|q| q.is_active && q.props.question_no == served_question.question_no)
+        .map(|q| q.into())
+}
+
 pub fn get_question(
     contest: &ContestWithQuestion,
     play_tracker: &PlayTracker,
@@ -165,24 +256,27 @@ pub async fn check_play_tracker(
 
 async fn update_play_tracker(
     db: &Arc<AppDatabase>,
-    contest_id: &str,
+    contest: &ContestWithQuestion,
     user_id: u32,
     play_tracker: &PlayTracker,
 ) -> Result<PlayTracker, AppError> {
     let filter = doc! {
-        "contestId": contest_id,
+        "contestId": &play_tracker.contest_id,
         "userId": user_id,
-        "status": {"$ne": PlayTrackerStatus::FINISHED.to_bson()?}
+        "status": play_tracker.status.to_bson()?
     };
     let ts = get_epoch_ts() as i64;
     let mut update = doc! {"updatedTs": ts, "updatedBy": user_id};
     let update = match play_tracker.status {
         PlayTrackerStatus::INIT | PlayTrackerStatus::PAID => {
+            let deadline_ts = contest.props.play_deadline(ts as u64);
             update.insert("startTs", ts);
+            update.insert("deadlineTs", deadline_ts as i64);
             update.insert("status", PlayTrackerStatus::STARTED.to_bson()?);
             doc! {"$set": update}
         }
         PlayTrackerStatus::STARTED => {
+            check_play_deadline(play_tracker)?;
             doc! {
                 "$push": {"resumeTs": ts},
                 "$set": update
@@ -195,15 +289,28 @@ async fn update_play_tracker(
         }
     };
 
+    // the player of a free contest gets the slot when starting, a paid player has it already
+    let reserve_slot = play_tracker.status == PlayTrackerStatus::INIT;
+    if reserve_slot {
+        update_joined_count(db, contest, &play_tracker.contest_id, 1).await?;
+    }
     let options = FindOneAndUpdateOptions::builder()
         .return_document(Some(ReturnDocument::After))
         .build();
     let options = Some(options);
-    let play_tracker = db
+    let result = db
         .find_one_and_update::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, filter, update, options)
-        .await?
-        .ok_or(AppError::AnyError(anyhow::anyhow!(
-            "not able to update playTracker"
-        )))?;
-    Ok(play_tracker)
+        .await;
+    match result {
+        Ok(Some(play_tracker)) => Ok(play_tracker),
+        result => {
+            if reserve_slot {
+                update_joined_count(db, contest, &play_tracker.contest_id, -1).await?;
+            }
+            let err = result
+                .err()
+                .unwrap_or(anyhow::anyhow!("not able to update playTracker"));
+            Err(AppError::AnyError(err))
+        }
+    }
 }
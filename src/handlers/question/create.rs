@@ -21,7 +21,8 @@ use crate::{
     request_body = CreateQuesReqBody,
     responses(
         (status = StatusCode::OK, description = "Contest created", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -30,6 +31,7 @@ pub async fn create_question_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<CreateQuesReqBody>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
     let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
     validate_request(&db, &body, &contest_id, true).await?;
@@ -77,22 +79,42 @@ pub async fn validate_request(
             }
         }
     }
-    validate_options(body.question.options.as_ref())?;
+    validate_options(&body.question)?;
 
     Ok(())
 }
 
-fn validate_options(options: &Vec<Answer>) -> Result<(), AppError> {
-    if options.len() != 4 {
-        let err = AppError::BadRequestErr("options array must have 4 values".into());
-        return Err(err);
-    }
+/// Validate the options and the answer of a question as per the question type
+pub fn validate_options(question: &QuestionReqBody) -> Result<(), AppError> {
+    let options = &question.options;
+    let question_type = question.props.question_type;
     let correct_count = options.iter().filter(|ans| ans.is_correct).count();
-    if correct_count != 1 {
-        let err = AppError::BadRequestErr("options must have one correct answer".into());
-        return Err(err);
+    let (options_range, correct_range) = match question_type {
+        QuestionType::SINGLE_CHOICE => (4..=4, 1..=1),
+        QuestionType::TRUE_FALSE => (2..=2, 1..=1),
+        QuestionType::MULTI_SELECT => (2..=10, 1..=10),
+        QuestionType::IMAGE_CHOICE => (2..=10, 1..=1),
+        QuestionType::NUMERIC => (0..=0, 0..=0),
+    };
+    if !options_range.contains(&options.len()) {
+        let err = format!(
+            "options array must have {} to {} values for {:?} question",
+            options_range.start(),
+            options_range.end(),
+            question_type
+        );
+        return Err(AppError::BadRequestErr(err));
+    }
+    if !correct_range.contains(&correct_count) {
+        let err = format!(
+            "options must have {} to {} correct answers for {:?} question",
+            correct_range.start(),
+            correct_range.end(),
+            question_type
+        );
+        return Err(AppError::BadRequestErr(err));
     }
-    if (1..4).any(|idx| {
+    if (1..options.len()).any(|idx| {
         let option_id = options[idx - 1].props.option_id;
         options[idx..]
             .iter()
@@ -107,5 +129,53 @@ fn validate_options(options: &Vec<Answer>) -> Result<(), AppError> {
         let err = AppError::BadRequestErr("imageOrVideoUrl missing in options".into());
         return Err(err);
     }
+    if question_type == QuestionType::IMAGE_CHOICE && options.iter().any(|opt| !opt.props.has_image)
+    {
+        let err = AppError::BadRequestErr("all options must have an image".into());
+        return Err(err);
+    }
+    if question_type == QuestionType::NUMERIC && question.numeric_answer.is_none() {
+        let err = AppError::BadRequestErr("numericAnswer required".into());
+        return Err(err);
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helper::question;
+
+    fn req_body(question_type: QuestionType, options: usize, correct: usize) -> QuestionReqBody {
+        let mut question = question(1);
+        question.props.question_type = question_type;
+        question.options.truncate(options);
+        for (idx, opt) in question.options.iter_mut().enumerate() {
+            opt.is_correct = idx < correct;
+        }
+        QuestionReqBody {
+            props: question.props,
+            options: question.options,
+            numeric_answer: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_options() {
+        assert!(validate_options(&req_body(QuestionType::SINGLE_CHOICE, 4, 1)).is_ok());
+        assert!(validate_options(&req_body(QuestionType::SINGLE_CHOICE, 4, 2)).is_err());
+        assert!(validate_options(&req_body(QuestionType::TRUE_FALSE, 2, 1)).is_ok());
+        assert!(validate_options(&req_body(QuestionType::TRUE_FALSE, 3, 1)).is_err());
+        assert!(validate_options(&req_body(QuestionType::MULTI_SELECT, 4, 3)).is_ok());
+        assert!(validate_options(&req_body(QuestionType::MULTI_SELECT, 4, 0)).is_err());
+        // options of image choice questions must have images
+        assert!(validate_options(&req_body(QuestionType::IMAGE_CHOICE, 3, 1)).is_err());
+        let mut body = req_body(QuestionType::NUMERIC, 0, 0);
+        assert!(validate_options(&body).is_err());
+        body.numeric_answer = Some(NumericAnswer {
+            value: 9.8,
+            tolerance: 0.1,
+        });
+        assert!(validate_options(&body).is_ok());
+    }
+}
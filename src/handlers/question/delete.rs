@@ -21,7 +21,8 @@ use crate::{
     request_body = QuesDelReqBody,
     responses(
         (status = StatusCode::OK, description = "Question deleted", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -30,6 +31,7 @@ pub async fn delete_question_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<QuesDelReqBody>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let claims = claims.data;
     let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
     let ts = get_epoch_ts() as i64;
@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use mongodb::bson::doc;
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{parse_object_id, AppError},
+};
+
+/// Export questions
+///
+/// Download the active questions of a contest as a JSON array or a CSV file.
+/// Both formats can be imported into the question bank.
+#[utoipa::path(
+    get,
+    path = "/api/v1/question/export",
+    params(ExportQuestionsParams, ("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Exported questions", body = String, content_type = "text/csv"),
+        (status = StatusCode::NOT_FOUND, description = "Contest not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn export_questions_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<ExportQuestionsParams>,
+) -> Result<Response, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let contest_id = parse_object_id(&params.contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": contest_id};
+    let contest = db
+        .find_one::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("Contest not found".into()))?;
+    let questions = contest
+        .questions
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|q| q.is_active)
+        .collect::<Vec<_>>();
+    let res = match params.format.unwrap_or_default() {
+        QuestionFileFormat::JSON => {
+            let data = questions
+                .iter()
+                .map(|q| q.into())
+                .collect::<Vec<QuestionReqBody>>();
+            let disposition = "attachment; filename=\"questions.json\"";
+            ([(header::CONTENT_DISPOSITION, disposition)], Json(data)).into_response()
+        }
+        QuestionFileFormat::CSV => {
+            let mut writer = csv::Writer::from_writer(vec![]);
+            for question in questions.iter() {
+                writer.serialize(QuestionCsvRow::from(question))?;
+            }
+            let data = writer.into_inner().map_err(|err| err.into_error())?;
+            let headers = [
+                (header::CONTENT_TYPE, "text/csv"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"questions.csv\"",
+                ),
+            ];
+            (headers, data).into_response()
+        }
+    };
+    Ok(res)
+}
@@ -21,15 +21,17 @@ use crate::{
     security(("authorization" = [])),
     responses(
         (status = StatusCode::OK, description = "question list", body = GetQuestionResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
 pub async fn get_question_handler(
-    _claims: JwtClaimsAdmin,
+    claims: JwtClaimsAdmin,
     State(db): State<Arc<AppDatabase>>,
     params: Query<ContestIdRequest>,
 ) -> Result<Json<GetQuestionResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
     let contest_id = parse_object_id(&params.contest_id, "Not able to parse contestId")?;
     let filter = doc! {"_id": contest_id};
     let contest = db
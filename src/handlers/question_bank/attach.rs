@@ -0,0 +1,91 @@
+use axum::{extract::State, Json};
+use mongodb::bson::doc;
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{get_epoch_ts, parse_object_id, AppError, ValidatedBody},
+};
+
+/// Attach bank questions
+///
+/// Add bank questions to a contest in `CREATED` status, either as a copy or by reference.
+/// The questions get the next question numbers in the given order.
+#[utoipa::path(
+    post,
+    path = "/api/v1/questionBank/attach",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = AttachQuestionsReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Questions attached", body = GenericResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Contest or bank question not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn attach_questions_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<AttachQuestionsReqBody>,
+) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let contest_id = parse_object_id(&body.contest_id, "Not able to parse contestId")?;
+    let filter = doc! {"_id": contest_id, "status": ContestStatus::CREATED.to_bson()?};
+    let contest = db
+        .find_one::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter.clone()), None)
+        .await?
+        .ok_or(AppError::NotFound("Not valid contest".into()))?;
+    let ids = body
+        .bank_question_ids
+        .iter()
+        .map(|id| parse_object_id(id, "Not able to parse bankQuestionId"))
+        .collect::<Result<Vec<_>, _>>()?;
+    let bank_filter = doc! {"_id": {"$in": ids}, "isActive": true};
+    let bank_questions = db
+        .find::<BankQuestion>(DB_NAME, COLL_QUESTION_BANK, Some(bank_filter), None)
+        .await?;
+    let questions = contest.questions.unwrap_or_default();
+    let mut question_no = questions
+        .iter()
+        .map(|q| q.props.question_no)
+        .max()
+        .unwrap_or(0);
+    let mut new_questions = vec![];
+    for (idx, id) in body.bank_question_ids.iter().enumerate() {
+        if body.bank_question_ids[..idx].contains(id) {
+            let err = AppError::BadRequestErr("Duplicate bankQuestionId".into());
+            return Err(err);
+        }
+        let bank_question = bank_questions
+            .iter()
+            .find(|q| q._id.as_ref() == Some(id))
+            .ok_or(AppError::NotFound(format!("bank question {id} not found")))?;
+        if body.mode == AttachMode::REFERENCE
+            && questions
+                .iter()
+                .any(|q| q.is_active && q.bank_question_id.as_ref() == Some(id))
+        {
+            let err = format!("bank question {id} is already attached");
+            return Err(AppError::BadRequestErr(err));
+        }
+        question_no += 1;
+        let question = bank_question.to_question(question_no, body.mode);
+        new_questions.push(question.to_bson()?);
+    }
+    let update = doc! {
+        "$push": {"questions": {"$each": new_questions}},
+        "$set": {"updatedTs": get_epoch_ts() as i64, "updatedBy": claims.data.id}
+    };
+    db.update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+        .await?;
+    let res = GenericResponse {
+        success: true,
+        message: "Attached successfully".to_owned(),
+    };
+    Ok(Json(res))
+}
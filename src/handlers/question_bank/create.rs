@@ -0,0 +1,170 @@
+use axum::{extract::State, Json};
+use mongodb::bson::{doc, to_document, Document};
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    handlers::question::create::validate_options,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{get_epoch_ts, parse_object_id, AppError, ValidatedBody},
+};
+
+/// Bank question create
+///
+/// Add a new question to the question bank
+#[utoipa::path(
+    post,
+    path = "/api/v1/questionBank",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = BankQuestionProps,
+    responses(
+        (status = StatusCode::OK, description = "Bank question created", body = BankQuestionResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_bank_question_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<BankQuestionProps>,
+) -> Result<Json<BankQuestionResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    validate_bank_question(&body).map_err(AppError::BadRequestErr)?;
+    let mut question = BankQuestion::new(body, claims.data.id);
+    let inserted_id = db
+        .insert_one::<BankQuestion>(DB_NAME, COLL_QUESTION_BANK, &question, None)
+        .await?;
+    question._id = Some(inserted_id);
+    let res = BankQuestionResponse {
+        success: true,
+        data: question,
+    };
+    Ok(Json(res))
+}
+
+/// Bank question update
+///
+/// Update a bank question. Questions attached by reference to contests in `CREATED` status
+/// are updated as well.
+#[utoipa::path(
+    post,
+    path = "/api/v1/questionBank/update",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = UpdateBankQuestionReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Bank question updated", body = BankQuestionResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Bank question not found", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_bank_question_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<UpdateBankQuestionReqBody>,
+) -> Result<Json<BankQuestionResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    validate_bank_question(&body.props).map_err(AppError::BadRequestErr)?;
+    let id = parse_object_id(&body._id, "Not able to parse _id")?;
+    let filter = doc! {"_id": id, "isActive": true};
+    let existing = db
+        .find_one::<Document>(DB_NAME, COLL_QUESTION_BANK, Some(filter.clone()), None)
+        .await?
+        .ok_or(AppError::NotFound("bank question not found".into()))?;
+    let mut set_obj = to_document(&body.props)?;
+    set_obj.insert("updatedTs", get_epoch_ts() as i64);
+    set_obj.insert("updatedBy", claims.data.id);
+    // optional fields which are not present anymore must be removed
+    let keep = ["_id", "isActive", "createdTs", "createdBy"];
+    let mut unset_obj = Document::new();
+    for key in existing.keys() {
+        if !set_obj.contains_key(key) && !keep.contains(&key.as_str()) {
+            unset_obj.insert(key, "");
+        }
+    }
+    let mut update = doc! {"$set": set_obj};
+    if !unset_obj.is_empty() {
+        update.insert("$unset", unset_obj);
+    }
+    db.update_one(DB_NAME, COLL_QUESTION_BANK, filter.clone(), update, None)
+        .await?;
+    let question = db
+        .find_one::<BankQuestion>(DB_NAME, COLL_QUESTION_BANK, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("bank question not found".into()))?;
+    update_referenced_questions(&db, &question, claims.data.id).await?;
+    let res = BankQuestionResponse {
+        success: true,
+        data: question,
+    };
+    Ok(Json(res))
+}
+
+/// Validate the media url and the options of a bank question
+pub fn validate_bank_question(props: &BankQuestionProps) -> Result<(), String> {
+    let question = &props.question;
+    if (question.props.has_image || question.props.has_video)
+        && question.props.image_or_video_url.is_none()
+    {
+        return Err("imageOrVideoUrl missing".into());
+    }
+    validate_options(question).map_err(|err| match err {
+        AppError::BadRequestErr(msg) => msg,
+        err => format!("{:?}", err),
+    })
+}
+
+/// Replace the questions attached by reference in the contests which are not yet activated
+async fn update_referenced_questions(
+    db: &Arc<AppDatabase>,
+    bank_question: &BankQuestion,
+    user_id: u32,
+) -> Result<(), AppError> {
+    let Some(bank_question_id) = bank_question._id.as_ref() else {
+        return Ok(());
+    };
+    let filter = doc! {
+        "status": ContestStatus::CREATED.to_bson()?,
+        "questions.bankQuestionId": bank_question_id
+    };
+    let contests = db
+        .find::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?;
+    for contest in contests {
+        let Some(contest_id) = contest._id.as_ref() else {
+            continue;
+        };
+        let questions = contest
+            .questions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|question| {
+                if question.bank_question_id.as_ref() != Some(bank_question_id) {
+                    return question;
+                }
+                let mut updated =
+                    bank_question.to_question(question.props.question_no, AttachMode::REFERENCE);
+                updated.is_active = question.is_active;
+                updated
+            })
+            .collect::<Vec<_>>();
+        let contest_id = parse_object_id(contest_id, "Not able to parse contestId")?;
+        let filter = doc! {"_id": contest_id, "status": ContestStatus::CREATED.to_bson()?};
+        let update = doc! {
+            "$set": {
+                "questions": mongodb::bson::to_bson(&questions)?,
+                "updatedTs": get_epoch_ts() as i64,
+                "updatedBy": user_id
+            }
+        };
+        db.update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+    }
+    Ok(())
+}
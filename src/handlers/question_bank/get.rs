@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use mongodb::{bson::doc, options::FindOptions};
+use std::sync::Arc;
+
+use crate::{constants::*, database::AppDatabase, jwt::JwtClaimsAdmin, models::*, utils::AppError};
+
+/// Get bank questions
+///
+/// Search the question bank by tag, difficulty, category and question type, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/questionBank",
+    params(
+        ("authorization" = String, Header, description = "Admin JWT token"),
+        GetBankQuestionsParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Bank question list", body = GetBankQuestionsResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn get_bank_questions_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<GetBankQuestionsParams>,
+) -> Result<Json<GetBankQuestionsResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let mut filter = doc! {"isActive": true};
+    if let Some(tag) = params.tag.as_ref() {
+        filter.insert("tags", tag);
+    }
+    if let Some(difficulty) = params.difficulty.as_ref() {
+        filter.insert("difficulty", difficulty.to_bson()?);
+    }
+    if let Some(category) = params.category.as_ref() {
+        filter.insert("category", category);
+    }
+    if let Some(question_type) = params.question_type.as_ref() {
+        filter.insert("questionType", mongodb::bson::to_bson(question_type)?);
+    }
+    let page_index = params.page_index.unwrap_or(0);
+    let page_size = params.page_size.unwrap_or(DEFAULT_QUERY_LIMIT);
+    let options = FindOptions::builder()
+        .sort(Some(doc! {"_id": -1}))
+        .skip(Some(page_index * page_size))
+        .limit(Some(page_size as i64))
+        .build();
+    let data = db
+        .find::<BankQuestion>(DB_NAME, COLL_QUESTION_BANK, Some(filter), Some(options))
+        .await?;
+    let res = GetBankQuestionsResponse {
+        success: true,
+        data,
+    };
+    Ok(Json(res))
+}
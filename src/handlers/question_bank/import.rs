@@ -0,0 +1,195 @@
+use axum::{extract::State, Json};
+use std::sync::Arc;
+use validator::Validate;
+
+use super::create::validate_bank_question;
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{AppError, ValidatedBody},
+};
+
+/// Import bank questions
+///
+/// Bulk import questions into the question bank from a JSON array or a CSV file.
+/// Every row is validated and nothing is imported when any row has an error.
+#[utoipa::path(
+    post,
+    path = "/api/v1/questionBank/import",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = ImportQuestionsReqBody,
+    responses(
+        (status = StatusCode::OK, description = "Import result with the row errors", body = ImportQuestionsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "File not readable", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn import_questions_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<ImportQuestionsReqBody>,
+) -> Result<Json<ImportQuestionsResponse>, AppError> {
+    claims.check_permission(AdminPermission::CONTENT)?;
+    let rows = match body.format {
+        QuestionFileFormat::JSON => parse_json(&body.content)?,
+        QuestionFileFormat::CSV => parse_csv(&body.content)?,
+    };
+    if rows.is_empty() {
+        let err = AppError::BadRequestErr("no questions found".into());
+        return Err(err);
+    }
+    let mut questions = vec![];
+    let mut errors = vec![];
+    for (row, result) in (1..).zip(rows) {
+        let result = result.and_then(|props| {
+            props.validate().map_err(|err| err.to_string())?;
+            validate_bank_question(&props)?;
+            Ok(props)
+        });
+        match result {
+            Ok(props) => questions.push(BankQuestion::new(props, claims.data.id)),
+            Err(message) => errors.push(ImportRowError { row, message }),
+        }
+    }
+    if !errors.is_empty() {
+        let res = ImportQuestionsResponse {
+            success: false,
+            inserted_count: 0,
+            errors,
+        };
+        return Ok(Json(res));
+    }
+    let inserted_ids = db
+        .insert_many::<BankQuestion>(DB_NAME, COLL_QUESTION_BANK, &questions, None)
+        .await?;
+    let res = ImportQuestionsResponse {
+        success: true,
+        inserted_count: inserted_ids.len() as u32,
+        errors,
+    };
+    Ok(Json(res))
+}
+
+type ImportRow = Result<BankQuestionProps, String>;
+
+fn parse_json(content: &str) -> Result<Vec<ImportRow>, AppError> {
+    let values = serde_json::from_str::<Vec<serde_json::Value>>(content)
+        .map_err(|err| AppError::BadRequestErr(format!("invalid JSON array: {err}")))?;
+    let rows = values
+        .into_iter()
+        .map(|value| serde_json::from_value::<BankQuestionProps>(value).map_err(|e| e.to_string()))
+        .collect();
+    Ok(rows)
+}
+
+fn parse_csv(content: &str) -> Result<Vec<ImportRow>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+    reader
+        .headers()
+        .map_err(|err| AppError::BadRequestErr(format!("invalid CSV header: {err}")))?;
+    let rows = (1..)
+        .zip(reader.deserialize::<QuestionCsvRow>())
+        .map(|(question_no, row)| {
+            row.map_err(|err| err.to_string())
+                .and_then(|row| row.into_bank_question(question_no))
+        })
+        .collect();
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::{models::*, utils::test_helper::*};
+
+    #[tokio::test]
+    async fn test_question_bank_flow() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        let uri = "/api/v1/questionBank/import";
+        let content = "questionText,questionType,options,correctOptions,numericAnswer,category,difficulty,tags\n\
+            Capital of France?,SINGLE_CHOICE,Paris|Rome|Berlin|Madrid,1,,geography,EASY,europe|capital\n\
+            Sun is a star,TRUE_FALSE,True|False,1,,science,,\n\
+            Value of g?,NUMERIC,,,9.8,science,HARD,\n";
+        // the invalid second row fails the whole import
+        let invalid = content.replace("True|False,1", "True|False,3");
+        let body = json!({"format": "CSV", "content": invalid});
+        let (status, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["success"], false);
+        assert_eq!(res["errors"][0]["row"], 2);
+        assert_eq!(res["errors"].as_array().unwrap().len(), 1);
+        let body = json!({"format": "CSV", "content": content});
+        let (_, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(res["insertedCount"], 3);
+
+        let uri = "/api/v1/questionBank?tag=europe";
+        let (status, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"].as_array().unwrap().len(), 1);
+        let uri = "/api/v1/questionBank?category=science";
+        let (_, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        let ids = res["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| q["_id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids.len(), 2);
+
+        // attach one question by reference and one by copy
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::CREATED, 1).await;
+        let uri = "/api/v1/questionBank/attach";
+        let body =
+            json!({"contestId": contest_id, "bankQuestionIds": [ids[0]], "mode": "REFERENCE"});
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body = json!({"contestId": contest_id, "bankQuestionIds": [ids[1]]});
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // update of the bank question is applied to the referenced question only
+        let uri = "/api/v1/questionBank?category=science";
+        let (_, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        let mut updated = res["data"][0].clone();
+        updated["questionText"] = json!("updated question");
+        let uri = "/api/v1/questionBank/update";
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(updated)).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut copied = res["data"][1].clone();
+        copied["questionText"] = json!("updated copy");
+        send_admin_request(&db, Method::POST, uri, 100, Some(copied)).await;
+
+        let uri = format!("/api/v1/question/export?contestId={contest_id}&format=JSON");
+        let (status, res) = send_admin_request(&db, Method::GET, &uri, 100, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let texts = res
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| (q["questionNo"].as_u64().unwrap(), q["questionText"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(texts[1], (2, json!("updated question")));
+        assert_eq!(texts[2].0, 3);
+        assert_ne!(texts[2].1, json!("updated copy"));
+
+        // exported CSV can be imported back
+        let uri = format!("/api/v1/question/export?contestId={contest_id}&format=CSV");
+        let content = send_admin_text_request(&db, Method::GET, &uri, 100).await;
+        let uri = "/api/v1/questionBank/import";
+        let body = json!({"format": "CSV", "content": content});
+        let (_, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(res["insertedCount"], 3);
+    }
+}
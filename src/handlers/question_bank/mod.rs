@@ -0,0 +1,4 @@
+pub mod attach;
+pub mod create;
+pub mod get;
+pub mod import;
@@ -22,11 +22,12 @@ use crate::{
 pub struct Params {
     user_id: u32,
     name: Option<String>,
+    admin_token: Option<bool>,
 }
 
 /// Temporary API to get token
 ///
-/// Returns a JWT token for an user
+/// Returns a JWT token for an user, or for an admin user when `adminToken` is true
 #[utoipa::path(
     get,
     path = "/api/v1/tempApiGetToken",
@@ -37,7 +38,11 @@ pub struct Params {
     tag = "Debugging API"
 )]
 pub async fn temp_api_get_token(params: Query<Params>) -> Result<Json<JsonValue>, AppError> {
-    let token = JWT_KEYS.generate_token(params.user_id, params.name.clone())?;
+    let token = if params.admin_token == Some(true) {
+        JWT_KEYS.generate_admin_token(params.user_id, params.name.clone())?
+    } else {
+        JWT_KEYS.generate_token(params.user_id, params.name.clone())?
+    };
     let res = json!({"success": true, "token": token});
     Ok(Json(res))
 }
@@ -1,11 +1,13 @@
 use aws_sdk_s3::model::{CompletedMultipartUpload, CompletedPart};
 use axum::{
-    extract::{Multipart, Query},
+    extract::{Multipart, Query, State},
     Json,
 };
+use std::sync::Arc;
 use validator::Validate;
 
 use crate::{
+    config::AppConfig,
     constants::*,
     models::*,
     utils::{AppError, ValidatedBody},
@@ -23,6 +25,7 @@ use crate::{
     tag = "App User API"
 )]
 pub async fn create_multipart_handler(
+    State(app_config): State<Arc<AppConfig>>,
     Query(param): Query<MultipartUploadInitiateReq>,
 ) -> Result<Json<MultipartUploadInitiateRes>, AppError> {
     param
@@ -33,7 +36,7 @@ pub async fn create_multipart_handler(
     let key = super::uniq_file_name(&param.file_name);
     let res = client
         .create_multipart_upload()
-        .bucket(AWS_BUCKET)
+        .bucket(&app_config.aws.bucket)
         .key(&key)
         .send()
         .await
@@ -68,6 +71,7 @@ pub async fn create_multipart_handler(
     tag = "App User API"
 )]
 pub async fn upload_part_multipart_handler(
+    State(app_config): State<Arc<AppConfig>>,
     Query(param): Query<MultipartUploadPartReq>,
     mut files: Multipart,
 ) -> Result<Json<UploadPartRes>, AppError> {
@@ -88,7 +92,7 @@ pub async fn upload_part_multipart_handler(
     let res = client
         .upload_part()
         .key(&param.key)
-        .bucket(AWS_BUCKET)
+        .bucket(&app_config.aws.bucket)
         .upload_id(&param.upload_id)
         .body(data.into())
         .part_number(param.part_number)
@@ -132,6 +136,7 @@ pub async fn upload_part_multipart_handler(
     tag = "App User API"
 )]
 pub async fn complete_multipart_handler(
+    State(app_config): State<Arc<AppConfig>>,
     ValidatedBody(body): ValidatedBody<CompleteMultipartUploadReq>,
 ) -> Result<Json<FileUploadRes>, AppError> {
     validate_finish_request_body(&body)?;
@@ -152,7 +157,7 @@ pub async fn complete_multipart_handler(
         .build();
     let res = client
         .complete_multipart_upload()
-        .bucket(AWS_BUCKET)
+        .bucket(&app_config.aws.bucket)
         .key(body.key.as_str())
         .upload_id(body.upload_id.as_str())
         .multipart_upload(completed_parts)
@@ -1,7 +1,11 @@
-use axum::{extract::Multipart, Json};
+use axum::{
+    extract::{Multipart, State},
+    Json,
+};
+use std::sync::Arc;
 
 use crate::{
-    constants::*,
+    config::AppConfig,
     models::*,
     utils::{get_object_url, AppError},
 };
@@ -16,7 +20,10 @@ use crate::{
     ),
     tag = "App User API"
 )]
-pub async fn upload_handler(mut files: Multipart) -> Result<Json<FileUploadRes>, AppError> {
+pub async fn upload_handler(
+    State(app_config): State<Arc<AppConfig>>,
+    mut files: Multipart,
+) -> Result<Json<FileUploadRes>, AppError> {
     let config = aws_config::load_from_env().await;
     let client = aws_sdk_s3::Client::new(&config);
     let file = files
@@ -34,7 +41,7 @@ pub async fn upload_handler(mut files: Multipart) -> Result<Json<FileUploadRes>,
     let key = super::uniq_file_name(&file_name);
     let resp = client
         .put_object()
-        .bucket(AWS_BUCKET)
+        .bucket(&app_config.aws.bucket)
         .key(&key)
         .body(data.into())
         .send()
@@ -43,7 +50,7 @@ pub async fn upload_handler(mut files: Multipart) -> Result<Json<FileUploadRes>,
     let e_tag = resp
         .e_tag
         .ok_or(anyhow::anyhow!("unable to get ETag value"))?;
-    let url = get_object_url(&key);
+    let url = get_object_url(&app_config.aws, &key);
     let res = FileUploadRes {
         success: true,
         e_tag,
@@ -6,12 +6,17 @@ use mongodb::bson::doc;
 use std::sync::Arc;
 
 use crate::{
+    config::AppConfig,
     constants::*,
     database::AppDatabase,
-    handlers::user::{check_otp::check_and_update_otp, otp::generate_send_otp_admin},
+    handlers::user::{
+        check_otp::check_and_update_otp, otp::generate_send_otp_admin,
+        otp_limit::check_otp_send_limit,
+    },
     jwt::JWT_KEYS,
     models::*,
-    utils::{get_seq_nxt_val, AppError, ValidatedBody},
+    sms::SmsSender,
+    utils::{get_epoch_ts, get_seq_nxt_val, AppError, ClientIp, ValidatedBody},
 };
 
 /// Admin login
@@ -24,6 +29,7 @@ use crate::{
     responses(
         (status = StatusCode::OK, description = "Use successfully redeems referral code", body = AdminLoginResponse),
         (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse),
         (status = StatusCode::NOT_FOUND, description = "User/otp not found", body = GenericResponse)
     ),
     tag = "Admin API"
@@ -33,8 +39,8 @@ pub async fn admin_login_handler(
     ValidatedBody(body): ValidatedBody<CheckOtpReq>,
 ) -> Result<Json<AdminLoginResponse>, AppError> {
     let user = check_user(&db, &body.phone).await?;
-    check_and_update_otp(user.id, &body.otp, &db).await?;
-    let token = JWT_KEYS.generate_token(user.id, Some(user.name.to_string()))?;
+    check_and_update_otp(user.id, &body.phone, &body.otp, &db).await?;
+    let token = JWT_KEYS.generate_admin_token(user.id, Some(user.name.to_string()))?;
     let response = AdminLoginResponse {
         success: true,
         data: user,
@@ -54,40 +60,53 @@ async fn check_user(db: &Arc<AppDatabase>, phone: &str) -> Result<AdminUser, App
 
 /// Admin signup
 ///
-/// Creates a new admin user
+/// Bootstrap the first super admin. The signup is allowed only for the `BOOTSTRAP_ADMIN_PHONE`
+/// until a super admin exists, the other admins are created by a super admin.
 #[utoipa::path(
     post,
     path = "/api/v1/admin/signup",
     request_body = AdminSignupRequest,
     responses(
-        (status = StatusCode::OK, description = "Use successfully redeems referral code", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::OK, description = "Super admin created & OTP is generated", body = GenericResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Admin signup is closed", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
 pub async fn admin_signup_handler(
+    State(config): State<Arc<AppConfig>>,
     State(db): State<Arc<AppDatabase>>,
+    State(sms_sender): State<Arc<dyn SmsSender>>,
+    ClientIp(ip): ClientIp,
     ValidatedBody(body): ValidatedBody<AdminSignupRequest>,
 ) -> Result<Json<GenericResponse>, AppError> {
-    let id = get_seq_nxt_val(USER_ID_SEQ, &db).await?;
+    check_bootstrap_allowed(&config, &db, &body.phone).await?;
+    check_otp_send_limit(&db, &body.phone, ip.as_deref()).await?;
     let filter = doc! {"phone": &body.phone};
     let user = db
-        .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter), None)
+        .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter.clone()), None)
         .await?;
-    if user.is_some() {
-        let err = format!("User already exists with same phone: {}", body.phone);
-        let err = AppError::BadRequestErr(err);
-        return Err(err);
-    }
-    let user = AdminUser {
-        id,
-        name: body.name,
-        phone: body.phone,
-        is_active: true,
+    let user_id = match user {
+        // an admin created before the roles is promoted
+        Some(user) => {
+            let ts = get_epoch_ts() as i64;
+            let role = AdminRole::SUPER_ADMIN.to_bson()?;
+            let update = doc! {"$set": {"role": role, "isActive": true, "updatedTs": ts}};
+            db.update_one(DB_NAME, COLL_ADMIN_USERS, filter, update, None)
+                .await?;
+            user.id
+        }
+        None => {
+            let id = get_seq_nxt_val(USER_ID_SEQ, &db).await?;
+            let user = AdminUser::new(id, body.name, body.phone, AdminRole::SUPER_ADMIN, None);
+            db.insert_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, &user, None)
+                .await?;
+            id
+        }
     };
-    db.insert_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, &user, None)
-        .await?;
-    generate_send_otp_admin(user.id, &db).await?;
+    tracing::info!("admin {} is bootstrapped as super admin", user_id);
+    generate_send_otp_admin(user_id, &db, &sms_sender).await?;
     let res = GenericResponse {
         success: true,
         message: "Otp generated".to_owned(),
@@ -95,6 +114,26 @@ pub async fn admin_signup_handler(
     Ok(Json(res))
 }
 
+async fn check_bootstrap_allowed(
+    config: &AppConfig,
+    db: &Arc<AppDatabase>,
+    phone: &str,
+) -> Result<(), AppError> {
+    if config.bootstrap_admin_phone.as_deref() != Some(phone) {
+        let err = AppError::Forbidden("Admin signup is not allowed".into());
+        return Err(err);
+    }
+    let filter = doc! {"role": AdminRole::SUPER_ADMIN.to_bson()?, "isActive": true};
+    let super_admin = db
+        .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter), None)
+        .await?;
+    if super_admin.is_some() {
+        let err = "Super admin already exists, admins are created by the super admin";
+        return Err(AppError::Forbidden(err.into()));
+    }
+    Ok(())
+}
+
 /// Verify phone and generate otp
 ///
 /// Verify phone if it is valid admin user's phone and generate an otp
@@ -105,16 +144,20 @@ pub async fn admin_signup_handler(
     responses(
         (status = StatusCode::OK, description = "Valid user & OTP is generated", body = GenericResponse),
         (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse),
         (status = StatusCode::NOT_FOUND, description = "User not found", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
 pub async fn admin_generate_otp(
     State(db): State<Arc<AppDatabase>>,
+    State(sms_sender): State<Arc<dyn SmsSender>>,
+    ClientIp(ip): ClientIp,
     params: Query<VerifyUserReq>,
 ) -> Result<Json<GenericResponse>, AppError> {
     let user = check_user(&db, &params.phone).await?;
-    generate_send_otp_admin(user.id, &db).await?;
+    check_otp_send_limit(&db, &user.phone, ip.as_deref()).await?;
+    generate_send_otp_admin(user.id, &db, &sms_sender).await?;
     let res = GenericResponse {
         success: true,
         message: "Otp generated".to_owned(),
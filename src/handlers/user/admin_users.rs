@@ -0,0 +1,203 @@
+use axum::{extract::State, Json};
+use mongodb::bson::doc;
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{get_epoch_ts, get_seq_nxt_val, AppError, ValidatedBody},
+};
+
+/// Get admin users
+///
+/// List all the admin users with their roles. Only a super admin can manage the admin users.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Admin users", body = AdminUsersResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn get_admin_users_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+) -> Result<Json<AdminUsersResponse>, AppError> {
+    claims.check_permission(AdminPermission::ADMIN_USERS)?;
+    let users = db
+        .find::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, None, None)
+        .await?;
+    let res = AdminUsersResponse {
+        success: true,
+        data: users,
+    };
+    Ok(Json(res))
+}
+
+/// Create admin user
+///
+/// Create an admin user with a role, the admin logs in with the otp sent to the phone
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = AdminCreateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Admin user created", body = AdminUserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn create_admin_user_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<AdminCreateRequest>,
+) -> Result<Json<AdminUserResponse>, AppError> {
+    claims.check_permission(AdminPermission::ADMIN_USERS)?;
+    let filter = doc! {"phone": &body.phone};
+    let user = db
+        .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter), None)
+        .await?;
+    if user.is_some() {
+        let err = format!("User already exists with same phone: {}", body.phone);
+        return Err(AppError::BadRequestErr(err));
+    }
+    let id = get_seq_nxt_val(USER_ID_SEQ, &db).await?;
+    let user = AdminUser::new(id, body.name, body.phone, body.role, Some(claims.data.id));
+    db.insert_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, &user, None)
+        .await?;
+    let res = AdminUserResponse {
+        success: true,
+        data: user,
+    };
+    Ok(Json(res))
+}
+
+/// Update admin user
+///
+/// Change the name, the role or the active status of an admin user.
+/// A super admin can not change the own role or status.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/update",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = AdminUpdateRequest,
+    responses(
+        (status = StatusCode::OK, description = "Admin user updated", body = AdminUserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Admin user not found", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn update_admin_user_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<AdminUpdateRequest>,
+) -> Result<Json<AdminUserResponse>, AppError> {
+    claims.check_permission(AdminPermission::ADMIN_USERS)?;
+    if body.id == claims.data.id && (body.role.is_some() || body.is_active.is_some()) {
+        let err = "Not allowed to change own role or status";
+        return Err(AppError::BadRequestErr(err.into()));
+    }
+    let ts = get_epoch_ts() as i64;
+    let mut update = doc! {"updatedTs": ts, "updatedBy": claims.data.id};
+    if let Some(name) = body.name {
+        update.insert("name", name);
+    }
+    if let Some(role) = body.role {
+        update.insert("role", role.to_bson()?);
+    }
+    if let Some(is_active) = body.is_active {
+        update.insert("isActive", is_active);
+    }
+    let filter = doc! {"id": body.id};
+    let update = doc! {"$set": update};
+    let result = db
+        .update_one(DB_NAME, COLL_ADMIN_USERS, filter.clone(), update, None)
+        .await?;
+    if result.matched_count == 0 {
+        return Err(AppError::NotFound("admin user not found".into()));
+    }
+    let user = db
+        .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter), None)
+        .await?
+        .ok_or(AppError::NotFound("admin user not found".into()))?;
+    let res = AdminUserResponse {
+        success: true,
+        data: user,
+    };
+    Ok(Json(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_admin_roles() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        insert_admin_with_role(&db, 101, AdminRole::FINANCE).await;
+
+        let uri = "/api/v1/admin/withdrawals";
+        let (status, _) = send_admin_request(&db, Method::GET, uri, 101, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) =
+            send_admin_request(&db, Method::GET, "/api/v1/admin/users", 101, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // super admin creates a content editor who can not see the withdrawals
+        let body = json!({"phone": "9000000003", "name": "editor", "role": "CONTENT_EDITOR"});
+        let uri = "/api/v1/admin/users";
+        let (status, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let editor_id = res["data"]["id"].as_u64().unwrap() as u32;
+        let uri = "/api/v1/admin/withdrawals";
+        let (status, _) = send_admin_request(&db, Method::GET, uri, editor_id, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let uri = "/api/v1/admin/users/update";
+        let body = json!({"id": 100, "role": "SUPPORT"});
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body = json!({"id": editor_id, "isActive": false});
+        let (status, res) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["isActive"], false);
+        let uri = "/api/v1/admin/users";
+        let (status, _) = send_admin_request(&db, Method::GET, uri, editor_id, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // signup is closed once a super admin exists
+        let body = json!({"phone": BOOTSTRAP_ADMIN_PHONE, "name": "owner"});
+        let uri = "/api/v1/admin/signup";
+        let (status, _) = send_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let db = test_db();
+        let (status, _) = send_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        let filter = doc! {"phone": BOOTSTRAP_ADMIN_PHONE};
+        let admin = db
+            .find_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(admin.role, Some(AdminRole::SUPER_ADMIN));
+    }
+}
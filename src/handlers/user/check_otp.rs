@@ -9,7 +9,10 @@ use validator::Validate;
 use crate::{
     constants::*,
     database::AppDatabase,
-    handlers::user::login::update_user_login,
+    handlers::user::{
+        login::update_user_login,
+        otp_limit::{check_lockout, record_failed_attempt, reset_failed_attempts},
+    },
     jwt::JWT_KEYS,
     models::*,
     utils::{get_epoch_ts, AppError},
@@ -27,6 +30,7 @@ use crate::{
     responses(
         (status = StatusCode::OK, description = "Login successful", body = LoginResponse),
         (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse),
         (status = StatusCode::NOT_FOUND, description = "User not found", body = GenericResponse)
     ),
     tag = "App User API"
@@ -45,7 +49,7 @@ pub async fn check_otp_handler(
         .find_one::<User>(DB_NAME, COLL_USERS, filter, None)
         .await?
         .ok_or(not_found)?;
-    check_and_update_otp(user.id, params.otp.as_str(), &db).await?;
+    check_and_update_otp(user.id, &params.phone, params.otp.as_str(), &db).await?;
     update_user_login(&db, user.id, LoginScheme::OTP_BASED).await?;
     let token = JWT_KEYS.generate_token(user.id, Some(user.name.to_owned()))?;
     let refresh_token = JWT_KEYS.generate_refresh_token(user.id, None)?;
@@ -60,18 +64,29 @@ pub async fn check_otp_handler(
     Ok(Json(response))
 }
 
+/// Mark the otp as used if it is valid. A wrong otp is counted as failed attempt
+/// and the phone is rejected while it is locked out.
 pub async fn check_and_update_otp(
     user_id: u32,
+    phone: &str,
     otp: &str,
     db: &Arc<AppDatabase>,
 ) -> Result<(), AppError> {
+    check_lockout(db, phone).await?;
     let ts = get_epoch_ts() as i64;
-    let filter = doc! {"userId": user_id, "otp": &otp, "validTill": {"$gte": ts}, "isUsed": false};
+    let filter = doc! {
+        "userId": user_id,
+        "otp": &otp,
+        "validTill": {"$gte": ts},
+        "isUsed": false,
+        "attempts": {"$not": {"$gte": OTP_MAX_VERIFY_ATTEMPTS}}
+    };
     let update = doc! {"$set": {"isUsed": true, "updateTs": ts}};
     let result = db
         .update_one(DB_NAME, COLL_OTP, filter, update, None)
         .await?;
     if result.matched_count == 0 {
+        record_failed_attempt(db, user_id, phone).await?;
         let err = format!("Not valid otp");
         return Err(AppError::NotFound(err));
     }
@@ -79,5 +94,6 @@ pub async fn check_and_update_otp(
         let err = anyhow::anyhow!("Not able to update otp in DB");
         return Err(AppError::AnyError(err));
     }
+    reset_failed_attempts(db, phone).await?;
     Ok(())
 }
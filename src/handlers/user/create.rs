@@ -2,12 +2,15 @@ use axum::{extract::State, http::StatusCode, Json};
 use mongodb::bson::{doc, Document};
 use std::sync::Arc;
 
-use super::otp::generate_send_otp;
+use super::{otp::generate_send_otp, otp_limit::check_otp_send_limit};
 use crate::{
     constants::*,
     database::AppDatabase,
     models::*,
-    utils::{generate_referral_code, get_epoch_ts, get_seq_nxt_val, AppError, ValidatedBody},
+    sms::SmsSender,
+    utils::{
+        generate_referral_code, get_epoch_ts, get_seq_nxt_val, AppError, ClientIp, ValidatedBody,
+    },
 };
 
 impl CreateUserReq {
@@ -39,16 +42,20 @@ impl CreateUserReq {
     request_body = CreateUserReq,
     responses(
         (status = StatusCode::CREATED, description = "User created successfully", body = GenericResponse),
-        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse)
+        (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse)
     ),
     tag = "App User API"
 )]
 pub async fn create_user_handler(
     State(db): State<Arc<AppDatabase>>,
+    State(sms_sender): State<Arc<dyn SmsSender>>,
+    ClientIp(ip): ClientIp,
     ValidatedBody(body): ValidatedBody<CreateUserReq>,
 ) -> Result<(StatusCode, Json<GenericResponse>), AppError> {
     // check if phone already exists in the DB
     check_uniq_phone(&db, body.phone.as_str()).await?;
+    check_otp_send_limit(&db, &body.phone, ip.as_deref()).await?;
     // check if email already exists in the DB
     if let Some(email) = &body.email {
         check_uniq_email(&db, email.as_str()).await?;
@@ -57,7 +64,7 @@ pub async fn create_user_handler(
     db.insert_one::<User>(DB_NAME, COLL_USERS, &user, None)
         .await?;
     // generate and send otp to the phone
-    generate_send_otp(user.id, &db).await?;
+    generate_send_otp(user.id, &db, &sms_sender).await?;
     // return successful response
     let response = GenericResponse {
         success: true,
@@ -1,9 +1,11 @@
 pub mod admin_login;
+pub mod admin_users;
 pub mod check_otp;
 pub mod create;
 pub mod get_leaderboard;
 pub mod login;
 pub mod otp;
+pub mod otp_limit;
 pub mod referral;
 pub mod renew_token;
 pub mod update;
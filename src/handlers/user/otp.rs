@@ -1,11 +1,16 @@
-use mongodb::bson::doc;
+use mongodb::bson::{doc, oid::ObjectId, to_bson};
 use std::sync::Arc;
 
 use crate::{
     constants::*,
     database::AppDatabase,
-    models::{otp::Otp, user::User, AdminUser},
-    utils::generate_otp,
+    models::{
+        otp::{Otp, OtpDeliveryStatus},
+        user::User,
+        AdminUser,
+    },
+    sms::SmsSender,
+    utils::{generate_otp, get_epoch_ts},
 };
 
 /// Get user details from database by user Id
@@ -28,7 +33,11 @@ pub async fn get_admin_user_by_id(
 }
 
 /// Generate a random otp, save into the otp collection and send to user's phone
-pub async fn generate_send_otp(user_id: u32, db: &Arc<AppDatabase>) -> anyhow::Result<()> {
+pub async fn generate_send_otp(
+    user_id: u32,
+    db: &Arc<AppDatabase>,
+    sms_sender: &Arc<dyn SmsSender>,
+) -> anyhow::Result<()> {
     let user = get_user_by_id(user_id, db)
         .await?
         .ok_or(anyhow::anyhow!("User not found with id: {user_id}"))?;
@@ -36,26 +45,103 @@ pub async fn generate_send_otp(user_id: u32, db: &Arc<AppDatabase>) -> anyhow::R
         let err = anyhow::anyhow!("User phone not found");
         return Err(err);
     };
-    let otp = generate_otp(OTP_LENGTH);
-    let otp = Otp::new(user_id, otp.as_str());
-    db.insert_one::<Otp>(DB_NAME, COLL_OTP, &otp, None).await?;
-    send_otp(phone, &otp.otp);
-    Ok(())
+    save_send_otp(user_id, phone, db, sms_sender).await
 }
 
 /// Generate a random otp for admin, save into the otp collection and send to user's phone
-pub async fn generate_send_otp_admin(user_id: u32, db: &Arc<AppDatabase>) -> anyhow::Result<()> {
+pub async fn generate_send_otp_admin(
+    user_id: u32,
+    db: &Arc<AppDatabase>,
+    sms_sender: &Arc<dyn SmsSender>,
+) -> anyhow::Result<()> {
     let user = get_admin_user_by_id(user_id, db)
         .await?
         .ok_or(anyhow::anyhow!("User not found with id: {user_id}"))?;
+    save_send_otp(user_id, &user.phone, db, sms_sender).await
+}
+
+/// Save a new otp, send it to the phone and record the delivery status with the otp
+async fn save_send_otp(
+    user_id: u32,
+    phone: &str,
+    db: &Arc<AppDatabase>,
+    sms_sender: &Arc<dyn SmsSender>,
+) -> anyhow::Result<()> {
     let otp = generate_otp(OTP_LENGTH);
     let otp = Otp::new(user_id, otp.as_str());
-    db.insert_one::<Otp>(DB_NAME, COLL_OTP, &otp, None).await?;
-    send_otp(&user.phone, &otp.otp);
-    Ok(())
+    let otp_id = db.insert_one::<Otp>(DB_NAME, COLL_OTP, &otp, None).await?;
+    let result = send_otp(sms_sender, phone, &otp.otp).await;
+    let ts = get_epoch_ts() as i64;
+    let mut update = doc! {"smsProvider": sms_sender.name(), "updateTs": ts};
+    match &result {
+        Ok(provider_ref) => {
+            update.insert("deliveryStatus", to_bson(&OtpDeliveryStatus::SENT)?);
+            if let Some(provider_ref) = provider_ref {
+                update.insert("providerRef", provider_ref);
+            }
+        }
+        Err(err) => {
+            tracing::debug!("not able to send otp to {phone}: {:?}", err);
+            update.insert("deliveryStatus", to_bson(&OtpDeliveryStatus::FAILED)?);
+            update.insert("deliveryError", err.to_string());
+        }
+    }
+    let filter = doc! {"_id": ObjectId::parse_str(&otp_id)?};
+    db.update_one(DB_NAME, COLL_OTP, filter, doc! {"$set": update}, None)
+        .await?;
+    result
+        .map(|_| ())
+        .map_err(|err| anyhow::anyhow!("Not able to send otp: {err}"))
+}
+
+/// send otp to a given phone through the configured sms gateway
+pub async fn send_otp(
+    sms_sender: &Arc<dyn SmsSender>,
+    phone: &str,
+    otp: &str,
+) -> anyhow::Result<Option<String>> {
+    let message = format!(
+        "{otp} is your Trailsbuddy verification code. It is valid for {OTP_VALIDITY_MINS} minutes."
+    );
+    sms_sender.send(phone, &message).await
 }
 
-/// send otp to a given phone. SMS gateway API or SMS queue API to be called from here
-pub fn send_otp(phone: &str, otp: &str) {
-    tracing::debug!("Send otp {otp} to phone {phone}");
+#[cfg(test)]
+mod tests {
+    use axum::async_trait;
+
+    use super::*;
+    use crate::{sms::LogSmsSender, utils::test_helper::*};
+
+    struct FailingSmsSender;
+
+    #[async_trait]
+    impl SmsSender for FailingSmsSender {
+        fn name(&self) -> &'static str {
+            "FAILING"
+        }
+
+        async fn send(&self, _phone: &str, _message: &str) -> anyhow::Result<Option<String>> {
+            Err(anyhow::anyhow!("gateway is down"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_otp_delivery_status() {
+        let db = test_db();
+        insert_admin(&db, 1).await;
+        let sms_sender: Arc<dyn SmsSender> = Arc::new(LogSmsSender::default());
+        generate_send_otp_admin(1, &db, &sms_sender).await.unwrap();
+        let sms_sender: Arc<dyn SmsSender> = Arc::new(FailingSmsSender);
+        let err = generate_send_otp_admin(1, &db, &sms_sender)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("gateway is down"));
+
+        let otps = db.find::<Otp>(DB_NAME, COLL_OTP, None, None).await.unwrap();
+        assert_eq!(otps[0].delivery_status, Some(OtpDeliveryStatus::SENT));
+        assert_eq!(otps[0].sms_provider.as_deref(), Some("LOG"));
+        assert_eq!(otps[1].delivery_status, Some(OtpDeliveryStatus::FAILED));
+        assert_eq!(otps[1].delivery_error.as_deref(), Some("gateway is down"));
+    }
 }
@@ -0,0 +1,178 @@
+use mongodb::{
+    bson::doc,
+    options::{FindOneAndUpdateOptions, ReturnDocument, UpdateOptions},
+};
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    models::OtpLimit,
+    utils::{get_epoch_ts, AppError},
+};
+
+fn phone_key(phone: &str) -> String {
+    format!("phone:{phone}")
+}
+
+/// Check the lockout and the send rate limits of the phone and the ip address
+/// and count the otp which is going to be sent
+pub async fn check_otp_send_limit(
+    db: &Arc<AppDatabase>,
+    phone: &str,
+    ip: Option<&str>,
+) -> Result<(), AppError> {
+    check_lockout(db, phone).await?;
+    if let Some(ip) = ip {
+        consume_send(db, &format!("ip:{ip}"), OTP_SEND_LIMIT_PER_IP).await?;
+    }
+    consume_send(db, &phone_key(phone), OTP_SEND_LIMIT_PER_PHONE).await
+}
+
+/// Reject the request while the phone is locked out after too many failed verifications
+pub async fn check_lockout(db: &Arc<AppDatabase>, phone: &str) -> Result<(), AppError> {
+    let ts = get_epoch_ts();
+    let filter = doc! {"key": phone_key(phone), "lockedTill": {"$gt": ts as i64}};
+    let limit = db
+        .find_one::<OtpLimit>(DB_NAME, COLL_OTP_LIMITS, Some(filter), None)
+        .await?;
+    if let Some(locked_till) = limit.and_then(|limit| limit.locked_till) {
+        let secs = locked_till.saturating_sub(ts);
+        let err = format!("Too many failed attempts, try again after {secs} seconds");
+        return Err(AppError::TooManyRequests(err));
+    }
+    Ok(())
+}
+
+/// Count a send within the current window, a new window starts once the current one is over
+async fn consume_send(db: &Arc<AppDatabase>, key: &str, limit: u32) -> Result<(), AppError> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {"key": key};
+    let update = doc! {"$setOnInsert": {"windowStart": ts, "sendCount": 0}};
+    let options = UpdateOptions::builder().upsert(true).build();
+    db.update_one(DB_NAME, COLL_OTP_LIMITS, filter, update, Some(options))
+        .await?;
+    let filter = doc! {"key": key, "windowStart": {"$lte": ts - OTP_SEND_WINDOW_SECS as i64}};
+    let update = doc! {"$set": {"windowStart": ts, "sendCount": 0}};
+    db.update_one(DB_NAME, COLL_OTP_LIMITS, filter, update, None)
+        .await?;
+    let filter = doc! {"key": key, "sendCount": {"$lt": limit}};
+    let update = doc! {"$inc": {"sendCount": 1}, "$set": {"updateTs": ts}};
+    let result = db
+        .update_one(DB_NAME, COLL_OTP_LIMITS, filter, update, None)
+        .await?;
+    if result.modified_count == 0 {
+        let err = "Too many otp requests, please try again later".to_string();
+        return Err(AppError::TooManyRequests(err));
+    }
+    Ok(())
+}
+
+/// Count a wrong otp against the valid otps of the user and the phone.
+/// The otps are invalidated after `OTP_MAX_VERIFY_ATTEMPTS` and the phone is locked out
+/// after `OTP_LOCKOUT_THRESHOLD` failures.
+pub async fn record_failed_attempt(
+    db: &Arc<AppDatabase>,
+    user_id: u32,
+    phone: &str,
+) -> Result<(), AppError> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {"userId": user_id, "isUsed": false, "validTill": {"$gte": ts}};
+    let update = doc! {"$inc": {"attempts": 1}};
+    db.update_many(DB_NAME, COLL_OTP, filter, update, None)
+        .await?;
+    let filter = doc! {
+        "userId": user_id,
+        "isUsed": false,
+        "attempts": {"$gte": OTP_MAX_VERIFY_ATTEMPTS}
+    };
+    let update = doc! {"$set": {"isUsed": true, "updateTs": ts}};
+    db.update_many(DB_NAME, COLL_OTP, filter, update, None)
+        .await?;
+
+    let key = phone_key(phone);
+    let filter = doc! {"key": &key};
+    let update = doc! {"$inc": {"failedCount": 1}, "$set": {"updateTs": ts}};
+    let options = FindOneAndUpdateOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+    let limit = db
+        .find_one_and_update::<OtpLimit>(DB_NAME, COLL_OTP_LIMITS, filter, update, Some(options))
+        .await?
+        .unwrap_or_default();
+    if limit.failed_count >= OTP_LOCKOUT_THRESHOLD {
+        let locked_till = ts + OtpLimit::lockout_secs(limit.lockout_count) as i64;
+        let filter = doc! {"key": &key, "failedCount": {"$gte": OTP_LOCKOUT_THRESHOLD}};
+        let update = doc! {
+            "$set": {"failedCount": 0, "lockedTill": locked_till},
+            "$inc": {"lockoutCount": 1}
+        };
+        db.update_one(DB_NAME, COLL_OTP_LIMITS, filter, update, None)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Clear the failures and the lockout backoff of the phone after a successful verification
+pub async fn reset_failed_attempts(db: &Arc<AppDatabase>, phone: &str) -> Result<(), AppError> {
+    let filter = doc! {"key": phone_key(phone)};
+    let update = doc! {
+        "$set": {"failedCount": 0, "lockoutCount": 0},
+        "$unset": {"lockedTill": ""}
+    };
+    db.update_one(DB_NAME, COLL_OTP_LIMITS, filter, update, None)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use super::*;
+    use crate::{
+        models::{Otp, User},
+        utils::test_helper::*,
+    };
+
+    #[tokio::test]
+    async fn test_otp_limits() {
+        let db = test_db();
+        let user = User {
+            id: 1,
+            phone: Some("9000000001".into()),
+            is_active: true,
+            ..Default::default()
+        };
+        db.insert_one::<User>(DB_NAME, COLL_USERS, &user, None)
+            .await
+            .unwrap();
+
+        let uri = "/api/v1/user/verify?phone=9000000001";
+        for _ in 0..OTP_SEND_LIMIT_PER_PHONE {
+            let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // wrong otps invalidate the sent otps and lock the phone out
+        let uri = "/api/v1/user/checkOtp?phone=9000000001&otp=000000";
+        for _ in 0..OTP_LOCKOUT_THRESHOLD {
+            let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+        let otps = db.find::<Otp>(DB_NAME, COLL_OTP, None, None).await.unwrap();
+        assert!(otps.iter().all(|otp| otp.is_used));
+        let (status, res) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(res["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Too many failed attempts"));
+
+        assert_eq!(OtpLimit::lockout_secs(1), 2 * OTP_LOCKOUT_BASE_SECS);
+        assert_eq!(OtpLimit::lockout_secs(20), OTP_LOCKOUT_MAX_SECS);
+    }
+}
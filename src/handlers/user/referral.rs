@@ -1,12 +1,13 @@
 use axum::{extract::State, Json};
 use futures::FutureExt;
-use mongodb::{bson::doc, ClientSession};
+use mongodb::bson::doc;
 use std::sync::Arc;
 
 use super::otp::get_user_by_id;
 use crate::{
+    config::{AppConfig, ReferralConfig},
     constants::*,
-    database::AppDatabase,
+    database::{AppDatabase, DbSession},
     handlers::*,
     jwt::{JwtClaims, JwtClaimsAdmin},
     models::*,
@@ -32,6 +33,7 @@ use crate::{
 )]
 pub async fn use_referral_code_handler(
     claims: JwtClaims,
+    State(config): State<Arc<AppConfig>>,
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<ReferralCodeReqBody>,
 ) -> Result<Json<UpdateUserResponse>, AppError> {
@@ -68,7 +70,8 @@ pub async fn use_referral_code_handler(
             .find_one::<User>(DB_NAME, COLL_USERS, filter, None)
             .await?
             .ok_or(AppError::NotFound("Invalid referralCode".into()))?;
-        add_referral_bonus(&db, claims.id, referrer.id, &body.referral_code).await?;
+        let bonus = &config.referral;
+        add_referral_bonus(&db, bonus, claims.id, referrer.id, &body.referral_code).await?;
     }
     user.has_used_referral_code = Some(true);
     user.used_referral_code = Some(body.referral_code);
@@ -91,7 +94,8 @@ pub async fn use_referral_code_handler(
     responses(
         (status = StatusCode::OK, description = "Referral code saved", body = GenericResponse),
         (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
-        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse)
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
     ),
     tag = "Admin API"
 )]
@@ -100,6 +104,7 @@ pub async fn create_special_code_handler(
     State(db): State<Arc<AppDatabase>>,
     ValidatedBody(body): ValidatedBody<SpecialCodeReqBody>,
 ) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::REFERRAL_CODES)?;
     let claims = claims.data;
     let curr_ts = get_epoch_ts() as i64;
     if body.valid_till.timestamp() <= curr_ts {
@@ -162,6 +167,7 @@ async fn add_special_referral_bonus(
 
 async fn add_referral_bonus(
     db: &Arc<AppDatabase>,
+    bonus: &ReferralConfig,
     user_id: u32,
     referred_id: u32,
     referral_code: &str,
@@ -173,10 +179,11 @@ async fn add_referral_bonus(
     );
     db.execute_transaction(None, None, |db, session| {
         let referral_code = referral_code.to_owned();
+        let (referral_bonus, referrer_bonus) = (bonus.referral_bonus, bonus.referrer_bonus);
         async move {
             update_users(db, session, user_id, &referral_code, referred_id).await?;
-            credit_referral_bonus(db, session, user_id, REFERRAL_BONUS).await?;
-            credit_referrer_bonus(db, session, referred_id, user_id).await?;
+            credit_referral_bonus(db, session, user_id, referral_bonus).await?;
+            credit_referrer_bonus(db, session, referred_id, user_id, referrer_bonus).await?;
             Ok(())
         }
         .boxed()
@@ -187,7 +194,7 @@ async fn add_referral_bonus(
 
 async fn update_users(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     referral_code: &str,
     referred_id: u32,
@@ -202,12 +209,19 @@ async fn update_users(
 
 pub async fn credit_referral_bonus(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     bonus: u64,
 ) -> anyhow::Result<()> {
-    let (balance_before, balance_after) =
-        update_wallet_with_session(db, session, user_id, 0, bonus, false, false).await?;
+    let (balance_before, balance_after) = update_wallet_with_session(
+        db,
+        session,
+        user_id,
+        0,
+        bonus,
+        &WalltetTransactionType::ReferralBonus,
+    )
+    .await?;
     let transaction =
         WalletTransaction::referral_bonus_trans(user_id, bonus, balance_before, balance_after);
     insert_wallet_transaction_session(db, session, &transaction).await?;
@@ -216,15 +230,23 @@ pub async fn credit_referral_bonus(
 
 pub async fn credit_referrer_bonus(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     referrer_id: u32,
     user_id: u32,
+    bonus: u64,
 ) -> anyhow::Result<()> {
-    let (balance_before, balance_after) =
-        update_wallet_with_session(db, session, referrer_id, 0, REFERRER_BONUS, false, false)
-            .await?;
+    let (balance_before, balance_after) = update_wallet_with_session(
+        db,
+        session,
+        referrer_id,
+        0,
+        bonus,
+        &WalltetTransactionType::ReferrerBonus,
+    )
+    .await?;
     let transaction = WalletTransaction::referrer_bonus_trans(
         referrer_id,
+        bonus,
         balance_before,
         balance_after,
         user_id,
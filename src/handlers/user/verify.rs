@@ -6,8 +6,14 @@ use mongodb::bson::doc;
 use std::sync::Arc;
 use validator::Validate;
 
-use super::otp::generate_send_otp;
-use crate::{constants::*, database::AppDatabase, models::*, utils::AppError};
+use super::{otp::generate_send_otp, otp_limit::check_otp_send_limit};
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    models::*,
+    sms::SmsSender,
+    utils::{AppError, ClientIp},
+};
 
 /// Verify user with phone
 ///
@@ -22,12 +28,15 @@ use crate::{constants::*, database::AppDatabase, models::*, utils::AppError};
     responses(
         (status = StatusCode::OK, description = "Valid user & OTP is generated", body = GenericResponse),
         (status = StatusCode::BAD_REQUEST, description = "Bad request", body = GenericResponse),
+        (status = StatusCode::TOO_MANY_REQUESTS, description = "Too many requests", body = GenericResponse),
         (status = StatusCode::NOT_FOUND, description = "User not found", body = GenericResponse)
     ),
     tag = "App User API"
 )]
 pub async fn verify_user_handler(
     State(db): State<Arc<AppDatabase>>,
+    State(sms_sender): State<Arc<dyn SmsSender>>,
+    ClientIp(ip): ClientIp,
     params: Query<VerifyUserReq>,
 ) -> Result<Json<GenericResponse>, AppError> {
     params
@@ -38,7 +47,8 @@ pub async fn verify_user_handler(
         .find_one::<User>(DB_NAME, COLL_USERS, filter, None)
         .await?
         .ok_or(AppError::NotFound("User not found".into()))?;
-    generate_send_otp(user.id, &db).await?;
+    check_otp_send_limit(&db, &params.phone, ip.as_deref()).await?;
+    generate_send_otp(user.id, &db, &sms_sender).await?;
     let res = GenericResponse {
         success: true,
         message: "Otp generated".to_owned(),
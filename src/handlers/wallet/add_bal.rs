@@ -1,14 +1,17 @@
 use axum::{extract::State, Json};
 use futures::FutureExt;
-use mongodb::bson::doc;
+use mongodb::bson::{doc, oid::ObjectId};
 use std::sync::Arc;
 
 use crate::{
+    config::AppConfig,
+    constants::*,
     database::AppDatabase,
     handlers::wallet::helper::{get_user_balance, get_wallet_transaction},
     jwt::JwtClaims,
     models::*,
-    utils::{parse_object_id, AppError, ValidatedBody},
+    payment::{PaymentGateway, PaymentOrder},
+    utils::{get_epoch_ts, parse_object_id, AppError, ValidatedBody},
 };
 
 use super::helper::{
@@ -32,22 +35,72 @@ use super::helper::{
 )]
 pub async fn add_bal_init_handler(
     claims: JwtClaims,
+    State(config): State<Arc<AppConfig>>,
     State(db): State<Arc<AppDatabase>>,
+    State(payment_gateway): State<Arc<dyn PaymentGateway>>,
     ValidatedBody(body): ValidatedBody<AddBalInitReq>,
 ) -> Result<Json<AddBalInitRes>, AppError> {
-    let app_upi_id = std::env::var("APP_UPI_ID")?;
+    let app_upi_id = config
+        .app_upi_id
+        .clone()
+        .ok_or(anyhow::anyhow!("APP_UPI_ID is not configured"))?;
     let amount = Money::new(body.amount, 0);
     let balance_before = get_user_balance(&db, claims.id).await?.unwrap_or_default();
     let transaction = WalletTransaction::add_bal_init_trans(claims.id, amount, balance_before);
     let transaction_id = insert_wallet_transaction(&db, &transaction).await?;
+    let order = create_payment_order(
+        &db,
+        &payment_gateway,
+        claims.id,
+        &transaction_id,
+        body.amount,
+    )
+    .await?;
     let res = AddBalInitRes {
         success: true,
         transaction_id,
         app_upi_id,
+        payment_gateway: payment_gateway.name().to_string(),
+        order_id: order.order_id,
     };
     Ok(Json(res))
 }
 
+/// Create the gateway order for the transaction and store the order id with it.
+/// The transaction is marked as failed when the order can not be created.
+async fn create_payment_order(
+    db: &Arc<AppDatabase>,
+    payment_gateway: &Arc<dyn PaymentGateway>,
+    user_id: u32,
+    transaction_id: &str,
+    amount: u64,
+) -> Result<PaymentOrder, AppError> {
+    let transaction_id = parse_object_id(transaction_id, TRANSACTION_ID_PARSE_ERR)?;
+    let order = match payment_gateway
+        .create_order(&transaction_id.to_hex(), amount)
+        .await
+    {
+        Ok(order) => order,
+        Err(err) => {
+            tracing::debug!("not able to create payment order: {:?}", err);
+            let msg = Some(format!("not able to create payment order: {}", err));
+            updated_failed_transaction(db, user_id, &transaction_id, &msg, &None).await?;
+            return Err(err.into());
+        }
+    };
+    let filter = doc! {"_id": transaction_id};
+    let update = doc! {
+        "$set": {
+            "paymentGateway": payment_gateway.name(),
+            "gatewayOrderId": &order.order_id,
+            "updatedTs": get_epoch_ts() as i64
+        }
+    };
+    db.update_one(DB_NAME, COLL_WALLET_TRANSACTIONS, filter, update, None)
+        .await?;
+    Ok(order)
+}
+
 pub const TRANSACTION_ID_PARSE_ERR: &str = "Not able to parse transactionId value";
 
 /// Add balance finalize
@@ -67,29 +120,85 @@ pub const TRANSACTION_ID_PARSE_ERR: &str = "Not able to parse transactionId valu
 pub async fn add_bal_end_handler(
     claims: JwtClaims,
     State(db): State<Arc<AppDatabase>>,
+    State(payment_gateway): State<Arc<dyn PaymentGateway>>,
     ValidatedBody(body): ValidatedBody<AddBalEndReq>,
 ) -> Result<Json<GenericResponse>, AppError> {
-    validate_transaction(&claims, &db, &body).await?;
+    let transaction = validate_transaction(&claims, &db, &body).await?;
     if body.is_successful {
+        verify_payment(&payment_gateway, &transaction, &body)?;
         handle_success_transaction(&claims, &db, &body).await
     } else {
         handle_failed_transaction(&claims, &db, &body).await
     }
 }
 
+/// The wallet is credited only when the gateway signature of the payment is valid
+fn verify_payment(
+    payment_gateway: &Arc<dyn PaymentGateway>,
+    transaction: &WalletTransaction,
+    body: &AddBalEndReq,
+) -> Result<(), AppError> {
+    let order_id = transaction
+        .gateway_order_id()
+        .ok_or(AppError::BadRequestErr("payment order not found".into()))?;
+    let (Some(payment_id), Some(signature)) = (&body.payment_id, &body.signature) else {
+        let err = AppError::BadRequestErr("paymentId and signature are required".into());
+        return Err(err);
+    };
+    payment_gateway
+        .verify_payment(order_id, payment_id, signature)
+        .map_err(|err| {
+            tracing::debug!("payment verification failed: {:?}", err);
+            AppError::BadRequestErr("payment verification failed".into())
+        })
+}
+
 async fn handle_success_transaction(
     claims: &JwtClaims,
     db: &Arc<AppDatabase>,
     body: &AddBalEndReq,
 ) -> Result<Json<GenericResponse>, AppError> {
     let transaction_id = parse_object_id(&body.transaction_id, TRANSACTION_ID_PARSE_ERR)?;
-    let user_id = claims.id;
+    let payment_id = body.payment_id.clone().unwrap_or_default();
+    credit_add_balance(
+        db,
+        &transaction_id,
+        claims.id,
+        body.amount,
+        &payment_id,
+        &body.tracking_id,
+    )
+    .await?;
+    let res = GenericResponse {
+        success: true,
+        message: "Updated successfully".to_owned(),
+    };
+    Ok(Json(res))
+}
+
+/// Credit the verified payment to the user wallet and complete the add balance transaction
+pub async fn credit_add_balance(
+    db: &AppDatabase,
+    transaction_id: &ObjectId,
+    user_id: u32,
+    amount: u64,
+    payment_id: &str,
+    tracking_id: &Option<String>,
+) -> anyhow::Result<()> {
     db.execute_transaction(None, None, |db, session| {
-        let tracking_id = body.tracking_id.clone();
-        let amount = body.amount;
+        let transaction_id = *transaction_id;
+        let payment_id = payment_id.to_string();
+        let tracking_id = tracking_id.clone();
         async move {
-            let (_, balance_after) =
-                update_wallet_with_session(db, session, user_id, amount, 0, false, false).await?;
+            let (_, balance_after) = update_wallet_with_session(
+                db,
+                session,
+                user_id,
+                amount,
+                0,
+                &WalltetTransactionType::AddBalance,
+            )
+            .await?;
             update_wallet_transaction_session(
                 db,
                 session,
@@ -98,16 +207,22 @@ async fn handle_success_transaction(
                 &tracking_id,
             )
             .await?;
+            let filter = doc! {"_id": transaction_id};
+            let update = doc! {"$set": {"gatewayPaymentId": payment_id}};
+            db.update_one_with_session(
+                session,
+                DB_NAME,
+                COLL_WALLET_TRANSACTIONS,
+                filter,
+                update,
+                None,
+            )
+            .await?;
             Ok(())
         }
         .boxed()
     })
-    .await?;
-    let res = GenericResponse {
-        success: true,
-        message: "Updated successfully".to_owned(),
-    };
-    Ok(Json(res))
+    .await
 }
 
 async fn handle_failed_transaction(
@@ -135,7 +250,7 @@ async fn validate_transaction(
     claims: &JwtClaims,
     db: &Arc<AppDatabase>,
     body: &AddBalEndReq,
-) -> Result<(), AppError> {
+) -> Result<WalletTransaction, AppError> {
     let transaction_id = parse_object_id(&body.transaction_id, TRANSACTION_ID_PARSE_ERR)?;
     let filter = doc! {
         "_id": transaction_id,
@@ -143,30 +258,60 @@ async fn validate_transaction(
         "status": WalletTransactionStatus::Pending.to_bson()?,
         "transactionType": WalltetTransactionType::AddBalance.to_bson()?
     };
-    let filter = Some(filter);
-    let (transaction_result, balance_result) = tokio::join!(
-        get_wallet_transaction(db, filter),
-        get_user_balance(db, claims.id)
-    );
-    let transaction =
-        transaction_result?.ok_or(AppError::NotFound("transaction not found".into()))?;
-    let user_balance = balance_result?.unwrap_or_default();
+    let transaction = get_wallet_transaction(db, Some(filter))
+        .await?
+        .ok_or(AppError::NotFound("transaction not found".into()))?;
     let amount = Money::new(body.amount, 0);
     if transaction.amount() != amount {
         let err = AppError::BadRequestErr("amount do not match".into());
         return Err(err);
     }
-    if user_balance != transaction.balance_before() {
-        let msg = format!(
-            "user balance {} does not match with transaction balanceBefore {}",
-            user_balance,
-            transaction.balance_before()
-        );
-        let msg = Some(msg);
-        updated_failed_transaction(db, claims.id, &transaction_id, &msg, &body.tracking_id).await?;
-        let err = AppError::BadRequestErr(msg.unwrap());
-        return Err(err);
-    }
+    Ok(transaction)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{payment::MockPaymentGateway, utils::test_helper::*};
+
+    #[tokio::test]
+    async fn test_add_balance_requires_valid_signature() {
+        let db = test_db();
+        let body = json!({"amount": 100});
+        let uri = "/api/v1/wallet/addBalanceInit";
+        let (status, res) = send_request(&db, Method::POST, uri, 1, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["paymentGateway"], "MOCK");
+        let transaction_id = res["transactionId"].as_str().unwrap().to_string();
+        let order_id = res["orderId"].as_str().unwrap().to_string();
+        // the wallet can change while the payment is in progress
+        add_balance(&db, 1, 20, 0).await;
+
+        let uri = "/api/v1/wallet/addBalanceEnd";
+        let mut body = json!({
+            "amount": 100,
+            "transactionId": transaction_id,
+            "isSuccessful": true,
+            "paymentId": "pay_1",
+            "signature": MockPaymentGateway::new().payment_signature(&order_id, "pay_2"),
+        });
+        let (status, _) = send_request(&db, Method::POST, uri, 1, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(get_balance(&db, 1).await, Money::new(20, 0));
+
+        body["signature"] = MockPaymentGateway::new()
+            .payment_signature(&order_id, "pay_1")
+            .into();
+        let (status, _) = send_request(&db, Method::POST, uri, 1, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(get_balance(&db, 1).await, Money::new(120, 0));
+
+        // the same payment can not be credited twice
+        let (status, _) = send_request(&db, Method::POST, uri, 1, Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(get_balance(&db, 1).await, Money::new(120, 0));
+    }
 }
@@ -3,16 +3,19 @@ use std::sync::Arc;
 use mongodb::{
     bson::{doc, oid::ObjectId, Document},
     options::{FindOneAndUpdateOptions, ReturnDocument, UpdateModifications},
-    ClientSession,
 };
 
 use crate::{
     constants::*,
-    database::AppDatabase,
-    models::wallet::{Money, Wallet, WalletTransaction, WalletTransactionStatus},
+    database::{AppDatabase, DbSession},
+    models::wallet::{
+        Money, Wallet, WalletTransaction, WalletTransactionStatus, WalltetTransactionType,
+    },
     utils::get_epoch_ts,
 };
 
+use super::ledger::{open_wallet_ledger, post_ledger_entries};
+
 pub async fn get_user_wallet(
     db: &Arc<AppDatabase>,
     user_id: u32,
@@ -35,7 +38,7 @@ pub async fn get_user_balance(
 
 pub async fn get_user_balance_session(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
 ) -> anyhow::Result<Money> {
     let filter = doc! {"userId": user_id};
@@ -58,17 +61,25 @@ pub async fn get_wallet_transaction(
     Ok(transaction)
 }
 
+/// Update the user wallet for the transaction type and post the balanced ledger entries.
+/// The ledger of a wallet created before the ledger is opened with the balance before the update.
+/// Returns the balance before and after the update.
 pub async fn update_wallet_with_session(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     real: u64,
     bonus: u64,
-    subtract: bool,
-    update_withdrawable: bool,
+    transaction_type: &WalltetTransactionType,
 ) -> anyhow::Result<(Money, Money)> {
+    open_wallet_ledger(db, session, user_id).await?;
     let balance_before = get_user_balance_session(db, session, user_id).await?;
-    let withdrawable = if update_withdrawable { real } else { 0 };
+    let subtract = transaction_type.is_debit();
+    let withdrawable = if transaction_type.updates_withdrawable() {
+        real
+    } else {
+        0
+    };
     let wallet = if subtract {
         sub_wallet(db, session, user_id, real, bonus, withdrawable).await?
     } else {
@@ -89,12 +100,13 @@ pub async fn update_wallet_with_session(
         );
         return Err(err);
     }
+    post_ledger_entries(db, session, user_id, real, bonus, transaction_type).await?;
     Ok((balance_before, wallet.balance()))
 }
 
 async fn add_wallet(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     real: u64,
     bonus: u64,
@@ -108,7 +120,7 @@ async fn add_wallet(
             "balance.real": real as i64,
             "balance.withdrawable": withdrawable as i64
         },
-        "$setOnInsert": {"createdTs": ts},
+        "$setOnInsert": {"createdTs": ts, "ledgerOpened": true},
         "$set": {"updatedTs": ts},
     };
     find_and_modify_wallet(db, session, filter, update).await
@@ -116,7 +128,7 @@ async fn add_wallet(
 
 async fn sub_wallet(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     real: u64,
     bonus: u64,
@@ -175,7 +187,7 @@ async fn sub_wallet(
 
 async fn find_and_modify_wallet(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     filter: Document,
     update: impl Into<UpdateModifications>,
 ) -> anyhow::Result<Wallet> {
@@ -199,13 +211,20 @@ async fn find_and_modify_wallet(
 
 pub async fn update_wallet_transaction_session(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     transaction_id: &ObjectId,
     balance_after: Money,
     tracking_id: &Option<String>,
 ) -> anyhow::Result<()> {
     let ts = get_epoch_ts() as i64;
-    let filter = doc! {"_id": transaction_id};
+    // a failed transaction can still be completed by a late captured payment,
+    // a completed transaction is never credited twice
+    let pending = WalletTransactionStatus::Pending.to_bson()?;
+    let error = WalletTransactionStatus::Error.to_bson()?;
+    let filter = doc! {
+        "_id": transaction_id,
+        "status": {"$in": [pending, error]}
+    };
     let update = doc! {
         "$set": {
             "balanceAfter": balance_after.to_bson()?,
@@ -214,15 +233,19 @@ pub async fn update_wallet_transaction_session(
             "updatedTs": ts
         }
     };
-    db.update_one_with_session(
-        session,
-        DB_NAME,
-        COLL_WALLET_TRANSACTIONS,
-        filter,
-        update,
-        None,
-    )
-    .await?;
+    let result = db
+        .update_one_with_session(
+            session,
+            DB_NAME,
+            COLL_WALLET_TRANSACTIONS,
+            filter,
+            update,
+            None,
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(anyhow::anyhow!("transaction is already completed"));
+    }
     Ok(())
 }
 
@@ -238,7 +261,7 @@ pub async fn insert_wallet_transaction(
 
 pub async fn insert_wallet_transaction_session(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     transaction: &WalletTransaction,
 ) -> anyhow::Result<String> {
     let transaction_id = db
@@ -260,7 +283,10 @@ pub async fn updated_failed_transaction(
     error_reason: &Option<String>,
     tracking_id: &Option<String>,
 ) -> anyhow::Result<()> {
-    let filter = doc! {"_id": transaction_id};
+    let filter = doc! {
+        "_id": transaction_id,
+        "status": WalletTransactionStatus::Pending.to_bson()?
+    };
     let update = doc! {
         "$set": {
             "status": WalletTransactionStatus::Error.to_bson()?,
@@ -274,3 +300,50 @@ pub async fn updated_failed_transaction(
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+    use crate::utils::test_helper::{add_balance, get_balance, test_db};
+
+    const PAY: WalltetTransactionType = WalltetTransactionType::PayForContest;
+
+    #[tokio::test]
+    async fn test_update_wallet_with_session() {
+        let db = test_db();
+        add_balance(&db, 1, 100, 20).await;
+        add_balance(&db, 1, 50, 0).await;
+        assert_eq!(get_balance(&db, 1).await, Money::new(150, 20));
+
+        let result = db
+            .execute_transaction(None, None, |db, session| {
+                async move {
+                    let (before, after) =
+                        update_wallet_with_session(db, session, 1, 30, 20, &PAY).await?;
+                    assert_eq!(before, Money::new(150, 20));
+                    assert_eq!(after, Money::new(120, 0));
+                    Ok(())
+                }
+                .boxed()
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(get_balance(&db, 1).await, Money::new(120, 0));
+
+        // insufficient balance must fail and leave the wallet untouched
+        let result = db
+            .execute_transaction(None, None, |db, session| {
+                async move {
+                    update_wallet_with_session(db, session, 1, 10, 0, &PAY).await?;
+                    update_wallet_with_session(db, session, 1, 500, 0, &PAY).await?;
+                    Ok(())
+                }
+                .boxed()
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(get_balance(&db, 1).await, Money::new(120, 0));
+    }
+}
@@ -0,0 +1,72 @@
+use mongodb::{
+    bson::doc,
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+};
+
+use crate::{
+    constants::*,
+    database::{AppDatabase, DbSession},
+    models::*,
+};
+
+/// Post the balanced ledger entries of a wallet update.
+/// Debits are posted with negative amounts for the user account.
+pub async fn post_ledger_entries(
+    db: &AppDatabase,
+    session: &mut DbSession,
+    user_id: u32,
+    real: u64,
+    bonus: u64,
+    transaction_type: &WalltetTransactionType,
+) -> anyhow::Result<()> {
+    let sign = if transaction_type.is_debit() { -1 } else { 1 };
+    let entries = LedgerEntry::posting(
+        user_id,
+        sign * real as i64,
+        sign * bonus as i64,
+        transaction_type.counter_account(),
+        Some(transaction_type.clone()),
+    );
+    db.insert_many_with_session(session, DB_NAME, COLL_LEDGER_ENTRIES, &entries, None)
+        .await?;
+    Ok(())
+}
+
+/// Open the ledger of a wallet created before the ledger, the current balance of the wallet
+/// is posted against the house account. Does nothing when the ledger is already opened or
+/// the user has no wallet.
+pub async fn open_wallet_ledger(
+    db: &AppDatabase,
+    session: &mut DbSession,
+    user_id: u32,
+) -> anyhow::Result<()> {
+    let filter = doc! {"userId": user_id, "ledgerOpened": {"$ne": true}};
+    let update = doc! {"$set": {"ledgerOpened": true}};
+    let options = FindOneAndUpdateOptions::builder()
+        .return_document(Some(ReturnDocument::After))
+        .build();
+    let wallet = db
+        .find_one_and_update_with_session::<Wallet>(
+            session,
+            DB_NAME,
+            COLL_WALLETS,
+            filter,
+            update,
+            Some(options),
+        )
+        .await?;
+    let Some(wallet) = wallet else {
+        return Ok(());
+    };
+    let balance = wallet.balance();
+    let entries = LedgerEntry::posting(
+        user_id,
+        balance.real() as i64,
+        balance.bonus() as i64,
+        LedgerAccount::House,
+        None,
+    );
+    db.insert_many_with_session(session, DB_NAME, COLL_LEDGER_ENTRIES, &entries, None)
+        .await?;
+    Ok(())
+}
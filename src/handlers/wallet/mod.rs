@@ -1,5 +1,9 @@
 pub mod add_bal;
 pub mod get_bal;
 pub mod helper;
+pub mod ledger;
 pub mod pay_contest;
+pub mod payment_webhook;
+pub mod transactions;
 pub mod withdraw_bal;
+pub mod withdrawals;
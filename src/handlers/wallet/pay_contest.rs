@@ -3,15 +3,16 @@ use futures::FutureExt;
 use mongodb::{
     bson::doc,
     options::{FindOneAndUpdateOptions, ReturnDocument},
-    ClientSession,
 };
 use std::sync::Arc;
 
 use crate::{
     constants::*,
-    database::AppDatabase,
+    database::{AppDatabase, DbSession},
     handlers::{
-        play_tracker::get::{get_contest_id, insert_new_play_tracker, validate_contest},
+        play_tracker::get::{
+            get_contest_id, insert_new_play_tracker, update_joined_count, validate_contest,
+        },
         wallet::helper::{get_user_balance, update_wallet_with_session},
     },
     jwt::JwtClaims,
@@ -76,46 +77,53 @@ pub async fn pay_contest_handler(
         let err = AppError::BadRequestErr(err);
         return Err(err);
     }
-    db.execute_transaction(None, None, |db, session| {
-        let user_id = claims.id;
-        let contest_id = body.contest_id.clone();
-        async move {
-            let (_, balance_after) = update_wallet_with_session(
-                db,
-                session,
-                user_id,
-                real_money_amount,
-                bonus_money_amount,
-                true,
-                false,
-            )
-            .await?;
-            let transaction_id = update_wallet_transaction(
-                db,
-                session,
-                user_id,
-                &contest_id,
-                real_money_amount,
-                bonus_money_amount,
-                user_balance,
-                balance_after,
-            )
-            .await?;
-            let paid_amount = Money::new(real_money_amount, bonus_money_amount);
-            update_play_tracker(
-                db,
-                session,
-                user_id,
-                &contest_id,
-                &transaction_id,
-                paid_amount,
-            )
-            .await?;
-            Ok(())
-        }
-        .boxed()
-    })
-    .await?;
+    // the player slot is reserved once the player pays
+    let contest_id = get_contest_id(&contest)?;
+    update_joined_count(&db, &contest, contest_id, 1).await?;
+    let result = db
+        .execute_transaction(None, None, |db, session| {
+            let user_id = claims.id;
+            let contest_id = body.contest_id.clone();
+            async move {
+                let (_, balance_after) = update_wallet_with_session(
+                    db,
+                    session,
+                    user_id,
+                    real_money_amount,
+                    bonus_money_amount,
+                    &WalltetTransactionType::PayForContest,
+                )
+                .await?;
+                let transaction_id = update_wallet_transaction(
+                    db,
+                    session,
+                    user_id,
+                    &contest_id,
+                    real_money_amount,
+                    bonus_money_amount,
+                    user_balance,
+                    balance_after,
+                )
+                .await?;
+                let paid_amount = Money::new(real_money_amount, bonus_money_amount);
+                update_play_tracker(
+                    db,
+                    session,
+                    user_id,
+                    &contest_id,
+                    &transaction_id,
+                    paid_amount,
+                )
+                .await?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await;
+    if let Err(err) = result {
+        update_joined_count(&db, &contest, contest_id, -1).await?;
+        return Err(err.into());
+    }
     play_tracker.status = PlayTrackerStatus::PAID;
     play_tracker.answers = None;
     let res = PlayTrackerResponse {
@@ -128,7 +136,7 @@ pub async fn pay_contest_handler(
 
 async fn update_wallet_transaction(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     contest_id: &str,
     real: u64,
@@ -150,7 +158,7 @@ async fn update_wallet_transaction(
 
 async fn update_play_tracker(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     contest_id: &str,
     transaction_id: &str,
@@ -160,6 +168,7 @@ async fn update_play_tracker(
     let filter = doc! {
         "contestId": contest_id,
         "userId": user_id,
+        "status": PlayTrackerStatus::INIT.to_bson()?
     };
     let update = doc! {
         "$set": {
@@ -202,7 +211,7 @@ async fn check_play_tracker(
         .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
         .await?;
     let Some(play_tracker) = play_tracker else {
-        let play_tracker = insert_new_play_tracker(user_id, contest, db).await?;
+        let play_tracker = insert_new_play_tracker(user_id, contest, None, db).await?;
         return Ok(play_tracker);
     };
     if play_tracker.status == PlayTrackerStatus::PAID {
@@ -222,3 +231,45 @@ async fn check_play_tracker(
     }
     Ok(play_tracker)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_pay_contest_handler() {
+        let db = test_db();
+        let props = contest_props(10, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        let body = json!({"contestId": contest_id});
+        let uri = "/api/v1/wallet/payContest";
+
+        let (status, _) = send_request(&db, Method::POST, uri, 1, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        add_balance(&db, 1, 25, 0).await;
+        let (status, res) = send_request(&db, Method::POST, uri, 1, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"]["status"], "PAID");
+        assert_eq!(get_balance(&db, 1).await, Money::new(15, 0));
+
+        let filter = doc! {"userId": 1, "contestId": &contest_id};
+        let play_tracker = db
+            .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(play_tracker.status, PlayTrackerStatus::PAID);
+        assert!(play_tracker.wallet_transaction_id.is_some());
+
+        let (status, _) = send_request(&db, Method::POST, uri, 1, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(get_balance(&db, 1).await, Money::new(15, 0));
+        // only the paid player holds a slot of the contest
+        assert_eq!(get_contest(&db, &contest_id).await.joined_count, 1);
+    }
+}
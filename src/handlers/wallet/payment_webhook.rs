@@ -0,0 +1,183 @@
+use axum::{body::Bytes, extract::State, http::HeaderMap, Json};
+use mongodb::bson::doc;
+use std::sync::Arc;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    models::*,
+    payment::{PaymentEvent, PaymentGateway, PaymentStatus},
+    utils::{parse_object_id, AppError},
+};
+
+use super::{
+    add_bal::{credit_add_balance, TRANSACTION_ID_PARSE_ERR},
+    helper::{get_wallet_transaction, updated_failed_transaction},
+};
+
+/// Payment gateway webhook
+///
+/// Complete the add balance transaction from the signed gateway notification
+#[utoipa::path(
+    post,
+    path = "/api/v1/wallet/paymentWebhook",
+    params(("x-razorpay-signature" = String, Header, description = "Webhook payload signature")),
+    request_body(content = String, description = "Webhook payload as sent by the payment gateway"),
+    responses(
+        (status = StatusCode::OK, description = "Webhook processed", body = GenericResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid signature", body = GenericResponse),
+    ),
+    tag = "App User API"
+)]
+pub async fn payment_webhook_handler(
+    State(db): State<Arc<AppDatabase>>,
+    State(payment_gateway): State<Arc<dyn PaymentGateway>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<GenericResponse>, AppError> {
+    let signature = headers
+        .get(PAYMENT_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(AppError::BadRequestErr("missing webhook signature".into()))?;
+    let event = payment_gateway
+        .parse_webhook(&body, signature)
+        .map_err(|err| {
+            tracing::debug!("invalid payment webhook: {:?}", err);
+            AppError::BadRequestErr("invalid payment webhook".into())
+        })?;
+    let message = match event {
+        Some(event) => handle_payment_event(&db, &event).await?,
+        None => "Event ignored",
+    };
+    let res = GenericResponse {
+        success: true,
+        message: message.to_owned(),
+    };
+    Ok(Json(res))
+}
+
+async fn handle_payment_event(
+    db: &Arc<AppDatabase>,
+    event: &PaymentEvent,
+) -> Result<&'static str, AppError> {
+    // the user can retry the payment of the same order after a failed attempt
+    if event.status == PaymentStatus::Failed {
+        tracing::debug!(
+            "payment {} failed for order {}: {:?}",
+            event.payment_id,
+            event.order_id,
+            event.error_reason
+        );
+        return Ok("Payment failure ignored");
+    }
+    // a captured payment also completes a transaction marked as failed earlier
+    let pending = WalletTransactionStatus::Pending.to_bson()?;
+    let error = WalletTransactionStatus::Error.to_bson()?;
+    let filter = doc! {
+        "gatewayOrderId": &event.order_id,
+        "status": {"$in": [pending, error]},
+        "transactionType": WalltetTransactionType::AddBalance.to_bson()?
+    };
+    // the transaction might already be processed by the client callback
+    let Some(transaction) = get_wallet_transaction(db, Some(filter)).await? else {
+        return Ok("No pending transaction found");
+    };
+    let transaction_id = transaction.id().unwrap_or_default();
+    let transaction_id = parse_object_id(transaction_id, TRANSACTION_ID_PARSE_ERR)?;
+    let user_id = transaction.user_id();
+    let tracking_id = Some(event.payment_id.clone());
+    if transaction.amount() != Money::new(event.amount, 0) {
+        let msg = format!(
+            "paid amount {} does not match with transaction amount {}",
+            event.amount,
+            transaction.amount()
+        );
+        updated_failed_transaction(db, user_id, &transaction_id, &Some(msg), &tracking_id).await?;
+        return Ok("Transaction marked as failed");
+    }
+    credit_add_balance(
+        db,
+        &transaction_id,
+        user_id,
+        event.amount,
+        &event.payment_id,
+        &tracking_id,
+    )
+    .await?;
+    Ok("Updated successfully")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+    use crate::{
+        handlers::wallet::helper::insert_wallet_transaction, payment::MockPaymentGateway,
+        utils::test_helper::*,
+    };
+
+    async fn send_webhook(
+        db: &Arc<AppDatabase>,
+        event: &PaymentEvent,
+        signature: Option<String>,
+    ) -> Result<Json<GenericResponse>, AppError> {
+        let gateway = MockPaymentGateway::new();
+        let payload = serde_json::to_vec(event).unwrap();
+        let signature = signature.unwrap_or(gateway.webhook_signature(&payload));
+        let mut headers = HeaderMap::new();
+        let value = HeaderValue::from_str(&signature).unwrap();
+        headers.insert(PAYMENT_SIGNATURE_HEADER, value);
+        let gateway: Arc<dyn PaymentGateway> = Arc::new(gateway);
+        payment_webhook_handler(State(db.clone()), State(gateway), headers, payload.into()).await
+    }
+
+    #[tokio::test]
+    async fn test_payment_webhook_handler() {
+        let db = test_db();
+        let transaction =
+            WalletTransaction::add_bal_init_trans(1, Money::new(50, 0), Money::default());
+        let transaction_id = insert_wallet_transaction(&db, &transaction).await.unwrap();
+        let filter = doc! {"_id": parse_object_id(&transaction_id, "").unwrap()};
+        let update = doc! {"$set": {"gatewayOrderId": "order_1"}};
+        db.update_one(DB_NAME, COLL_WALLET_TRANSACTIONS, filter, update, None)
+            .await
+            .unwrap();
+        let event = PaymentEvent {
+            order_id: "order_1".to_string(),
+            payment_id: "pay_1".to_string(),
+            amount: 50,
+            status: PaymentStatus::Captured,
+            error_reason: None,
+        };
+
+        let res = send_webhook(&db, &event, Some("00".to_string())).await;
+        assert!(matches!(res, Err(AppError::BadRequestErr(_))));
+        assert_eq!(get_balance(&db, 1).await, Money::new(0, 0));
+
+        // a failed attempt keeps the order payable
+        let failed = PaymentEvent {
+            payment_id: "pay_0".to_string(),
+            status: PaymentStatus::Failed,
+            error_reason: Some("card declined".to_string()),
+            ..event.clone()
+        };
+        let res = send_webhook(&db, &failed, None).await.unwrap();
+        assert_eq!(res.message, "Payment failure ignored");
+
+        // the client reported the failure, but the retried payment is captured
+        let filter = doc! {"_id": parse_object_id(&transaction_id, "").unwrap()};
+        let update = doc! {"$set": {"status": WalletTransactionStatus::Error.to_bson().unwrap()}};
+        db.update_one(DB_NAME, COLL_WALLET_TRANSACTIONS, filter, update, None)
+            .await
+            .unwrap();
+        let res = send_webhook(&db, &event, None).await.unwrap();
+        assert_eq!(res.message, "Updated successfully");
+        assert_eq!(get_balance(&db, 1).await, Money::new(50, 0));
+
+        // webhook retries must not credit the payment again
+        let res = send_webhook(&db, &event, None).await.unwrap();
+        assert_eq!(res.message, "No pending transaction found");
+        assert_eq!(get_balance(&db, 1).await, Money::new(50, 0));
+    }
+}
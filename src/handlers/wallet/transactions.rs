@@ -0,0 +1,194 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use mongodb::{bson::doc, options::FindOptions};
+use std::sync::Arc;
+use validator::Validate;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::{JwtClaims, JwtClaimsAdmin},
+    models::*,
+    utils::{parse_object_id, AppError},
+};
+
+/// Get wallet transactions
+///
+/// User gets own wallet transactions, newest first.
+/// Pass the returned `nextCursor` as `cursor` to get the next page.
+#[utoipa::path(
+    get,
+    path = "/api/v1/wallet/transactions",
+    params(
+        ("authorization" = String, Header, description = "JWT token"),
+        GetTransactionsParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Transaction list", body = GetTransactionsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid cursor or pageSize", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse)
+    ),
+    tag = "App User API"
+)]
+pub async fn get_transactions_handler(
+    claims: JwtClaims,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<GetTransactionsParams>,
+) -> Result<Json<GetTransactionsResponse>, AppError> {
+    let res = get_transactions(&db, claims.id, &params).await?;
+    Ok(Json(res))
+}
+
+/// Get wallet transactions of a user
+///
+/// Admin gets the wallet transactions of any user, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/walletTransactions",
+    params(
+        ("authorization" = String, Header, description = "Admin JWT token"),
+        AdminGetTransactionsParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Transaction list", body = GetTransactionsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid cursor or pageSize", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn admin_get_transactions_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<AdminGetTransactionsParams>,
+) -> Result<Json<GetTransactionsResponse>, AppError> {
+    claims.check_permission(AdminPermission::TRANSACTIONS)?;
+    let user_id = params.user_id;
+    let res = get_transactions(&db, user_id, &params.into()).await?;
+    Ok(Json(res))
+}
+
+async fn get_transactions(
+    db: &AppDatabase,
+    user_id: u32,
+    params: &GetTransactionsParams,
+) -> Result<GetTransactionsResponse, AppError> {
+    let mut filter = doc! {"userId": user_id};
+    if let Some(transaction_type) = &params.transaction_type {
+        filter.insert("transactionType", transaction_type.to_bson()?);
+    }
+    if let Some(status) = &params.status {
+        filter.insert("status", status.to_bson()?);
+    }
+    let mut created_ts = doc! {};
+    if let Some(from_ts) = params.from_ts {
+        created_ts.insert("$gte", from_ts as i64);
+    }
+    if let Some(to_ts) = params.to_ts {
+        created_ts.insert("$lte", to_ts as i64);
+    }
+    if !created_ts.is_empty() {
+        filter.insert("createdTs", created_ts);
+    }
+    if let Some(cursor) = &params.cursor {
+        let cursor = parse_object_id(cursor, "Not able to parse cursor value")?;
+        filter.insert("_id", doc! {"$lt": cursor});
+    }
+    params
+        .validate()
+        .map_err(|err| AppError::BadRequestErr(err.to_string()))?;
+    let page_size = params
+        .page_size
+        .unwrap_or(MAX_TRANSACTIONS_PAGE_SIZE)
+        .clamp(1, MAX_TRANSACTIONS_PAGE_SIZE);
+    // one extra record tells whether there is a next page
+    let options = FindOptions::builder()
+        .sort(Some(doc! {"_id": -1}))
+        .limit(Some(page_size as i64 + 1))
+        .build();
+    let mut data = db
+        .find::<WalletTransaction>(
+            DB_NAME,
+            COLL_WALLET_TRANSACTIONS,
+            Some(filter),
+            Some(options),
+        )
+        .await?;
+    let mut next_cursor = None;
+    if data.len() as u64 > page_size {
+        data.truncate(page_size as usize);
+        next_cursor = data.last().and_then(|t| t.id()).map(|id| id.to_string());
+    }
+    let res = GetTransactionsResponse {
+        success: true,
+        data,
+        next_cursor,
+    };
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_get_transactions() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        for amount in [10, 20, 30] {
+            let transaction =
+                WalletTransaction::add_bal_init_trans(1, Money::new(amount, 0), Money::default());
+            db.insert_one(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+                .await
+                .unwrap();
+        }
+        let transaction =
+            WalletTransaction::add_bal_init_trans(2, Money::new(5, 0), Money::default());
+        db.insert_one(DB_NAME, COLL_WALLET_TRANSACTIONS, &transaction, None)
+            .await
+            .unwrap();
+
+        // pages are returned newest first until the cursor runs out
+        let uri = "/api/v1/wallet/transactions?pageSize=2";
+        let (status, res) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"].as_array().unwrap().len(), 2);
+        assert_eq!(res["data"][0]["amount"]["real"], 30);
+        assert!(res["data"][0]["balanceBefore"].is_object());
+        let cursor = res["nextCursor"].as_str().unwrap();
+        let uri = format!("/api/v1/wallet/transactions?pageSize=2&cursor={}", cursor);
+        let (_, res) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(res["data"].as_array().unwrap().len(), 1);
+        assert_eq!(res["data"][0]["amount"]["real"], 10);
+        assert!(res["nextCursor"].is_null());
+
+        let uri = "/api/v1/wallet/transactions?status=COMPLETED";
+        let (_, res) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert!(res["data"].as_array().unwrap().is_empty());
+        let uri = "/api/v1/wallet/transactions?transactionType=ADD_BALANCE&toTs=1";
+        let (_, res) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert!(res["data"].as_array().unwrap().is_empty());
+        let uri = "/api/v1/wallet/transactions?cursor=abc";
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let uri = "/api/v1/wallet/transactions?pageSize=18446744073709551615";
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // admin can query any user
+        let uri = "/api/v1/admin/walletTransactions?userId=2";
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"].as_array().unwrap().len(), 1);
+        assert_eq!(res["data"][0]["userId"], 2);
+    }
+}
@@ -0,0 +1,379 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use futures::FutureExt;
+use mongodb::{bson::doc, options::FindOptions};
+use std::sync::Arc;
+use validator::Validate;
+
+use super::{
+    add_bal::TRANSACTION_ID_PARSE_ERR,
+    helper::{
+        get_wallet_transaction, insert_wallet_transaction_session, update_wallet_with_session,
+    },
+};
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    jwt::JwtClaimsAdmin,
+    models::*,
+    utils::{get_epoch_ts, parse_object_id, AppError, ValidatedBody},
+};
+
+/// Get withdrawals
+///
+/// Admin lists the withdrawal requests in the payout queue, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/withdrawals",
+    params(
+        ("authorization" = String, Header, description = "Admin JWT token"),
+        GetWithdrawalsParams
+    ),
+    security(("authorization" = [])),
+    responses(
+        (status = StatusCode::OK, description = "Withdrawal list", body = GetWithdrawalsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid pageSize or pageIndex", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn get_withdrawals_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    Query(params): Query<GetWithdrawalsParams>,
+) -> Result<Json<GetWithdrawalsResponse>, AppError> {
+    claims.check_permission(AdminPermission::PAYOUTS)?;
+    params
+        .validate()
+        .map_err(|err| AppError::BadRequestErr(err.to_string()))?;
+    let status = params
+        .status
+        .clone()
+        .unwrap_or(WalletTransactionStatus::Pending);
+    let mut filter = doc! {
+        "transactionType": WalltetTransactionType::Withdraw.to_bson()?,
+        "status": status.to_bson()?
+    };
+    if let Some(user_id) = params.user_id {
+        filter.insert("userId", user_id);
+    }
+    let page_index = params.page_index.unwrap_or(0);
+    let page_size = params.page_size.unwrap_or(MAX_PAGE_SIZE);
+    let options = FindOptions::builder()
+        .sort(Some(doc! {"_id": 1}))
+        .skip(Some(page_index.saturating_mul(page_size)))
+        .limit(Some(page_size as i64))
+        .build();
+    let data = db
+        .find::<WalletTransaction>(
+            DB_NAME,
+            COLL_WALLET_TRANSACTIONS,
+            Some(filter),
+            Some(options),
+        )
+        .await?;
+    let res = GetWithdrawalsResponse {
+        success: true,
+        data,
+    };
+    Ok(Json(res))
+}
+
+/// Approve withdrawal
+///
+/// Admin approves a pending withdrawal. The payout worker pays out the approved withdrawals.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/withdrawals/approve",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = WithdrawalIdReq,
+    responses(
+        (status = StatusCode::OK, description = "Withdrawal approved", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Pending withdrawal not found", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn approve_withdrawal_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<WithdrawalIdReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::PAYOUTS)?;
+    let transaction_id = parse_object_id(&body.transaction_id, TRANSACTION_ID_PARSE_ERR)?;
+    let filter = doc! {
+        "_id": transaction_id,
+        "transactionType": WalltetTransactionType::Withdraw.to_bson()?,
+        "status": WalletTransactionStatus::Pending.to_bson()?
+    };
+    let update = doc! {
+        "$set": {
+            "status": WalletTransactionStatus::Approved.to_bson()?,
+            "updatedBy": claims.data.id,
+            "updatedTs": get_epoch_ts() as i64
+        }
+    };
+    let result = db
+        .update_one(DB_NAME, COLL_WALLET_TRANSACTIONS, filter, update, None)
+        .await?;
+    if result.matched_count == 0 {
+        let err = AppError::NotFound("pending withdrawal not found".into());
+        return Err(err);
+    }
+    let res = GenericResponse {
+        success: true,
+        message: "Withdrawal approved".to_owned(),
+    };
+    Ok(Json(res))
+}
+
+/// Reject withdrawal
+///
+/// Admin rejects a pending withdrawal and the reserved amount is credited back to the user
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/withdrawals/reject",
+    params(("authorization" = String, Header, description = "Admin JWT token")),
+    security(("authorization" = [])),
+    request_body = RejectWithdrawalReq,
+    responses(
+        (status = StatusCode::OK, description = "Withdrawal rejected", body = GenericResponse),
+        (status = StatusCode::NOT_FOUND, description = "Pending withdrawal not found", body = GenericResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Invalid token", body = GenericResponse),
+        (status = StatusCode::FORBIDDEN, description = "Permission denied", body = GenericResponse)
+    ),
+    tag = "Admin API"
+)]
+pub async fn reject_withdrawal_handler(
+    claims: JwtClaimsAdmin,
+    State(db): State<Arc<AppDatabase>>,
+    ValidatedBody(body): ValidatedBody<RejectWithdrawalReq>,
+) -> Result<Json<GenericResponse>, AppError> {
+    claims.check_permission(AdminPermission::PAYOUTS)?;
+    let transaction_id = parse_object_id(&body.transaction_id, TRANSACTION_ID_PARSE_ERR)?;
+    let filter = doc! {
+        "_id": transaction_id,
+        "transactionType": WalltetTransactionType::Withdraw.to_bson()?,
+        "status": WalletTransactionStatus::Pending.to_bson()?
+    };
+    let transaction = get_wallet_transaction(&db, Some(filter))
+        .await?
+        .ok_or(AppError::NotFound("pending withdrawal not found".into()))?;
+    reverse_withdrawal(
+        &db,
+        &transaction,
+        WalletTransactionStatus::Pending,
+        WalletTransactionStatus::Rejected,
+        &body.reason,
+        claims.data.id,
+    )
+    .await?;
+    let res = GenericResponse {
+        success: true,
+        message: "Withdrawal rejected".to_owned(),
+    };
+    Ok(Json(res))
+}
+
+/// Move the withdrawal from `from` status to `to` status and credit back the reserved amount.
+/// The refund is recorded as a `WithdrawReversal` transaction referencing the withdrawal.
+pub async fn reverse_withdrawal(
+    db: &AppDatabase,
+    withdrawal: &WalletTransaction,
+    from: WalletTransactionStatus,
+    to: WalletTransactionStatus,
+    reason: &str,
+    updated_by: u32,
+) -> anyhow::Result<()> {
+    let transaction_id = withdrawal.id().unwrap_or_default();
+    let transaction_id = parse_object_id(transaction_id, TRANSACTION_ID_PARSE_ERR)
+        .map_err(|_| anyhow::anyhow!(TRANSACTION_ID_PARSE_ERR))?;
+    db.execute_transaction(None, None, |db, session| {
+        let withdrawal = withdrawal.clone();
+        let from = from.clone();
+        let to = to.clone();
+        let reason = reason.to_string();
+        async move {
+            let filter = doc! {"_id": transaction_id, "status": from.to_bson()?};
+            let update = doc! {
+                "$set": {
+                    "status": to.to_bson()?,
+                    "errorReason": &reason,
+                    "updatedBy": updated_by,
+                    "updatedTs": get_epoch_ts() as i64
+                }
+            };
+            let result = db
+                .update_one_with_session(
+                    session,
+                    DB_NAME,
+                    COLL_WALLET_TRANSACTIONS,
+                    filter,
+                    update,
+                    None,
+                )
+                .await?;
+            if result.matched_count == 0 {
+                return Err(anyhow::anyhow!("withdrawal is not in {:?} status", from));
+            }
+            let user_id = withdrawal.user_id();
+            let amount = withdrawal.amount().real();
+            let (balance_before, balance_after) = update_wallet_with_session(
+                db,
+                session,
+                user_id,
+                amount,
+                0,
+                &WalltetTransactionType::WithdrawReversal,
+            )
+            .await?;
+            let reversal = WalletTransaction::withdraw_reversal_trans(
+                &withdrawal,
+                balance_before,
+                balance_after,
+                &reason,
+                updated_by,
+            );
+            insert_wallet_transaction_session(db, session, &reversal).await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        jobs::payout::process_payouts, payment::MockPayoutProvider, utils::test_helper::*,
+    };
+
+    async fn withdraw(db: &Arc<AppDatabase>, user_id: u32, amount: u64) -> String {
+        let uri = "/api/v1/wallet/withdrawBalInit";
+        let body = json!({"amount": amount, "receiverUpiId": "user@upi"});
+        let (status, res) = send_request(db, Method::POST, uri, user_id, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        res["transactionId"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_payout_queue() {
+        let db = test_db();
+        insert_admin(&db, 100).await;
+        add_withdrawable_balance(&db, 1, 100).await;
+
+        // the amount is reserved as soon as the withdrawal is requested
+        let transaction_id = withdraw(&db, 1, 40).await;
+        assert_eq!(get_balance(&db, 1).await, Money::new(60, 0));
+        let uri = "/api/v1/admin/withdrawals";
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, res) = send_admin_request(&db, Method::GET, uri, 100, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(res["data"][0]["_id"], transaction_id.as_str());
+        let page_uri = "/api/v1/admin/withdrawals?pageSize=1000000&pageIndex=1000000";
+        let (status, _) = send_admin_request(&db, Method::GET, page_uri, 100, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // rejection restores the reserved balance with a reversal transaction
+        let uri = "/api/v1/admin/withdrawals/reject";
+        let body = json!({"transactionId": &transaction_id, "reason": "invalid upi id"});
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(get_balance(&db, 1).await, Money::new(100, 0));
+        let filter = doc! {"referenceTransactionId": &transaction_id};
+        let reversal = get_wallet_transaction(&db, Some(filter))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reversal.amount(), Money::new(40, 0));
+
+        // approved withdrawal is paid out by the worker
+        let transaction_id = withdraw(&db, 1, 30).await;
+        let uri = "/api/v1/admin/withdrawals/approve";
+        let body = json!({"transactionId": &transaction_id});
+        let (status, _) = send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        assert_eq!(status, StatusCode::OK);
+        process_payouts(&db, &MockPayoutProvider::new())
+            .await
+            .unwrap();
+        let filter = doc! {"_id": parse_object_id(&transaction_id, "").unwrap()};
+        let transaction = get_wallet_transaction(&db, Some(filter.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.status(), &WalletTransactionStatus::Completed);
+        assert_eq!(get_balance(&db, 1).await, Money::new(70, 0));
+
+        // provider failure restores the balance
+        let transaction_id = withdraw(&db, 1, 20).await;
+        let uri = "/api/v1/admin/withdrawals/approve";
+        let body = json!({"transactionId": &transaction_id});
+        send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        process_payouts(&db, &MockPayoutProvider::failing())
+            .await
+            .unwrap();
+        let filter = doc! {"_id": parse_object_id(&transaction_id, "").unwrap()};
+        let transaction = get_wallet_transaction(&db, Some(filter))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.status(), &WalletTransactionStatus::Error);
+        assert_eq!(get_balance(&db, 1).await, Money::new(70, 0));
+
+        // payout of unknown outcome stays in processing and is checked again later
+        let transaction_id = withdraw(&db, 1, 10).await;
+        let uri = "/api/v1/admin/withdrawals/approve";
+        let body = json!({"transactionId": &transaction_id});
+        send_admin_request(&db, Method::POST, uri, 100, Some(body)).await;
+        process_payouts(&db, &MockPayoutProvider::unreachable())
+            .await
+            .unwrap();
+        let filter = doc! {"_id": parse_object_id(&transaction_id, "").unwrap()};
+        let transaction = get_wallet_transaction(&db, Some(filter.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.status(), &WalletTransactionStatus::Processing);
+        assert_eq!(get_balance(&db, 1).await, Money::new(60, 0));
+        process_payouts(&db, &MockPayoutProvider::new())
+            .await
+            .unwrap();
+        let transaction = get_wallet_transaction(&db, Some(filter.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.status(), &WalletTransactionStatus::Processing);
+        let stale_ts = (get_epoch_ts() - PAYOUT_RECHECK_SECS - 1) as i64;
+        let update = doc! {"$set": {"updatedTs": stale_ts}};
+        db.update_one(
+            DB_NAME,
+            COLL_WALLET_TRANSACTIONS,
+            filter.clone(),
+            update,
+            None,
+        )
+        .await
+        .unwrap();
+        process_payouts(&db, &MockPayoutProvider::new())
+            .await
+            .unwrap();
+        let transaction = get_wallet_transaction(&db, Some(filter))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.status(), &WalletTransactionStatus::Completed);
+        assert_eq!(get_balance(&db, 1).await, Money::new(60, 0));
+    }
+}
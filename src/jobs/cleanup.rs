@@ -5,10 +5,9 @@ use tokio::time::interval;
 use crate::{constants::*, database::AppDatabase, utils::get_epoch_ts};
 
 /// This function periodically deletes the old OTP & used tokens from database
-pub async fn cleanup_job(db: Arc<AppDatabase>) {
+pub async fn cleanup_job(db: Arc<AppDatabase>, interval_secs: u64) {
     tracing::debug!("initializing cleanup scheduler job");
-    // CLEANUP_JOB_INTERVAL is mentioned in seconds
-    let mut interval = interval(Duration::from_secs(CLEANUP_JOB_INTERVAL));
+    let mut interval = interval(Duration::from_secs(interval_secs));
     loop {
         interval.tick().await;
         let (otp_result, token_result) = tokio::join!(delete_otp(&db), delete_used_tokens(&db));
@@ -29,6 +28,13 @@ async fn delete_otp(db: &Arc<AppDatabase>) -> anyhow::Result<()> {
     let cut_off = ts - cut_off;
     let filter = doc! {"validTill": {"$lt": cut_off as i64}};
     db.delete_many(DB_NAME, COLL_OTP, filter, None).await?;
+    // delete the otp limits which are neither updated nor locked within the retention period
+    let filter = doc! {
+        "updateTs": {"$lt": cut_off as i64},
+        "lockedTill": {"$not": {"$gte": cut_off as i64}}
+    };
+    db.delete_many(DB_NAME, COLL_OTP_LIMITS, filter, None)
+        .await?;
     Ok(())
 }
 
@@ -0,0 +1,317 @@
+use chrono::{prelude::*, Duration as ChronoDuration};
+use mongodb::{
+    bson::{doc, to_document, Document},
+    options::UpdateOptions,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::time::interval;
+
+use crate::{
+    constants::*,
+    database::{is_duplicate_key_error, AppDatabase},
+    events::{ContestEvent, ContestEventType, EventBus},
+    models::*,
+    utils::{generate_invite_code, get_epoch_ts},
+};
+
+/// This function periodically creates the contests of the scheduled templates, activates
+/// the contests which are due for activation and announces the contests starting soon
+pub async fn contest_scheduler_job(
+    db: Arc<AppDatabase>,
+    events: Arc<EventBus>,
+    interval_secs: u64,
+) {
+    tracing::debug!("initializing contest scheduler job");
+    let mut interval = interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(err) = create_scheduled_contests(&db).await {
+            tracing::debug!("Error in contest_scheduler_job create => {:?}", err);
+        }
+        if let Err(err) = activate_scheduled_contests(&db, &events).await {
+            tracing::debug!("Error in contest_scheduler_job activate => {:?}", err);
+        }
+        if let Err(err) = announce_starting_contests(&db, &events).await {
+            tracing::debug!("Error in contest_scheduler_job starting soon => {:?}", err);
+        }
+    }
+}
+
+/// Create the due contests of every scheduled template which is not paused.
+/// Each contest carries the `scheduleKey` of its occurrence so that it is created only once.
+/// A template which fails, e.g. for a duplicate title, does not stop the other templates.
+pub async fn create_scheduled_contests(db: &AppDatabase) -> anyhow::Result<u64> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {"schedule.isPaused": false};
+    let templates = db
+        .find::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, Some(filter), None)
+        .await?;
+    let mut created = 0;
+    for template in templates {
+        let (Some(template_id), Some(schedule)) = (&template._id, &template.schedule) else {
+            continue;
+        };
+        for start_ts in schedule.due_start_times(ts) {
+            let schedule_key = format!("{template_id}:{start_ts}");
+            match create_contest(db, &template, schedule, start_ts, &schedule_key).await {
+                Ok(true) => created += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::debug!(
+                        "Error in creating scheduled contest {schedule_key} => {err:?}"
+                    );
+                }
+            }
+        }
+    }
+    if created > 0 {
+        tracing::debug!("created {} scheduled contests", created);
+    }
+    Ok(created)
+}
+
+async fn create_contest(
+    db: &AppDatabase,
+    template: &ContestTemplate,
+    schedule: &ContestSchedule,
+    start_ts: i64,
+    schedule_key: &str,
+) -> anyhow::Result<bool> {
+    let start_time = Utc
+        .timestamp_opt(start_ts, 0)
+        .single()
+        .ok_or(anyhow::anyhow!("invalid start time {start_ts}"))?;
+    let mut props = template.contest.clone();
+    props.title = format!("{} {}", template.name, start_time.format("%d %b %Y %H:%M"));
+    props.start_time = start_time;
+    props.end_time = start_time + ChronoDuration::seconds(schedule.duration as i64);
+    props.activate_at = Some(start_time - ChronoDuration::seconds(schedule.activate_before as i64));
+    let contest = ContestWithQuestion {
+        _id: None,
+        invite_code: props.is_private.then(generate_invite_code),
+        props,
+        status: ContestStatus::CREATED,
+        joined_count: 0,
+        questions: Some(template.questions.clone()),
+        created_ts: Some(get_epoch_ts()),
+        created_by: template.created_by,
+        updated_ts: None,
+        updated_by: None,
+    };
+    let mut contest = to_document(&contest)?;
+    contest.remove("_id");
+    // concurrent runs upsert the same occurrence, only one of them inserts the contest
+    let filter = doc! {"scheduleKey": schedule_key};
+    let update = doc! {"$setOnInsert": contest};
+    let options = UpdateOptions::builder().upsert(Some(true)).build();
+    let result = db
+        .update_one(
+            DB_NAME,
+            COLL_CONTESTS,
+            filter.clone(),
+            update,
+            Some(options),
+        )
+        .await;
+    match result {
+        Ok(result) => Ok(result.upserted_id.is_some()),
+        Err(err) if is_duplicate_key_error(&err) => {
+            let existing = db
+                .find_one::<Document>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+                .await?;
+            if existing.is_some() {
+                return Ok(false);
+            }
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Activate the CREATED contests whose `activateAt` has passed and announce them to the users.
+/// A contest is announced only by the run which changed its status.
+pub async fn activate_scheduled_contests(
+    db: &AppDatabase,
+    events: &EventBus,
+) -> anyhow::Result<u64> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {
+        "status": ContestStatus::CREATED.to_bson()?,
+        "activateAt": {"$lte": ts},
+        "endTime": {"$gt": ts}
+    };
+    let contests = db
+        .find::<ContestWithQuestion>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await?;
+    let mut activated = 0;
+    for contest in contests {
+        let Some(contest_id) = contest._id.as_ref() else {
+            continue;
+        };
+        let has_questions = contest
+            .questions
+            .as_ref()
+            .is_some_and(|questions| questions.iter().any(|q| q.is_active));
+        if !has_questions {
+            tracing::debug!("scheduled contest {} has no questions", contest_id);
+            continue;
+        }
+        let oid = mongodb::bson::oid::ObjectId::parse_str(contest_id)?;
+        let filter = doc! {"_id": oid, "status": ContestStatus::CREATED.to_bson()?};
+        let update = doc! {
+            "$set": {
+                "status": ContestStatus::ACTIVE.to_bson()?,
+                "updatedTs": ts
+            }
+        };
+        let result = db
+            .update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        if result.modified_count == 0 {
+            continue;
+        }
+        announce_contest(db, contest_id, &contest.props.title).await?;
+        let event = ContestEvent::new(ContestEventType::CONTEST_ACTIVATED, contest_id)
+            .with_private(contest.props.is_private);
+        events.publish(event.with_title(&contest.props.title));
+        activated += 1;
+    }
+    if activated > 0 {
+        tracing::debug!("activated {} scheduled contests", activated);
+    }
+    Ok(activated)
+}
+
+/// Publish `CONTEST_STARTING_SOON` once for the ACTIVE contests starting within
+/// `CONTEST_STARTING_SOON_SECS`
+pub async fn announce_starting_contests(
+    db: &AppDatabase,
+    events: &EventBus,
+) -> anyhow::Result<u64> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {
+        "status": ContestStatus::ACTIVE.to_bson()?,
+        "startTime": {"$gt": ts, "$lte": ts + CONTEST_STARTING_SOON_SECS as i64},
+        "startingSoonTs": {"$exists": false}
+    };
+    let contests = db
+        .find::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter.clone()), None)
+        .await?;
+    let mut announced = 0;
+    for contest in contests {
+        let Some(contest_id) = contest._id.as_ref() else {
+            continue;
+        };
+        let mut filter = filter.clone();
+        filter.insert("_id", mongodb::bson::oid::ObjectId::parse_str(contest_id)?);
+        let update = doc! {"$set": {"startingSoonTs": ts}};
+        let result = db
+            .update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+            .await?;
+        if result.modified_count == 0 {
+            continue;
+        }
+        let event = ContestEvent::new(ContestEventType::CONTEST_STARTING_SOON, contest_id)
+            .with_private(contest.props.is_private);
+        events.publish(event.with_title(&contest.props.title));
+        announced += 1;
+    }
+    Ok(announced)
+}
+
+async fn announce_contest(db: &AppDatabase, contest_id: &str, title: &str) -> anyhow::Result<()> {
+    let filter = doc! {"isActive": true};
+    let users = db
+        .find::<User>(DB_NAME, COLL_USERS, Some(filter), None)
+        .await?;
+    let requests = users
+        .into_iter()
+        .map(|user| {
+            let mut data = HashMap::new();
+            data.insert("contestId".into(), contest_id.to_string());
+            data.insert("title".into(), title.to_string());
+            NotificationReq::new(user.id, EVENT_CONTEST_ACTIVATED, data)
+        })
+        .collect::<Vec<_>>();
+    if !requests.is_empty() {
+        db.insert_many::<NotificationReq>(DB_NAME, COLL_NOTIFICATION_REQUESTS, &requests, None)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{database::MemoryStorage, utils::test_helper::*};
+
+    #[tokio::test]
+    async fn test_scheduled_contests() {
+        let storage = MemoryStorage::new().with_unique_index(DB_NAME, COLL_CONTESTS, &["title"]);
+        let db = Arc::new(AppDatabase::with_storage(storage));
+        let ts = get_epoch_ts() as i64;
+        let mut props = contest_props(0, 100);
+        props.category = ContestCategory::Others;
+        let schedule = ContestSchedule {
+            recurrence: Recurrence::DAILY,
+            first_start_time: Utc.timestamp_opt(ts + 600, 0).unwrap(),
+            duration: 3600,
+            activate_before: 900,
+            create_before: None,
+            is_paused: false,
+        };
+        let template = ContestTemplate {
+            _id: None,
+            name: "daily quiz".into(),
+            contest: props,
+            questions: vec![question(1), question(2)],
+            schedule: Some(schedule),
+            created_ts: None,
+            created_by: Some(100),
+        };
+        db.insert_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, &template, None)
+            .await
+            .unwrap();
+
+        // a restart of the job does not create the same occurrence again
+        assert_eq!(create_scheduled_contests(&db).await.unwrap(), 1);
+        assert_eq!(create_scheduled_contests(&db).await.unwrap(), 0);
+        let contests = db
+            .find::<Contest>(DB_NAME, COLL_CONTESTS, None, None)
+            .await
+            .unwrap();
+        assert_eq!(contests.len(), 1);
+        assert_eq!(contests[0].status, ContestStatus::CREATED);
+        assert_eq!(contests[0].props.start_time.timestamp(), ts + 600);
+        assert_eq!(contests[0].props.end_time.timestamp(), ts + 4200);
+
+        let events = EventBus::default();
+        let mut receiver = events.subscribe();
+        assert_eq!(activate_scheduled_contests(&db, &events).await.unwrap(), 1);
+        assert_eq!(activate_scheduled_contests(&db, &events).await.unwrap(), 0);
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.event_type, ContestEventType::CONTEST_ACTIVATED);
+        // the contest starting in ten minutes is not announced as starting soon yet
+        assert_eq!(announce_starting_contests(&db, &events).await.unwrap(), 0);
+        let contest_id = contests[0]._id.as_ref().unwrap();
+        let contest = get_contest(&db, contest_id).await;
+        assert_eq!(contest.status, ContestStatus::ACTIVE);
+
+        // a template failing on a duplicate title does not stop the other templates
+        for name in ["broken quiz", "weekly quiz"] {
+            let mut template = template.clone();
+            template.name = name.into();
+            db.insert_one::<ContestTemplate>(DB_NAME, COLL_CONTEST_TEMPLATES, &template, None)
+                .await
+                .unwrap();
+        }
+        let title = format!(
+            "broken quiz {}",
+            contests[0].props.start_time.format("%d %b %Y %H:%M")
+        );
+        db.insert_one(DB_NAME, COLL_CONTESTS, &doc! {"title": title}, None)
+            .await
+            .unwrap();
+        assert_eq!(create_scheduled_contests(&db).await.unwrap(), 1);
+    }
+}
@@ -1,16 +1,16 @@
 use std::collections::HashMap;
 
-use mongodb::{bson::doc, ClientSession};
+use mongodb::bson::doc;
 
 use crate::{
     constants::*,
-    database::AppDatabase,
+    database::{AppDatabase, DbSession},
     handlers::wallet::helper::{insert_wallet_transaction_session, update_wallet_with_session},
     models::{
         contest::{Contest, ContestStatus},
         notification::NotificationReq,
         play_tracker::PlayTracker,
-        wallet::{Money, WalletTransaction},
+        wallet::{Money, WalletTransaction, WalltetTransactionType},
     },
     utils::{get_epoch_ts, parse_object_id},
 };
@@ -19,7 +19,7 @@ use super::finish_contest::update_play_trackers;
 
 pub async fn cancel_contest(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest: &Contest,
     all_play_trackers: &Vec<PlayTracker>,
 ) -> anyhow::Result<()> {
@@ -42,7 +42,7 @@ pub async fn cancel_contest(
 
 async fn refund_entry_fee(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest: &Contest,
     play_tracker: &PlayTracker,
 ) -> anyhow::Result<()> {
@@ -66,8 +66,15 @@ async fn refund_entry_fee(
         .unwrap_or_default();
     let amount = Money::new(real, bonus);
     let user_id = play_tracker.user_id;
-    let (balance_before, balance_after) =
-        update_wallet_with_session(db, session, user_id, real, bonus, false, false).await?;
+    let (balance_before, balance_after) = update_wallet_with_session(
+        db,
+        session,
+        user_id,
+        real,
+        bonus,
+        &WalltetTransactionType::RefundContestEntryFee,
+    )
+    .await?;
     let transaction = WalletTransaction::refund_contest_entry_fee_trans(
         user_id,
         contest_id,
@@ -81,7 +88,7 @@ async fn refund_entry_fee(
 
 async fn create_push_for_cancel(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     contest: &Contest,
     total_players: u32,
@@ -109,7 +116,7 @@ async fn create_push_for_cancel(
 
 async fn update_contest_status(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest: &Contest,
     all_play_trackers: &Vec<PlayTracker>,
 ) -> anyhow::Result<()> {
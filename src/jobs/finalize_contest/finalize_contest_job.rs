@@ -6,24 +6,31 @@ use super::finish_contest::finish_contest;
 use crate::{
     constants::*,
     database::AppDatabase,
+    events::EventBus,
+    jobs::play_tracker_sweep::finish_overrun_play_trackers,
     models::contest::{Contest, ContestStatus},
     utils::get_epoch_ts,
 };
 
-pub async fn finalize_contest_job(db: Arc<AppDatabase>) {
+pub async fn finalize_contest_job(db: Arc<AppDatabase>, events: Arc<EventBus>, interval_secs: u64) {
     tracing::debug!("initializing finalize contest scheduler job");
-    let mut interval = interval(Duration::from_secs(FINALIZE_CONTEST_JOB_INTERVAL));
+    let mut interval = interval(Duration::from_secs(interval_secs));
     loop {
         interval.tick().await;
-        let result = check_and_finalize_contest(&db).await;
+        let result = check_and_finalize_contest(&db, &events).await;
         if result.is_err() {
             tracing::debug!("Error in finalize_contest_job => {:?}", result.err());
         }
     }
 }
 
-pub async fn check_and_finalize_contest(db: &Arc<AppDatabase>) -> anyhow::Result<()> {
+pub async fn check_and_finalize_contest(
+    db: &Arc<AppDatabase>,
+    events: &EventBus,
+) -> anyhow::Result<()> {
     tracing::debug!("check_and_finalize_contest called");
+    // overrun plays are finished first so that they are ranked with the finish time at deadline
+    finish_overrun_play_trackers(db).await?;
     let ts = get_epoch_ts() as i64;
     let status = ContestStatus::ACTIVE.to_bson()?;
     let filter = doc! {"status": status, "endTime": {"$lte": ts}};
@@ -35,7 +42,7 @@ pub async fn check_and_finalize_contest(db: &Arc<AppDatabase>) -> anyhow::Result
         .await
         .unwrap_or_default();
     for contest in contests {
-        finish_contest(db, &contest).await;
+        finish_contest(db, events, &contest).await;
     }
     Ok(())
 }
@@ -1,9 +1,6 @@
 use futures::FutureExt;
-use mongodb::{
-    bson::{doc, Document},
-    ClientSession,
-};
-use std::{cmp::Ordering, sync::Arc};
+use mongodb::bson::{doc, Document};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
 
 use super::{
     cancel_contest::cancel_contest, credit_prize::credit_prize_value,
@@ -11,36 +8,62 @@ use super::{
 };
 use crate::{
     constants::*,
-    database::AppDatabase,
+    database::{AppDatabase, DbSession},
+    events::{ContestEvent, ContestEventType, EventBus},
     models::{
-        contest::{Contest, ContestStatus, PrizeSelection},
+        contest::{Contest, ContestStatus, PrizeSelection, TiePolicy},
         play_tracker::{PlayTracker, PlayTrackerStatus},
         wallet::Money,
     },
     utils::{get_epoch_ts, parse_object_id},
 };
 
-pub async fn finish_contest(db: &Arc<AppDatabase>, contest: &Contest) {
+pub async fn finish_contest(db: &Arc<AppDatabase>, events: &EventBus, contest: &Contest) {
     tracing::debug!("finish_contest: {:?}", contest._id);
     let result = db
         .execute_transaction(None, None, |db, session| {
             let contest = contest.clone();
             async move {
-                update_contest(db, session, &contest).await?;
-                Ok(())
+                let winners = update_contest(db, session, &contest).await?;
+                Ok(winners)
             }
             .boxed()
         })
         .await;
-    if let Err(e) = result {
-        tracing::debug!("Not able to finish contest: {:?}", e);
-        let r = update_contest_error(db, contest).await;
-        if r.is_err() {
-            tracing::debug!("Error in update contest error => {:?}", r.err());
+    match result {
+        Ok(winners) => publish_contest_events(events, contest, winners),
+        Err(e) => {
+            tracing::debug!("Not able to finish contest: {:?}", e);
+            let r = update_contest_error(db, contest).await;
+            if r.is_err() {
+                tracing::debug!("Error in update contest error => {:?}", r.err());
+            }
         }
     }
 }
 
+/// Publish the end of the contest, and the results with the prizes when it is not cancelled
+fn publish_contest_events(events: &EventBus, contest: &Contest, winners: Option<Vec<PlayTracker>>) {
+    let contest_id = contest._id.as_deref().unwrap_or_default();
+    let new_event = |event_type| {
+        ContestEvent::new(event_type, contest_id)
+            .with_title(&contest.props.title)
+            .with_private(contest.props.is_private)
+    };
+    events.publish(new_event(ContestEventType::CONTEST_ENDED));
+    let Some(winners) = winners else {
+        return;
+    };
+    events.publish(new_event(ContestEventType::RESULTS_READY));
+    for winner in winners {
+        let mut event = new_event(ContestEventType::PRIZE_CREDITED);
+        event.user_id = Some(winner.user_id);
+        event.recipient = Some(winner.user_id);
+        event.amount = winner.prize_amount;
+        events.publish(event);
+    }
+}
+
 async fn update_contest_error(db: &Arc<AppDatabase>, contest: &Contest) -> anyhow::Result<()> {
     let contest_id = contest
         ._id
@@ -58,28 +81,30 @@ async fn update_contest_error(db: &Arc<AppDatabase>, contest: &Contest) -> anyho
     Ok(())
 }
 
+/// Rank the players and credit the prizes. The winners are returned unless the contest is
+/// cancelled for not having the minimum required players.
 async fn update_contest(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest: &Contest,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<Vec<PlayTracker>>> {
     let contest_id = contest
         ._id
         .as_ref()
         .ok_or(anyhow::anyhow!("contest_id not present"))?;
-    let prize_money = Money::new(
-        contest.props.prize_value_real_money as u64,
-        contest.props.prize_value_bonus_money as u64,
-    );
-    let all_play_trackers = get_all_play_trackers(db, session, contest_id).await?;
+    let tie_policy = contest.props.tie_policy;
+    let all_play_trackers = get_all_play_trackers(db, session, contest, tie_policy).await?;
     let total_player = all_play_trackers.len() as u32;
     let min_required_players = contest.props.min_required_players;
     if total_player < min_required_players {
-        return cancel_contest(db, session, contest, &all_play_trackers).await;
+        cancel_contest(db, session, contest, &all_play_trackers).await?;
+        return Ok(None);
     }
     let winners_count = get_winners_count(contest, total_player);
-    let winners = get_winners(&all_play_trackers, winners_count);
+    let prize_pool = get_prize_pool(&all_play_trackers);
+    let winners = get_winners(&all_play_trackers, winners_count, contest, prize_pool);
     for winner in winners.iter() {
+        let prize_money = winner.prize_amount.unwrap_or_default();
         credit_prize_value(db, session, winner.user_id, prize_money, contest_id).await?;
         create_push_for_prize_win(
             db,
@@ -90,16 +115,16 @@ async fn update_contest(
         )
         .await?;
     }
-    update_users(db, session, &all_play_trackers, &winners, prize_money).await?;
+    update_users(db, session, &all_play_trackers, &winners).await?;
     update_play_trackers(db, session, contest_id).await?;
     update_contest_status(db, session, contest_id, &all_play_trackers, &winners).await?;
 
-    Ok(())
+    Ok(Some(winners))
 }
 
 async fn update_contest_status(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest_id: &str,
     all_play_trackers: &Vec<PlayTracker>,
     winners: &Vec<PlayTracker>,
@@ -130,37 +155,66 @@ async fn update_contest_status(
 
 async fn get_all_play_trackers(
     db: &AppDatabase,
-    session: &mut ClientSession,
-    contest_id: &str,
+    session: &mut DbSession,
+    contest: &Contest,
+    tie_policy: TiePolicy,
 ) -> anyhow::Result<Vec<PlayTracker>> {
+    let contest_id = contest
+        ._id
+        .as_ref()
+        .ok_or(anyhow::anyhow!("contest_id not present"))?;
     let filter = play_tracker_filter(contest_id)?;
     let mut play_trackers = db
         .find_with_session::<PlayTracker>(session, DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
         .await?;
-    let ts = get_epoch_ts();
     for pt in play_trackers.iter_mut() {
-        let finish = pt.finish_ts.and(pt.updated_ts).unwrap_or(u64::MAX);
-        let start = pt.start_ts.unwrap_or(ts);
-        pt.time_taken = if finish >= start {
-            Some((finish - start) as u32)
-        } else {
-            Some(u32::MAX)
-        };
+        pt.time_taken = get_time_taken(contest, pt);
         pt.status = PlayTrackerStatus::ENDED;
     }
+    rank_play_trackers(&mut play_trackers, tie_policy);
+    Ok(play_trackers)
+}
+
+/// Time taken from the start till the finish, capped by the contest time budget.
+/// An unfinished play is charged the whole time until its deadline.
+fn get_time_taken(contest: &Contest, pt: &PlayTracker) -> Option<u32> {
+    let start = pt.start_ts?;
+    let deadline = pt
+        .deadline_ts
+        .unwrap_or_else(|| contest.props.play_deadline(start));
+    let finish = pt.finish_ts.unwrap_or(deadline).min(deadline);
+    Some(finish.saturating_sub(start) as u32)
+}
+
+/// Sort the play trackers and set the rank of every player with a positive weighted score.
+/// With `RANK_BY_TIME` every rank is unique. Otherwise players with the same score share
+/// the rank and the next rank is skipped for each of them (1, 1, 3).
+pub fn rank_play_trackers(play_trackers: &mut [PlayTracker], tie_policy: TiePolicy) {
     play_trackers.sort_unstable_by(sort_play_tracker);
+    let mut prev_score = None;
+    let mut prev_rank = 0;
     for (idx, pt) in play_trackers.iter_mut().enumerate() {
         let score = pt.score.unwrap_or(0);
-        if score > 0 {
-            pt.rank = Some((idx + 1) as u32);
+        if score <= 0 {
+            continue;
         }
+        let position = (idx + 1) as u32;
+        let rank = match tie_policy {
+            TiePolicy::RANK_BY_TIME => position,
+            TiePolicy::SPLIT_PRIZE | TiePolicy::SHARED_RANK if prev_score == Some(score) => {
+                prev_rank
+            }
+            TiePolicy::SPLIT_PRIZE | TiePolicy::SHARED_RANK => position,
+        };
+        pt.rank = Some(rank);
+        prev_score = Some(score);
+        prev_rank = rank;
     }
-    Ok(play_trackers)
 }
 
 pub async fn update_play_trackers(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     contest_id: &str,
 ) -> anyhow::Result<()> {
     let ts = get_epoch_ts() as i64;
@@ -176,10 +230,9 @@ pub async fn update_play_trackers(
 
 async fn update_users(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     all_play_trackers: &Vec<PlayTracker>,
     winners: &Vec<PlayTracker>,
-    prize_money: Money,
 ) -> anyhow::Result<()> {
     let users = all_play_trackers
         .iter()
@@ -190,56 +243,103 @@ async fn update_users(
     let update = doc! {"$set": {"updatedTs": ts}, "$inc": {"totalPlayed": 1}};
     db.update_many_with_session(session, DB_NAME, COLL_USERS, filter, update, None)
         .await?;
-    let real = prize_money.real() as i64;
-    let bonus = prize_money.bonus() as i64;
-    let users = winners.iter().map(|pt| pt.user_id).collect::<Vec<_>>();
-    let filter = doc! {"id": {"$in": users}};
-    let update = doc! {
-        "$set": {"updatedTs": ts},
-        "$inc": {"contestWon": 1, "totalEarning.real": real, "totalEarning.bonus": bonus}
-    };
-    db.update_many_with_session(session, DB_NAME, COLL_USERS, filter, update, None)
-        .await?;
+    for winner in winners.iter() {
+        let prize_money = winner.prize_amount.unwrap_or_default();
+        let real = prize_money.real() as i64;
+        let bonus = prize_money.bonus() as i64;
+        let filter = doc! {"id": winner.user_id};
+        let update = doc! {
+            "$set": {"updatedTs": ts},
+            "$inc": {"contestWon": 1, "totalEarning.real": real, "totalEarning.bonus": bonus}
+        };
+        db.update_one_with_session(session, DB_NAME, COLL_USERS, filter, update, None)
+            .await?;
+    }
 
     Ok(())
 }
 
+/// higher score first, then the faster total response time, time taken and start time
 fn sort_play_tracker(a: &PlayTracker, b: &PlayTracker) -> Ordering {
-    match a.score.cmp(&b.score) {
-        Ordering::Equal => match a.time_taken.cmp(&b.time_taken) {
-            Ordering::Equal => a.start_ts.cmp(&b.start_ts),
-            Ordering::Less => Ordering::Less,
-            Ordering::Greater => Ordering::Greater,
-        },
-        Ordering::Less => Ordering::Greater,
-        Ordering::Greater => Ordering::Less,
-    }
+    b.score
+        .cmp(&a.score)
+        .then_with(|| a.total_response_time.cmp(&b.total_response_time))
+        .then_with(|| a.time_taken.cmp(&b.time_taken))
+        .then_with(|| a.start_ts.cmp(&b.start_ts))
 }
 
+/// Number of paid ranks. `TOP_WINNERS` pays the configured count and `RATIO_BASED`
+/// pays `numerator * total_player / denominator` ranks rounded down.
+/// Players without a positive score are counted in `total_player` but never win.
 fn get_winners_count(contest: &Contest, total_player: u32) -> u32 {
     match contest.props.prize_selection {
         PrizeSelection::TOP_WINNERS => contest.props.top_winners_count.unwrap_or_default(),
         PrizeSelection::RATIO_BASED => {
-            let numerator = contest.props.prize_ratio_numerator.unwrap_or_default();
-            let denominator = contest.props.prize_ratio_denominator.unwrap_or(1);
-            (numerator * total_player) / denominator
+            let numerator = contest.props.prize_ratio_numerator.unwrap_or_default() as u64;
+            let denominator = contest.props.prize_ratio_denominator.unwrap_or(1).max(1) as u64;
+            ((numerator * total_player as u64) / denominator) as u32
         }
     }
 }
 
-fn get_winners(all_play_trackers: &Vec<PlayTracker>, winners_count: u32) -> Vec<PlayTracker> {
+/// total entry fee collected from the players
+fn get_prize_pool(all_play_trackers: &[PlayTracker]) -> Money {
+    all_play_trackers
+        .iter()
+        .filter_map(|pt| pt.paid_amount)
+        .fold(Money::default(), |pool, paid| pool + paid)
+}
+
+/// Players ranked within `winners_count` with a non zero prize, each with the prize set.
+/// Tied players at the last paid rank are all winners unless ranked by time.
+fn get_winners(
+    all_play_trackers: &Vec<PlayTracker>,
+    winners_count: u32,
+    contest: &Contest,
+    prize_pool: Money,
+) -> Vec<PlayTracker> {
+    let props = &contest.props;
+    let mut tied_count = HashMap::new();
+    for rank in all_play_trackers.iter().filter_map(|pt| pt.rank) {
+        *tied_count.entry(rank).or_insert(0) += 1;
+    }
     let winners = all_play_trackers
         .iter()
-        .filter(|pt| {
-            let rank = pt.rank.unwrap_or(0);
-            rank > 0 && rank < winners_count
+        .filter_map(|pt| {
+            let rank = pt.rank.filter(|rank| *rank <= winners_count)?;
+            // prizes of the ranks occupied by the tied players within the paid ranks
+            let tied = tied_count.get(&rank).copied().unwrap_or(1);
+            let last = (rank + tied - 1).min(winners_count);
+            let split = |prize_of: &dyn Fn(u32) -> Money| {
+                let total = (rank..=last).fold(Money::default(), |total, r| total + prize_of(r));
+                Money::new(total.real() / tied as u64, total.bonus() / tied as u64)
+            };
+            let prize = match props.tie_policy {
+                TiePolicy::RANK_BY_TIME => props.prize_for_rank(rank, prize_pool),
+                TiePolicy::SPLIT_PRIZE => split(&|r| props.prize_for_rank(r, prize_pool)),
+                TiePolicy::SHARED_RANK => {
+                    // the fixed prize is paid in full, the pool share is split so that
+                    // the payouts never exceed the pool
+                    let fixed = props.prize_for_rank(rank, Money::default());
+                    let pool_share = split(&|r| {
+                        props.prize_for_rank(r, prize_pool)
+                            - props.prize_for_rank(r, Money::default())
+                    });
+                    fixed + pool_share
+                }
+            };
+            if prize == Money::default() {
+                return None;
+            }
+            let mut winner = pt.clone();
+            winner.prize_amount = Some(prize);
+            Some(winner)
         })
-        .cloned()
         .collect::<Vec<_>>();
     winners
 }
 
-fn play_tracker_filter(contest_id: &str) -> anyhow::Result<Document> {
+pub fn play_tracker_filter(contest_id: &str) -> anyhow::Result<Document> {
     let filter = doc! {
         "contestId": contest_id,
         "$or": [
@@ -250,3 +350,220 @@ fn play_tracker_filter(contest_id: &str) -> anyhow::Result<Document> {
     };
     Ok(filter)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::contest::PrizeTier, utils::test_helper::*};
+
+    async fn insert_play_tracker(
+        db: &AppDatabase,
+        contest_id: &str,
+        user_id: u32,
+        score: i32,
+        paid: u64,
+    ) {
+        let mut play_tracker = PlayTracker::new(user_id, contest_id, 3);
+        play_tracker.paid_amount = Some(Money::new(paid, 0));
+        let ts = get_epoch_ts();
+        play_tracker.status = PlayTrackerStatus::FINISHED;
+        play_tracker.score = Some(score);
+        play_tracker.start_ts = Some(ts - 100);
+        play_tracker.finish_ts = Some(ts - 50);
+        play_tracker.updated_ts = Some(ts - 50);
+        db.insert_one(DB_NAME, COLL_PLAY_TRACKERS, &play_tracker, None)
+            .await
+            .unwrap();
+    }
+
+    /// contest and ranked play trackers where user `idx + 1` has the score & time taken at `idx`
+    fn ranked(
+        players: &[(i32, u32)],
+        tie_policy: TiePolicy,
+        winners_count: u32,
+    ) -> (Contest, Vec<PlayTracker>) {
+        let mut props = contest_props(0, 90);
+        props.tie_policy = tie_policy;
+        props.top_winners_count = Some(winners_count);
+        let contest = Contest::new(&props, 1);
+        let mut play_trackers = players
+            .iter()
+            .enumerate()
+            .map(|(idx, (score, time_taken))| {
+                let mut pt = PlayTracker::new(idx as u32 + 1, "contest", 3);
+                pt.score = Some(*score);
+                pt.time_taken = Some(*time_taken);
+                pt
+            })
+            .collect::<Vec<_>>();
+        rank_play_trackers(&mut play_trackers, tie_policy);
+        (contest, play_trackers)
+    }
+
+    fn winner_prizes(players: &[(i32, u32)], tie_policy: TiePolicy, count: u32) -> Vec<(u32, u64)> {
+        let (contest, play_trackers) = ranked(players, tie_policy, count);
+        let winners_count = get_winners_count(&contest, play_trackers.len() as u32);
+        let mut winners = get_winners(&play_trackers, winners_count, &contest, Money::default())
+            .iter()
+            .map(|pt| (pt.user_id, pt.prize_amount.unwrap().real()))
+            .collect::<Vec<_>>();
+        winners.sort();
+        winners
+    }
+
+    #[test]
+    fn test_winners_count_boundary() {
+        let players = [(3, 10), (2, 10), (1, 10), (0, 10)];
+        let policy = TiePolicy::RANK_BY_TIME;
+        // exactly the top N ranks are paid
+        assert_eq!(winner_prizes(&players, policy, 1), vec![(1, 90)]);
+        assert_eq!(winner_prizes(&players, policy, 2), vec![(1, 90), (2, 90)]);
+        // players with zero score never win
+        assert_eq!(winner_prizes(&players, policy, 4).len(), 3);
+
+        let (mut contest, _) = ranked(&players, policy, 1);
+        contest.props.prize_selection = PrizeSelection::RATIO_BASED;
+        contest.props.prize_ratio_numerator = Some(1);
+        contest.props.prize_ratio_denominator = Some(2);
+        assert_eq!(get_winners_count(&contest, 5), 2);
+        assert_eq!(get_winners_count(&contest, 4), 2);
+        assert_eq!(get_winners_count(&contest, 1), 0);
+        contest.props.prize_ratio_numerator = Some(2);
+        assert_eq!(get_winners_count(&contest, 7), 7);
+    }
+
+    #[test]
+    fn test_tie_policies() {
+        let players = [(3, 20), (3, 10), (2, 5), (0, 5)];
+        let (_, play_trackers) = ranked(&players, TiePolicy::RANK_BY_TIME, 1);
+        let ranks = play_trackers
+            .iter()
+            .map(|pt| (pt.user_id, pt.rank))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranks,
+            vec![(2, Some(1)), (1, Some(2)), (3, Some(3)), (4, None)]
+        );
+        let (_, play_trackers) = ranked(&players, TiePolicy::SHARED_RANK, 1);
+        let ranks = play_trackers.iter().map(|pt| pt.rank).collect::<Vec<_>>();
+        assert_eq!(ranks, vec![Some(1), Some(1), Some(3), None]);
+
+        assert_eq!(
+            winner_prizes(&players, TiePolicy::RANK_BY_TIME, 1),
+            vec![(2, 90)]
+        );
+        // tied players at the last paid rank are all paid
+        let prizes = winner_prizes(&players, TiePolicy::SHARED_RANK, 1);
+        assert_eq!(prizes, vec![(1, 90), (2, 90)]);
+        let prizes = winner_prizes(&players, TiePolicy::SPLIT_PRIZE, 1);
+        assert_eq!(prizes, vec![(1, 45), (2, 45)]);
+        let prizes = winner_prizes(&players, TiePolicy::SPLIT_PRIZE, 2);
+        assert_eq!(prizes, vec![(1, 90), (2, 90)]);
+        let prizes = winner_prizes(&players, TiePolicy::SPLIT_PRIZE, 3);
+        assert_eq!(prizes, vec![(1, 90), (2, 90), (3, 90)]);
+
+        // the pool share of the shared rank is split, the fixed prize is not
+        let (mut contest, play_trackers) = ranked(&players, TiePolicy::SHARED_RANK, 1);
+        contest.props.prize_table = vec![PrizeTier {
+            rank_from: 1,
+            rank_to: 1,
+            prize_value_real_money: 10,
+            prize_value_bonus_money: 0,
+            pool_percentage: Some(50),
+        }];
+        let winners = get_winners(&play_trackers, 1, &contest, Money::new(100, 0));
+        let prizes = winners
+            .iter()
+            .map(|pt| pt.prize_amount.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(prizes, vec![Money::new(35, 0), Money::new(35, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_finish_contest() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        insert_play_tracker(&db, &contest_id, 1, 3, 0).await;
+        insert_play_tracker(&db, &contest_id, 2, 1, 0).await;
+        insert_play_tracker(&db, &contest_id, 3, 0, 0).await;
+        let contest = get_contest(&db, &contest_id).await;
+
+        finish_contest(&db, &EventBus::default(), &contest).await;
+
+        let contest = get_contest(&db, &contest_id).await;
+        assert_eq!(contest.status, ContestStatus::ENDED);
+        assert_eq!(get_balance(&db, 1).await, Money::new(100, 0));
+        assert_eq!(get_balance(&db, 2).await, Money::new(0, 0));
+        let filter = doc! {"contestId": &contest_id, "status": "ENDED"};
+        let play_trackers = db
+            .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await
+            .unwrap();
+        assert_eq!(play_trackers.len(), 3);
+        let notifications = db
+            .find::<Document>(DB_NAME, COLL_NOTIFICATION_REQUESTS, None, None)
+            .await
+            .unwrap();
+        assert_eq!(notifications.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_finish_contest_prize_table() {
+        let db = test_db();
+        let mut props = contest_props(10, 0);
+        props.top_winners_count = Some(3);
+        props.prize_table = vec![
+            PrizeTier {
+                rank_from: 1,
+                rank_to: 1,
+                prize_value_real_money: 50,
+                prize_value_bonus_money: 5,
+                pool_percentage: None,
+            },
+            PrizeTier {
+                rank_from: 2,
+                rank_to: 3,
+                prize_value_real_money: 0,
+                prize_value_bonus_money: 0,
+                pool_percentage: Some(25),
+            },
+        ];
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        for user_id in 1..=4 {
+            insert_play_tracker(&db, &contest_id, user_id, 5 - user_id as i32, 10).await;
+        }
+        let contest = get_contest(&db, &contest_id).await;
+
+        finish_contest(&db, &EventBus::default(), &contest).await;
+
+        assert_eq!(get_balance(&db, 1).await, Money::new(50, 5));
+        // 25% of the 40 collected as entry fee
+        assert_eq!(get_balance(&db, 2).await, Money::new(10, 0));
+        assert_eq!(get_balance(&db, 3).await, Money::new(10, 0));
+        assert_eq!(get_balance(&db, 4).await, Money::new(0, 0));
+        let contest = db
+            .find_one::<Document>(DB_NAME, COLL_CONTESTS, None, None)
+            .await
+            .unwrap()
+            .unwrap();
+        let winners = contest.get_array("winners").unwrap();
+        assert_eq!(winners.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_finish_contest_not_enough_players() {
+        let db = test_db();
+        let mut props = contest_props(10, 100);
+        props.min_required_players = 3;
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        insert_play_tracker(&db, &contest_id, 1, 3, 10).await;
+        let contest = get_contest(&db, &contest_id).await;
+
+        finish_contest(&db, &EventBus::default(), &contest).await;
+
+        let contest = get_contest(&db, &contest_id).await;
+        assert_eq!(contest.status, ContestStatus::CANCELLED);
+        assert_eq!(get_balance(&db, 1).await, Money::new(10, 0));
+    }
+}
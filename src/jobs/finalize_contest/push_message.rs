@@ -1,15 +1,14 @@
-use mongodb::ClientSession;
 use std::collections::HashMap;
 
 use crate::{
     constants::*,
-    database::AppDatabase,
+    database::{AppDatabase, DbSession},
     models::{notification::NotificationReq, wallet::Money},
 };
 
 pub async fn create_push_for_prize_win(
     db: &AppDatabase,
-    session: &mut ClientSession,
+    session: &mut DbSession,
     user_id: u32,
     contest_title: &str,
     amount: Money,
@@ -0,0 +1,257 @@
+use futures::FutureExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::{FindOptions, ReadConcern, TransactionOptions},
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::time::interval;
+
+use crate::{
+    constants::*,
+    database::{AppDatabase, DbSession},
+    handlers::wallet::ledger::open_wallet_ledger,
+    models::*,
+    utils::get_epoch_ts,
+};
+
+/// This function periodically reconciles the wallet balances with the ledger
+pub async fn ledger_reconcile_job(db: Arc<AppDatabase>, interval_secs: u64) {
+    tracing::debug!("initializing ledger reconcile scheduler job");
+    let mut interval = interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(err) = reconcile_ledger(&db).await {
+            tracing::debug!("Error in ledger_reconcile_job => {:?}", err);
+        }
+    }
+}
+
+/// Recompute every wallet balance from the ledger and record the wallets which drifted.
+/// The drift collection always holds the result of the latest run.
+pub async fn reconcile_ledger(db: &AppDatabase) -> anyhow::Result<Vec<LedgerDrift>> {
+    open_legacy_wallets(db).await?;
+    check_unbalanced_postings(db).await?;
+    let mut ledger_balances = get_ledger_balances(db).await?;
+    let mut suspects = vec![];
+    let mut last_user_id = None;
+    loop {
+        let wallets = get_wallets_page(db, last_user_id).await?;
+        let Some(last) = wallets.last() else {
+            break;
+        };
+        last_user_id = Some(last.user_id());
+        for wallet in wallets {
+            let balance = wallet.balance();
+            let wallet_balance = (balance.real() as i64, balance.bonus() as i64);
+            let ledger_balance = ledger_balances
+                .remove(&wallet.user_id())
+                .unwrap_or_default();
+            if wallet_balance != ledger_balance {
+                suspects.push(wallet.user_id());
+            }
+        }
+    }
+    // ledger entries of users without any wallet
+    for (user_id, ledger_balance) in ledger_balances {
+        if ledger_balance != (0, 0) {
+            suspects.push(user_id);
+        }
+    }
+    suspects.sort();
+    // the ledger and the wallets are not read from one snapshot,
+    // so a wallet updated in between is read again with its ledger before it is recorded
+    let ts = get_epoch_ts();
+    let mut drifts = vec![];
+    for user_id in suspects {
+        let options = TransactionOptions::builder()
+            .read_concern(Some(ReadConcern::snapshot()))
+            .build();
+        let drift = db
+            .execute_transaction(None, Some(options), |db, session| {
+                get_drift(db, session, user_id, ts).boxed()
+            })
+            .await?;
+        if let Some(drift) = drift {
+            tracing::warn!("wallet balance drifted from ledger: {:?}", drift);
+            drifts.push(drift);
+        }
+    }
+    db.delete_many(DB_NAME, COLL_LEDGER_DRIFTS, doc! {}, None)
+        .await?;
+    if !drifts.is_empty() {
+        db.insert_many(DB_NAME, COLL_LEDGER_DRIFTS, &drifts, None)
+            .await?;
+    }
+    Ok(drifts)
+}
+
+/// Wallets created before the ledger get an opening balance posted against the house account
+async fn open_legacy_wallets(db: &AppDatabase) -> anyhow::Result<()> {
+    let filter = doc! {"ledgerOpened": {"$ne": true}};
+    let wallets = db
+        .find::<Wallet>(DB_NAME, COLL_WALLETS, Some(filter), None)
+        .await?;
+    for wallet in wallets {
+        let user_id = wallet.user_id();
+        db.execute_transaction(None, None, |db, session| {
+            open_wallet_ledger(db, session, user_id).boxed()
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Wallets ordered by user id, starting after the given user id
+async fn get_wallets_page(
+    db: &AppDatabase,
+    after_user_id: Option<u32>,
+) -> anyhow::Result<Vec<Wallet>> {
+    let filter = after_user_id.map(|user_id| doc! {"userId": {"$gt": user_id}});
+    let options = FindOptions::builder()
+        .sort(Some(doc! {"userId": 1}))
+        .limit(Some(LEDGER_RECONCILE_PAGE_SIZE))
+        .build();
+    db.find::<Wallet>(DB_NAME, COLL_WALLETS, filter, Some(options))
+        .await
+}
+
+/// Compare the wallet of the user with its ledger balance within the same transaction
+async fn get_drift(
+    db: &AppDatabase,
+    session: &mut DbSession,
+    user_id: u32,
+    ts: u64,
+) -> anyhow::Result<Option<LedgerDrift>> {
+    let filter = doc! {"userId": user_id};
+    let wallet = db
+        .find_one_with_session::<Wallet>(session, DB_NAME, COLL_WALLETS, Some(filter), None)
+        .await?;
+    let pipeline = ledger_balances_pipeline(Some(user_id))?;
+    let docs = db
+        .aggregate_with_session(session, DB_NAME, COLL_LEDGER_ENTRIES, pipeline, None)
+        .await?;
+    let (ledger_real, ledger_bonus) = to_ledger_balances(docs)?
+        .remove(&user_id)
+        .unwrap_or_default();
+    let balance = wallet.map(|wallet| wallet.balance()).unwrap_or_default();
+    let wallet_real = balance.real() as i64;
+    let wallet_bonus = balance.bonus() as i64;
+    if wallet_real == ledger_real && wallet_bonus == ledger_bonus {
+        return Ok(None);
+    }
+    let drift = LedgerDrift {
+        user_id,
+        wallet_real,
+        wallet_bonus,
+        ledger_real,
+        ledger_bonus,
+        detected_ts: ts,
+    };
+    Ok(Some(drift))
+}
+
+/// Log the postings whose entries do not add up to zero
+async fn check_unbalanced_postings(db: &AppDatabase) -> anyhow::Result<()> {
+    let pipeline = vec![
+        doc! {"$group": {"_id": "$postingId", "real": {"$sum": "$real"}, "bonus": {"$sum": "$bonus"}}},
+        doc! {"$match": {"$or": [{"real": {"$ne": 0}}, {"bonus": {"$ne": 0}}]}},
+    ];
+    let postings = db
+        .aggregate(DB_NAME, COLL_LEDGER_ENTRIES, pipeline, None)
+        .await?;
+    for posting in postings {
+        tracing::warn!("unbalanced ledger posting: {:?}", posting);
+    }
+    Ok(())
+}
+
+/// Sum of the user account entries grouped by user
+async fn get_ledger_balances(db: &AppDatabase) -> anyhow::Result<HashMap<u32, (i64, i64)>> {
+    let pipeline = ledger_balances_pipeline(None)?;
+    let docs = db
+        .aggregate(DB_NAME, COLL_LEDGER_ENTRIES, pipeline, None)
+        .await?;
+    to_ledger_balances(docs)
+}
+
+fn ledger_balances_pipeline(user_id: Option<u32>) -> anyhow::Result<Vec<Document>> {
+    let mut filter = doc! {"account": LedgerAccount::User.to_bson()?};
+    if let Some(user_id) = user_id {
+        filter.insert("userId", user_id);
+    }
+    let pipeline = vec![
+        doc! {"$match": filter},
+        doc! {"$group": {"_id": "$userId", "real": {"$sum": "$real"}, "bonus": {"$sum": "$bonus"}}},
+    ];
+    Ok(pipeline)
+}
+
+fn to_ledger_balances(docs: Vec<Document>) -> anyhow::Result<HashMap<u32, (i64, i64)>> {
+    let mut balances = HashMap::new();
+    for doc in docs {
+        let user_id = get_number(&doc, "_id")? as u32;
+        let real = get_number(&doc, "real")?;
+        let bonus = get_number(&doc, "bonus")?;
+        balances.insert(user_id, (real, bonus));
+    }
+    Ok(balances)
+}
+
+fn get_number(doc: &Document, key: &str) -> anyhow::Result<i64> {
+    match doc.get(key) {
+        Some(Bson::Int32(n)) => Ok(*n as i64),
+        Some(Bson::Int64(n)) => Ok(*n),
+        Some(Bson::Double(n)) => Ok(*n as i64),
+        _ => Err(anyhow::anyhow!("{} is not a number in {:?}", key, doc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_reconcile_ledger() {
+        let db = test_db();
+        add_balance(&db, 1, 100, 20).await;
+        add_withdrawable_balance(&db, 2, 50).await;
+        let drifts = reconcile_ledger(&db).await.unwrap();
+        assert!(drifts.is_empty());
+
+        // wallet created before the ledger is opened with its balance
+        let wallet = doc! {"userId": 3, "balance": {"real": 10, "bonus": 5}};
+        db.insert_one(DB_NAME, COLL_WALLETS, &wallet, None)
+            .await
+            .unwrap();
+        let drifts = reconcile_ledger(&db).await.unwrap();
+        assert!(drifts.is_empty());
+
+        // legacy wallet updated before the job opens it
+        let wallet = doc! {"userId": 4, "balance": {"real": 10, "bonus": 0}};
+        db.insert_one(DB_NAME, COLL_WALLETS, &wallet, None)
+            .await
+            .unwrap();
+        add_balance(&db, 4, 5, 0).await;
+        let drifts = reconcile_ledger(&db).await.unwrap();
+        assert!(drifts.is_empty());
+
+        // balance changed outside of the ledger
+        let filter = doc! {"userId": 1};
+        let update = doc! {"$inc": {"balance.real": 7}};
+        db.update_one(DB_NAME, COLL_WALLETS, filter, update, None)
+            .await
+            .unwrap();
+        let drifts = reconcile_ledger(&db).await.unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].user_id, 1);
+        assert_eq!(drifts[0].wallet_real, 107);
+        assert_eq!(drifts[0].ledger_real, 100);
+        assert_eq!(drifts[0].ledger_bonus, 20);
+        let saved = db
+            .find::<LedgerDrift>(DB_NAME, COLL_LEDGER_DRIFTS, None, None)
+            .await
+            .unwrap();
+        assert_eq!(saved, drifts);
+    }
+}
@@ -1,33 +1,89 @@
 use std::sync::Arc;
 
 use self::{
-    cleanup::cleanup_job, finalize_contest::finalize_contest_job, notification::notification_job,
+    cleanup::cleanup_job, contest_scheduler::contest_scheduler_job,
+    finalize_contest::finalize_contest_job, ledger_reconcile::ledger_reconcile_job,
+    notification::notification_job, payout::payout_job, play_tracker_sweep::play_tracker_sweep_job,
+};
+use crate::{
+    config::JobsConfig, database::AppDatabase, events::EventBus,
+    jobs::notification::google_auth_token::GoogleAuthToken, payment::PayoutProvider,
 };
-use crate::database::AppDatabase;
 
 pub mod cleanup;
+pub mod contest_scheduler;
 pub mod finalize_contest;
+pub mod ledger_reconcile;
 pub mod notification;
+pub mod payout;
+pub mod play_tracker_sweep;
 
-pub fn spawn_all_jobs(db_client: Arc<AppDatabase>) {
+pub fn spawn_all_jobs(
+    db_client: Arc<AppDatabase>,
+    config: &JobsConfig,
+    payout_provider: Arc<dyn PayoutProvider>,
+    google_auth_token: GoogleAuthToken,
+    events: Arc<EventBus>,
+) {
     {
         let db_client = db_client.clone();
+        let interval = config.cleanup_interval;
         // spawn job to cleanup old otp & used tokens
-        tokio::spawn(async {
-            cleanup_job(db_client).await;
+        tokio::spawn(async move {
+            cleanup_job(db_client, interval).await;
         });
     }
 
     {
         let db_client = db_client.clone();
+        let events = events.clone();
+        let interval = config.notification_interval;
         // spawn job to periodically send out notifications
-        tokio::spawn(async {
-            notification_job(db_client).await;
+        tokio::spawn(async move {
+            notification_job(db_client, events, google_auth_token, interval).await;
+        });
+    }
+
+    {
+        let db_client = db_client.clone();
+        let interval = config.payout_interval;
+        // spawn job to pay out the approved withdrawals
+        tokio::spawn(async move {
+            payout_job(db_client, payout_provider, interval).await;
+        });
+    }
+
+    {
+        let db_client = db_client.clone();
+        let interval = config.ledger_reconcile_interval;
+        // spawn job to reconcile the wallet balances with the ledger
+        tokio::spawn(async move {
+            ledger_reconcile_job(db_client, interval).await;
+        });
+    }
+
+    {
+        let db_client = db_client.clone();
+        let interval = config.play_tracker_sweep_interval;
+        // spawn job to finish the plays which ran over the contest time budget
+        tokio::spawn(async move {
+            play_tracker_sweep_job(db_client, interval).await;
+        });
+    }
+
+    {
+        let db_client = db_client.clone();
+        let events = events.clone();
+        let interval = config.contest_scheduler_interval;
+        // spawn job to create the scheduled contests and activate them
+        tokio::spawn(async move {
+            contest_scheduler_job(db_client, events, interval).await;
         });
     }
 
-    tokio::spawn(async {
+    let interval = config.finalize_contest_interval;
+    tokio::spawn(async move {
         // spawn job which handle all tasks when a content is finished
-        finalize_contest_job(db_client).await;
+        finalize_contest_job(db_client, events, interval).await;
     });
 }
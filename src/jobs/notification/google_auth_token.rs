@@ -4,6 +4,53 @@ use serde::{Deserialize, Serialize};
 
 use crate::{constants::*, utils::get_epoch_ts};
 
+/// Firebase service account used for sending the push messages.
+/// The project is selected by the credentials, so staging and prod only differ in config.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FirebaseCredentials {
+    pub project_id: String,
+    pub client_email: String,
+    pub private_key: String,
+}
+
+impl FirebaseCredentials {
+    /// Load the service account JSON file mentioned in `FIREBASE_SERVICE_ACCOUNT_FILE`,
+    /// otherwise read `FIREBASE_PROJECT_ID`, `FIREBASE_CLIENT_EMAIL` and `FIREBASE_PRIVATE_KEY`
+    pub fn from_env() -> anyhow::Result<Self> {
+        if let Ok(file) = std::env::var("FIREBASE_SERVICE_ACCOUNT_FILE") {
+            let json = std::fs::read_to_string(&file).map_err(|e| {
+                anyhow::anyhow!("Not able to read firebase service account file {file}: {e}")
+            })?;
+            return Self::from_json(&json);
+        }
+        let read = |key: &str| {
+            std::env::var(key).map_err(|_| {
+                anyhow::anyhow!(
+                    "{key} not found in environment, set FIREBASE_SERVICE_ACCOUNT_FILE or \
+                     FIREBASE_PROJECT_ID, FIREBASE_CLIENT_EMAIL and FIREBASE_PRIVATE_KEY"
+                )
+            })
+        };
+        let credentials = Self {
+            project_id: read("FIREBASE_PROJECT_ID")?,
+            client_email: read("FIREBASE_CLIENT_EMAIL")?,
+            // the key is usually kept in a single line with escaped new lines
+            private_key: read("FIREBASE_PRIVATE_KEY")?.replace("\\n", "\n"),
+        };
+        Ok(credentials)
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("Not valid firebase service account json: {e}"))
+    }
+
+    /// FCM endpoint of the project for sending the messages
+    pub fn fcm_endpoint(&self) -> String {
+        format!("{FCM_API_URL}/projects/{}/messages:send", self.project_id)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GoogleTokenClaims {
     iss: String,
@@ -14,10 +61,10 @@ struct GoogleTokenClaims {
 }
 
 impl GoogleTokenClaims {
-    fn new() -> Self {
+    fn new(client_email: &str) -> Self {
         let ts = get_epoch_ts();
         Self {
-            iss: FIREBASE_SERVICE_CLIENT_EMAIL.to_string(),
+            iss: client_email.to_string(),
             iat: ts,
             exp: ts + 3600,
             aud: GOOGLE_TOKEN_URL.to_string(),
@@ -26,11 +73,11 @@ impl GoogleTokenClaims {
     }
 }
 
-#[derive(Default)]
 pub struct GoogleAuthToken {
+    credentials: FirebaseCredentials,
     access_token: Option<String>,
     valid_till: Option<u64>,
-    signing_key: Option<EncodingKey>,
+    signing_key: EncodingKey,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,6 +87,27 @@ struct GoogleTokenResponse {
 }
 
 impl GoogleAuthToken {
+    /// Validate the private key of the credentials, it is used for signing the token requests
+    pub fn new(credentials: FirebaseCredentials) -> anyhow::Result<Self> {
+        let signing_key = EncodingKey::from_rsa_pem(credentials.private_key.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Not valid firebase private key: {e}"))?;
+        let token = Self {
+            credentials,
+            access_token: None,
+            valid_till: None,
+            signing_key,
+        };
+        Ok(token)
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::new(FirebaseCredentials::from_env()?)
+    }
+
+    pub fn fcm_endpoint(&self) -> String {
+        self.credentials.fcm_endpoint()
+    }
+
     pub async fn get_access_token(&mut self) -> anyhow::Result<&str> {
         if self.is_new_token_required() {
             self.new_access_token().await?;
@@ -47,22 +115,10 @@ impl GoogleAuthToken {
         Ok(self.access_token.as_ref().unwrap())
     }
 
-    fn get_signing_key(&mut self) -> anyhow::Result<&EncodingKey> {
-        if self.signing_key.is_none() {
-            let key = EncodingKey::from_rsa_pem(FIREBASE_SERVICE_PRIVATE_KEY.as_bytes())?;
-            self.signing_key = Some(key);
-        }
-        let signing_key = self
-            .signing_key
-            .as_ref()
-            .ok_or(anyhow::anyhow!("signing_key not found"))?;
-        Ok(signing_key)
-    }
-
     fn is_new_token_required(&self) -> bool {
         if let Some(valid_till) = self.valid_till {
             let ts = get_epoch_ts();
-            return ts <= valid_till;
+            return ts > valid_till;
         }
         self.access_token.is_none() || self.valid_till.is_none()
     }
@@ -94,11 +150,35 @@ impl GoogleAuthToken {
         Ok(())
     }
 
-    fn new_jwt(&mut self) -> anyhow::Result<String> {
-        let claims = GoogleTokenClaims::new();
-        let key = self.get_signing_key()?;
+    fn new_jwt(&self) -> anyhow::Result<String> {
+        let claims = GoogleTokenClaims::new(&self.credentials.client_email);
         let header = Header::new(Algorithm::RS256);
-        let jwt = encode(&header, &claims, &key)?;
+        let jwt = encode(&header, &claims, &self.signing_key)?;
         Ok(jwt)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_firebase_credentials() {
+        let json = r#"{
+            "type": "service_account",
+            "project_id": "trailsbuddy-staging",
+            "client_email": "firebase-adminsdk@trailsbuddy-staging.iam.gserviceaccount.com",
+            "private_key": "not a key"
+        }"#;
+        let credentials = FirebaseCredentials::from_json(json).unwrap();
+        assert_eq!(
+            credentials.fcm_endpoint(),
+            "https://fcm.googleapis.com/v1/projects/trailsbuddy-staging/messages:send"
+        );
+        let err = GoogleAuthToken::new(credentials).err().unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("Not valid firebase private key"));
+        assert!(FirebaseCredentials::from_json(r#"{"project_id": "p"}"#).is_err());
+    }
+}
@@ -6,29 +6,37 @@ use super::notification_req::{process_new_req, process_ready_req};
 use crate::{
     constants::*,
     database::AppDatabase,
+    events::EventBus,
     jobs::notification::google_auth_token::GoogleAuthToken,
     models::notification::{NotificationReq, NotificationReqStatus},
 };
 
 /// This function periodically calls `handle_notification` function
 /// to send out the push notifications
-pub async fn notification_job(db: Arc<AppDatabase>) {
+pub async fn notification_job(
+    db: Arc<AppDatabase>,
+    events: Arc<EventBus>,
+    mut google_auth_token: GoogleAuthToken,
+    interval_secs: u64,
+) {
     tracing::debug!("initializing notification scheduler job");
-    // NOTIFICATION_JOB_INTERVAL is mentioned in seconds
-    let mut interval = interval(Duration::from_secs(NOTIFICATION_JOB_INTERVAL));
-    let mut google_auth_token = GoogleAuthToken::default();
+    let mut interval = interval(Duration::from_secs(interval_secs));
     loop {
         interval.tick().await;
-        handle_notification(&db, &mut google_auth_token).await;
+        handle_notification(&db, &events, &mut google_auth_token).await;
     }
 }
 
 /// This function processes the notification requests
-async fn handle_notification(db: &Arc<AppDatabase>, google_auth_token: &mut GoogleAuthToken) {
+async fn handle_notification(
+    db: &Arc<AppDatabase>,
+    events: &EventBus,
+    google_auth_token: &mut GoogleAuthToken,
+) {
     tracing::debug!("running handle_notification scheduler job");
     tokio::join!(
-        process_new_batch(&db),
-        process_ready_batch(&db, google_auth_token)
+        process_new_batch(db),
+        process_ready_batch(db, events, google_auth_token)
     );
 }
 
@@ -61,7 +69,11 @@ async fn process_new_batch(db: &Arc<AppDatabase>) {
 }
 
 /// This function processes the notifications which are in `READY_TO_SEND` status
-async fn process_ready_batch(db: &Arc<AppDatabase>, google_auth_token: &mut GoogleAuthToken) {
+async fn process_ready_batch(
+    db: &Arc<AppDatabase>,
+    events: &EventBus,
+    google_auth_token: &mut GoogleAuthToken,
+) {
     let Ok(status) = NotificationReqStatus::READY_TO_SEND.to_bson() else {
         tracing::debug!("not able to convert NotificationReqStatus to bson");
         return;
@@ -84,6 +96,6 @@ async fn process_ready_batch(db: &Arc<AppDatabase>, google_auth_token: &mut Goog
             vec![]
         });
     for req in requests {
-        process_ready_req(db, &req, google_auth_token).await;
+        process_ready_req(db, events, &req, google_auth_token).await;
     }
 }
@@ -5,6 +5,7 @@ use super::{google_auth_token::GoogleAuthToken, push_message::send_push_message}
 use crate::{
     constants::*,
     database::AppDatabase,
+    events::{EventBus, NotificationEvent},
     models::{
         notification::{
             NotificationContent, NotificationReq, NotificationReqStatus, NotificationType,
@@ -80,6 +81,7 @@ pub async fn process_new_req(db: &Arc<AppDatabase>, req: &NotificationReq) {
 /// This function processes single notification request which is in READY_TO_SEND status
 pub async fn process_ready_req(
     db: &Arc<AppDatabase>,
+    events: &EventBus,
     req: &NotificationReq,
     google_auth_token: &mut GoogleAuthToken,
 ) {
@@ -118,7 +120,7 @@ pub async fn process_ready_req(
         return update_error(db, req, "not able to send_push_message").await;
     }
     // update the request to completed status
-    update_completed(db, req, final_message).await;
+    update_completed(db, events, req, final_message).await;
 }
 
 pub async fn update_ready(
@@ -158,7 +160,13 @@ pub async fn update_ready(
     }
 }
 
-pub async fn update_completed(db: &Arc<AppDatabase>, req: &NotificationReq, msg: &str) {
+/// Save the in-app notification, publish it to the connected clients and mark the request SENT
+pub async fn update_completed(
+    db: &Arc<AppDatabase>,
+    events: &EventBus,
+    req: &NotificationReq,
+    msg: &str,
+) {
     let Some(id) = req._id.as_ref() else {
         return update_error(db, req, "_id not present in req").await;
     };
@@ -166,13 +174,20 @@ pub async fn update_completed(db: &Arc<AppDatabase>, req: &NotificationReq, msg:
         return update_error(db, req, "not able to parse _id").await;
     };
     let notification = Notifications::new_push(req.user_id, &req.event_name, msg);
-    if let Err(e) = db
+    let notification_id = match db
         .insert_one::<Notifications>(DB_NAME, COLL_NOTIFICATIONS, &notification, None)
         .await
     {
-        tracing::debug!("{:?}", e);
-        return update_error(db, req, "not able to insert into notifications").await;
-    }
+        Ok(id) => id,
+        Err(e) => {
+            tracing::debug!("{:?}", e);
+            return update_error(db, req, "not able to insert into notifications").await;
+        }
+    };
+    events.publish_notification(NotificationEvent {
+        user_id: req.user_id,
+        notification: notification.with_id(notification_id),
+    });
     let ts = get_epoch_ts() as i64;
     let filter = doc! {"_id": oid};
     let Ok(status) = NotificationReqStatus::SENT.to_bson() else {
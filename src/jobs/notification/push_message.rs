@@ -78,6 +78,7 @@ pub async fn send_push_message(
     device: &str,
     google_auth_token: &mut GoogleAuthToken,
 ) -> anyhow::Result<bool> {
+    let endpoint = google_auth_token.fcm_endpoint();
     let access_token = google_auth_token.get_access_token().await?;
     let bearer_token = format!("Bearer {}", access_token);
     let payload = PushPayload::new(msg, device);
@@ -86,7 +87,7 @@ pub async fn send_push_message(
     headers.insert(CONTENT_TYPE, "application/json".parse()?);
     let client = reqwest::Client::new();
     let res = client
-        .post(FCM_ENDPOINT)
+        .post(endpoint)
         .headers(headers)
         .json(&payload)
         .send()
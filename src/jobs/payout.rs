@@ -0,0 +1,170 @@
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+};
+use std::{sync::Arc, time::Duration};
+use tokio::time::interval;
+
+use crate::{
+    constants::*,
+    database::AppDatabase,
+    handlers::wallet::withdrawals::reverse_withdrawal,
+    models::*,
+    payment::{PayoutProvider, PayoutRequest, PayoutResult},
+    utils::{get_epoch_ts, parse_object_id},
+};
+
+/// This function periodically pays out the approved withdrawals
+pub async fn payout_job(
+    db: Arc<AppDatabase>,
+    provider: Arc<dyn PayoutProvider>,
+    interval_secs: u64,
+) {
+    tracing::debug!("initializing payout scheduler job");
+    let mut interval = interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(err) = process_payouts(&db, provider.as_ref()).await {
+            tracing::debug!("Error in payout_job => {:?}", err);
+        }
+    }
+}
+
+/// Pay out all approved withdrawals one by one.
+/// A withdrawal is moved to PROCESSING before calling the provider so that it is never picked twice.
+/// The withdrawal is reversed only when the provider rejects the payout, a payout of unknown
+/// outcome stays in PROCESSING and is sent again with the same idempotency key after
+/// `PAYOUT_RECHECK_SECS`, which returns the state of the original payout.
+pub async fn process_payouts(
+    db: &AppDatabase,
+    provider: &dyn PayoutProvider,
+) -> anyhow::Result<()> {
+    let recheck_ts = get_epoch_ts().saturating_sub(PAYOUT_RECHECK_SECS) as i64;
+    let stale_filter = doc! {
+        "transactionType": WalltetTransactionType::Withdraw.to_bson()?,
+        "status": WalletTransactionStatus::Processing.to_bson()?,
+        "updatedTs": {"$lt": recheck_ts}
+    };
+    while let Some(withdrawal) = claim_next_withdrawal(db, stale_filter.clone()).await? {
+        settle_withdrawal(db, provider, &withdrawal).await;
+    }
+    let filter = doc! {
+        "transactionType": WalltetTransactionType::Withdraw.to_bson()?,
+        "status": WalletTransactionStatus::Approved.to_bson()?
+    };
+    while let Some(withdrawal) = claim_next_withdrawal(db, filter.clone()).await? {
+        settle_withdrawal(db, provider, &withdrawal).await;
+    }
+    Ok(())
+}
+
+/// A withdrawal which could not be settled stays in PROCESSING and is picked again by the recheck
+async fn settle_withdrawal(
+    db: &AppDatabase,
+    provider: &dyn PayoutProvider,
+    withdrawal: &WalletTransaction,
+) {
+    if let Err(err) = pay_withdrawal(db, provider, withdrawal).await {
+        tracing::debug!("Error in payout of {:?} => {:?}", withdrawal.id(), err);
+    }
+}
+
+async fn pay_withdrawal(
+    db: &AppDatabase,
+    provider: &dyn PayoutProvider,
+    withdrawal: &WalletTransaction,
+) -> anyhow::Result<()> {
+    let Some(request) = payout_request(withdrawal) else {
+        tracing::debug!("invalid withdrawal for payout: {:?}", withdrawal);
+        let reason = "invalid withdrawal for payout";
+        let user_id = withdrawal.user_id();
+        return fail_withdrawal(db, withdrawal, reason, user_id).await;
+    };
+    match provider.payout(&request).await {
+        PayoutResult::Paid(reference_id) => complete_withdrawal(db, &request, &reference_id).await,
+        PayoutResult::Rejected(reason) => {
+            tracing::debug!("payout rejected for {}: {}", request.transaction_id, reason);
+            let reason = format!("payout failed: {}", reason);
+            fail_withdrawal(db, withdrawal, &reason, request.user_id).await
+        }
+        PayoutResult::Unknown(reason) => {
+            tracing::debug!("payout unknown for {}: {}", request.transaction_id, reason);
+            Ok(())
+        }
+    }
+}
+
+async fn fail_withdrawal(
+    db: &AppDatabase,
+    withdrawal: &WalletTransaction,
+    reason: &str,
+    updated_by: u32,
+) -> anyhow::Result<()> {
+    reverse_withdrawal(
+        db,
+        withdrawal,
+        WalletTransactionStatus::Processing,
+        WalletTransactionStatus::Error,
+        reason,
+        updated_by,
+    )
+    .await
+}
+
+/// Move the first withdrawal matching the filter to PROCESSING and stamp it with the current time
+async fn claim_next_withdrawal(
+    db: &AppDatabase,
+    filter: Document,
+) -> anyhow::Result<Option<WalletTransaction>> {
+    let update = doc! {
+        "$set": {
+            "status": WalletTransactionStatus::Processing.to_bson()?,
+            "updatedTs": get_epoch_ts() as i64
+        }
+    };
+    let options = FindOneAndUpdateOptions::builder()
+        .sort(Some(doc! {"_id": 1}))
+        .return_document(Some(ReturnDocument::After))
+        .build();
+    db.find_one_and_update::<WalletTransaction>(
+        DB_NAME,
+        COLL_WALLET_TRANSACTIONS,
+        filter,
+        update,
+        Some(options),
+    )
+    .await
+}
+
+fn payout_request(withdrawal: &WalletTransaction) -> Option<PayoutRequest> {
+    let request = PayoutRequest {
+        transaction_id: withdrawal.id()?.to_string(),
+        user_id: withdrawal.user_id(),
+        amount: withdrawal.amount().real(),
+        upi_id: withdrawal.receiver_upi_id()?.to_string(),
+    };
+    Some(request)
+}
+
+async fn complete_withdrawal(
+    db: &AppDatabase,
+    request: &PayoutRequest,
+    reference_id: &str,
+) -> anyhow::Result<()> {
+    let transaction_id = parse_object_id(&request.transaction_id, "")
+        .map_err(|_| anyhow::anyhow!("invalid transaction id {}", request.transaction_id))?;
+    let filter = doc! {
+        "_id": transaction_id,
+        "status": WalletTransactionStatus::Processing.to_bson()?
+    };
+    let update = doc! {
+        "$set": {
+            "status": WalletTransactionStatus::Completed.to_bson()?,
+            "trackingId": reference_id,
+            "updatedTs": get_epoch_ts() as i64
+        }
+    };
+    db.update_one(DB_NAME, COLL_WALLET_TRANSACTIONS, filter, update, None)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use mongodb::bson::doc;
+use std::{sync::Arc, time::Duration};
+use tokio::time::interval;
+
+use crate::{constants::*, database::AppDatabase, models::*, utils::get_epoch_ts};
+
+/// This function periodically finishes the plays which ran over the contest time budget
+pub async fn play_tracker_sweep_job(db: Arc<AppDatabase>, interval_secs: u64) {
+    tracing::debug!("initializing play tracker sweep scheduler job");
+    let mut interval = interval(Duration::from_secs(interval_secs));
+    loop {
+        interval.tick().await;
+        if let Err(err) = finish_overrun_play_trackers(&db).await {
+            tracing::debug!("Error in play_tracker_sweep_job => {:?}", err);
+        }
+    }
+}
+
+/// Move the STARTED play trackers past their deadline to FINISHED with the answers recorded so far.
+/// The finish time is set to the deadline so that an abandoned play gets no extra time.
+pub async fn finish_overrun_play_trackers(db: &AppDatabase) -> anyhow::Result<u64> {
+    let ts = get_epoch_ts() as i64;
+    let filter = doc! {
+        "status": PlayTrackerStatus::STARTED.to_bson()?,
+        "deadlineTs": {"$lt": ts}
+    };
+    let play_trackers = db
+        .find::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+        .await?;
+    let mut finished = 0;
+    for pt in play_trackers {
+        let Some(deadline_ts) = pt.deadline_ts else {
+            continue;
+        };
+        let filter = doc! {
+            "contestId": &pt.contest_id,
+            "userId": pt.user_id,
+            "status": PlayTrackerStatus::STARTED.to_bson()?
+        };
+        let update = doc! {
+            "$set": {
+                "status": PlayTrackerStatus::FINISHED.to_bson()?,
+                "finishTs": deadline_ts as i64,
+                "updatedTs": ts
+            },
+            "$unset": {"currentQuestion": ""}
+        };
+        let result = db
+            .update_one(DB_NAME, COLL_PLAY_TRACKERS, filter, update, None)
+            .await?;
+        finished += result.modified_count;
+    }
+    if finished > 0 {
+        tracing::debug!("finished {} overrun play trackers", finished);
+    }
+    Ok(finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::test_helper::*;
+
+    #[tokio::test]
+    async fn test_finish_overrun_play_trackers() {
+        let db = test_db();
+        let props = contest_props(0, 100);
+        let contest_id = insert_contest(&db, &props, ContestStatus::ACTIVE, 3).await;
+        for user_id in [1, 2] {
+            let uri = format!("/api/v1/playTracker?contestId={contest_id}");
+            send_request(&db, Method::GET, &uri, user_id, None).await;
+            let body = json!({"contestId": &contest_id});
+            let uri = "/api/v1/playTracker/start";
+            let (status, res) = send_request(&db, Method::POST, uri, user_id, Some(body)).await;
+            assert_eq!(status, StatusCode::OK);
+            let start_ts = res["data"]["startTs"].as_u64().unwrap();
+            assert_eq!(res["data"]["deadlineTs"], start_ts + 600);
+        }
+        // user 1 ran over the time budget
+        let deadline_ts = get_epoch_ts() as i64 - 10;
+        let filter = doc! {"contestId": &contest_id, "userId": 1};
+        let update = doc! {"$set": {"deadlineTs": deadline_ts}};
+        db.update_one(DB_NAME, COLL_PLAY_TRACKERS, filter.clone(), update, None)
+            .await
+            .unwrap();
+        let uri = format!("/api/v1/playTracker/getNextQues?contestId={contest_id}");
+        let (status, _) = send_request(&db, Method::GET, &uri, 1, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert_eq!(finish_overrun_play_trackers(&db).await.unwrap(), 1);
+        let play_tracker = db
+            .find_one::<PlayTracker>(DB_NAME, COLL_PLAY_TRACKERS, Some(filter), None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(play_tracker.status, PlayTrackerStatus::FINISHED);
+        assert_eq!(play_tracker.finish_ts, Some(deadline_ts as u64));
+        assert!(play_tracker.current_question.is_none());
+        assert_eq!(finish_overrun_play_trackers(&db).await.unwrap(), 0);
+    }
+}
@@ -1,8 +1,8 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use axum::{
     async_trait,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     headers::{authorization::Bearer, Authorization},
     http::request::Parts,
     RequestPartsExt, TypedHeader,
@@ -14,52 +14,146 @@ use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    config::JwtConfig,
+    constants::*,
     database::AppDatabase,
     handlers::user::otp::get_admin_user_by_id,
+    models::{AdminPermission, AdminRole},
     utils::{get_epoch_ts, AppError},
 };
 
+static JWT_CONFIG: OnceLock<JwtConfig> = OnceLock::new();
+
 lazy_static! {
-    pub static ref JWT_KEYS: JwtKeys = JwtKeys::new();
+    pub static ref JWT_KEYS: JwtKeys = JwtKeys::new(JWT_CONFIG.get_or_init(JwtConfig::default));
 }
 
-pub struct JwtKeys {
-    pub encoding: EncodingKey,
-    pub decoding: DecodingKey,
+/// Set the jwt config of the app, it should be called at startup before any token is used
+pub fn init_jwt_keys(config: &JwtConfig) -> anyhow::Result<()> {
+    JWT_CONFIG
+        .set(config.clone())
+        .map_err(|_| anyhow::anyhow!("jwt keys are already initialized"))
 }
 
-impl JwtKeys {
-    fn new() -> Self {
-        let secret = std::env::var("JWT_SECRET_KEY").unwrap_or("my_secret".to_string());
+/// Role of the token holder, the app user and the admin tokens have different audiences
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum TokenRole {
+    USER,
+    ADMIN,
+}
+
+impl TokenRole {
+    fn audience(&self) -> &'static str {
+        match self {
+            Self::USER => JWT_AUD_APP,
+            Self::ADMIN => JWT_AUD_ADMIN,
+        }
+    }
+}
+
+struct SigningKey {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl SigningKey {
+    fn new(secret: &str) -> Self {
         Self {
             encoding: EncodingKey::from_secret(secret.as_bytes()),
             decoding: DecodingKey::from_secret(secret.as_bytes()),
         }
     }
+}
+
+pub struct JwtKeys {
+    app: SigningKey,
+    admin: SigningKey,
+    expiry: u64,
+    refresh_token_expiry: u64,
+    admin_expiry: u64,
+}
+
+impl JwtKeys {
+    fn new(config: &JwtConfig) -> Self {
+        let admin_secret = config
+            .admin_secret_key
+            .as_deref()
+            .unwrap_or(&config.secret_key);
+        Self {
+            app: SigningKey::new(&config.secret_key),
+            admin: SigningKey::new(admin_secret),
+            expiry: config.expiry,
+            refresh_token_expiry: config.refresh_token_expiry,
+            admin_expiry: config.admin_expiry,
+        }
+    }
+
+    fn key(&self, role: TokenRole) -> &SigningKey {
+        match role {
+            TokenRole::USER => &self.app,
+            TokenRole::ADMIN => &self.admin,
+        }
+    }
 
     pub fn generate_token(&self, id: u32, name: Option<String>) -> JwtResult<String> {
-        let jwt_expiry = std::env::var("JWT_EXPIRY").unwrap_or_default();
-        let jwt_expiry = jwt_expiry.parse::<usize>().unwrap_or(3600);
-        let jwt_expiry = get_epoch_ts() as usize + jwt_expiry;
-        self.sign(id, name, jwt_expiry)
+        self.sign(id, name, TokenRole::USER, self.expiry)
     }
 
     pub fn generate_refresh_token(&self, id: u32, name: Option<String>) -> JwtResult<String> {
-        let jwt_expiry = std::env::var("REFRESH_TOKEN_EXPIRY").unwrap_or_default();
-        let jwt_expiry = jwt_expiry.parse::<usize>().unwrap_or(24 * 3600);
-        let jwt_expiry = get_epoch_ts() as usize + jwt_expiry;
-        self.sign(id, name, jwt_expiry)
+        self.sign(id, name, TokenRole::USER, self.refresh_token_expiry)
     }
 
-    fn sign(&self, id: u32, name: Option<String>, exp: usize) -> JwtResult<String> {
-        let claims = JwtClaims::new(id, name, exp);
-        encode(&Header::default(), &claims, &self.encoding)
+    pub fn generate_admin_token(&self, id: u32, name: Option<String>) -> JwtResult<String> {
+        self.sign(id, name, TokenRole::ADMIN, self.admin_expiry)
     }
 
-    pub fn extract_claims(&self, token: &str) -> Option<JwtClaims> {
+    fn sign(
+        &self,
+        id: u32,
+        name: Option<String>,
+        role: TokenRole,
+        expiry: u64,
+    ) -> JwtResult<String> {
+        let exp = (get_epoch_ts() + expiry) as usize;
+        let claims = JwtClaims::new(id, name, role, exp);
+        encode(&Header::default(), &claims, &self.key(role).encoding)
+    }
+
+    /// Decode the token, it is valid only for the audience of the role
+    pub fn decode(&self, token: &str, role: TokenRole) -> Option<JwtClaims> {
+        let mut validation = Validation::default();
+        validation.set_audience(&[role.audience()]);
+        match decode::<JwtClaims>(token, &self.key(role).decoding, &validation) {
+            Ok(token_data) => {
+                let claims = token_data.claims;
+                (claims.role == role).then_some(claims)
+            }
+            Err(_) if role == TokenRole::USER => self.decode_legacy(token),
+            Err(_) => None,
+        }
+    }
+
+    /// App tokens issued before the audiences were added have no `aud`,
+    /// they are accepted as app user tokens until they expire
+    fn decode_legacy(&self, token: &str) -> Option<JwtClaims> {
         let token_data =
-            decode::<JwtClaims>(&token, &self.decoding, &Validation::default()).ok()?;
-        Some(token_data.claims)
+            decode::<LegacyJwtClaims>(token, &self.app.decoding, &Validation::default()).ok()?;
+        let claims = token_data.claims;
+        if claims.aud.is_some() {
+            return None;
+        }
+        Some(JwtClaims::new(
+            claims.id,
+            claims.name,
+            TokenRole::USER,
+            claims.exp,
+        ))
+    }
+
+    /// Claims of an app user token
+    pub fn extract_claims(&self, token: &str) -> Option<JwtClaims> {
+        self.decode(token, TokenRole::USER)
     }
 }
 
@@ -68,56 +162,87 @@ pub struct JwtClaims {
     pub id: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    pub aud: String,
+    pub role: TokenRole,
     pub exp: usize,
 }
 
 impl JwtClaims {
-    fn new(id: u32, name: Option<String>, exp: usize) -> Self {
-        Self { id, name, exp }
+    fn new(id: u32, name: Option<String>, role: TokenRole, exp: usize) -> Self {
+        let aud = role.audience().to_string();
+        Self {
+            id,
+            name,
+            aud,
+            role,
+            exp,
+        }
     }
 }
 
+/// Claims of the tokens issued before the audiences were added
+#[derive(Debug, Deserialize)]
+struct LegacyJwtClaims {
+    id: u32,
+    name: Option<String>,
+    aud: Option<serde_json::Value>,
+    exp: usize,
+}
+
 #[async_trait]
-impl FromRequestParts<Arc<AppDatabase>> for JwtClaims {
+impl<S> FromRequestParts<S> for JwtClaims
+where
+    S: Send + Sync,
+{
     type Rejection = AppError;
 
-    async fn from_request_parts(
-        parts: &mut Parts,
-        _state: &Arc<AppDatabase>,
-    ) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let TypedHeader(Authorization(bearer)) = parts
             .extract::<TypedHeader<Authorization<Bearer>>>()
             .await
             .map_err(|_| AppError::Auth("Missing token".into()))?;
-        let token_data =
-            decode::<JwtClaims>(bearer.token(), &JWT_KEYS.decoding, &Validation::default())
-                .map_err(|_| AppError::Auth("Invalid Token".into()))?;
-        Ok(token_data.claims)
+        JWT_KEYS
+            .decode(bearer.token(), TokenRole::USER)
+            .ok_or(AppError::Auth("Invalid Token".into()))
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwtClaimsAdmin {
     pub data: JwtClaims,
+    /// current role of the admin, read from the database on every request
+    pub role: Option<AdminRole>,
+}
+
+impl JwtClaimsAdmin {
+    /// Check that the role of the admin grants the permission of the route
+    pub fn check_permission(&self, permission: AdminPermission) -> Result<(), AppError> {
+        match self.role {
+            Some(role) if role.has_permission(permission) => Ok(()),
+            _ => Err(AppError::Forbidden("Permission denied".into())),
+        }
+    }
 }
 
 #[async_trait]
-impl FromRequestParts<Arc<AppDatabase>> for JwtClaimsAdmin {
+impl<S> FromRequestParts<S> for JwtClaimsAdmin
+where
+    Arc<AppDatabase>: FromRef<S>,
+    S: Send + Sync,
+{
     type Rejection = AppError;
 
-    async fn from_request_parts(
-        parts: &mut Parts,
-        state: &Arc<AppDatabase>,
-    ) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let db = Arc::<AppDatabase>::from_ref(state);
         let TypedHeader(Authorization(bearer)) = parts
             .extract::<TypedHeader<Authorization<Bearer>>>()
             .await
             .map_err(|_| AppError::Auth("Missing token".into()))?;
-        let token_data =
-            decode::<JwtClaims>(bearer.token(), &JWT_KEYS.decoding, &Validation::default())
-                .map_err(|_| AppError::Auth("Invalid Token".into()))?;
-        let user_id = token_data.claims.id;
-        get_admin_user_by_id(user_id, state)
+        let token_claims = JWT_KEYS
+            .decode(bearer.token(), TokenRole::ADMIN)
+            .ok_or(AppError::Auth("Invalid Token".into()))?;
+        let user_id = token_claims.id;
+        let admin = get_admin_user_by_id(user_id, &db)
             .await
             .map_err(|err| {
                 tracing::debug!("{:?}", err);
@@ -125,8 +250,42 @@ impl FromRequestParts<Arc<AppDatabase>> for JwtClaimsAdmin {
             })?
             .ok_or(AppError::Auth("user do not exists".into()))?;
         let claims = JwtClaimsAdmin {
-            data: token_data.claims,
+            data: token_claims,
+            role: admin.role,
         };
         Ok(claims)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_audience() {
+        let token = JWT_KEYS.generate_token(1, None).unwrap();
+        assert_eq!(JWT_KEYS.decode(&token, TokenRole::USER).unwrap().id, 1);
+        assert!(JWT_KEYS.decode(&token, TokenRole::ADMIN).is_none());
+        let token = JWT_KEYS.generate_admin_token(2, None).unwrap();
+        let claims = JWT_KEYS.decode(&token, TokenRole::ADMIN).unwrap();
+        assert_eq!(claims.aud, JWT_AUD_ADMIN);
+        assert!(JWT_KEYS.decode(&token, TokenRole::USER).is_none());
+    }
+
+    #[test]
+    fn test_legacy_token() {
+        let exp = (get_epoch_ts() + 60) as usize;
+        let claims = serde_json::json!({"id": 3, "exp": exp});
+        let token = encode(&Header::default(), &claims, &JWT_KEYS.app.encoding).unwrap();
+        let claims = JWT_KEYS.decode(&token, TokenRole::USER).unwrap();
+        assert_eq!(claims.id, 3);
+        assert_eq!(claims.aud, JWT_AUD_APP);
+        assert!(JWT_KEYS.decode(&token, TokenRole::ADMIN).is_none());
+
+        // legacy tokens are accepted only until they expire
+        let exp = get_epoch_ts().saturating_sub(3600) as usize;
+        let claims = serde_json::json!({"id": 3, "exp": exp});
+        let token = encode(&Header::default(), &claims, &JWT_KEYS.app.encoding).unwrap();
+        assert!(JWT_KEYS.decode(&token, TokenRole::USER).is_none());
+    }
+}
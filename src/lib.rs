@@ -1,31 +1,68 @@
 use std::{net::SocketAddr, sync::Arc};
 
+use config::AppConfig;
 use database::AppDatabase;
 use dotenvy::dotenv;
-use jobs::spawn_all_jobs;
+use events::EventBus;
+use jobs::{notification::google_auth_token::GoogleAuthToken, spawn_all_jobs};
+use jwt::init_jwt_keys;
+use payment::{payment_gateway_from_env, payout_provider_from_env};
+use sms::sms_sender_from_env;
+use state::AppState;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 pub mod app;
+pub mod config;
 pub mod constants;
 pub mod database;
+pub mod events;
 pub mod handlers;
 pub mod jobs;
 pub mod jwt;
 pub mod models;
+pub mod payment;
+pub mod sms;
+pub mod state;
 pub mod swagger;
 pub mod utils;
 
 pub async fn start_web_server() {
     // import .env file
     dotenv().ok();
+    initialize_logging();
+    // load and validate the config, the app does not start with an invalid config
+    let config = AppConfig::load().expect("Unable to load the config");
+    tracing::debug!("Starting the app in {:?} environment", config.environment);
+    init_jwt_keys(&config.jwt).expect("Unable to initialize the jwt keys");
     // create database client
-    let db_client = AppDatabase::new()
+    let db_client = AppDatabase::new(&config.mongodb)
         .await
         .expect("Unable to accquire database client");
     let db_client = Arc::new(db_client);
-    initialize_logging();
-    spawn_all_jobs(db_client.clone());
-    start_server(db_client).await;
+    // create the payment gateway client
+    let providers = &config.providers;
+    let payment_gateway = payment_gateway_from_env(&providers.payment_gateway)
+        .expect("Unable to configure the payment gateway");
+    let payout_provider = payout_provider_from_env(&providers.payout_provider)
+        .expect("Unable to configure the payout provider");
+    // create the sms gateway client used for sending the otp
+    let sms_sender =
+        sms_sender_from_env(&providers.sms_provider).expect("Unable to configure the sms gateway");
+    // load the firebase service account used for the push messages
+    let google_auth_token =
+        GoogleAuthToken::from_env().expect("Unable to load the firebase credentials");
+    // create the bus of the contest events published by the handlers and the jobs
+    let events = Arc::new(EventBus::default());
+    spawn_all_jobs(
+        db_client.clone(),
+        &config.jobs,
+        payout_provider,
+        google_auth_token,
+        events.clone(),
+    );
+    let config = Arc::new(config);
+    let state = AppState::new(config, db_client, payment_gateway, sms_sender, events);
+    start_server(state).await;
 }
 
 fn initialize_logging() {
@@ -40,14 +77,11 @@ fn initialize_logging() {
         .init();
 }
 
-async fn start_server(db_client: Arc<AppDatabase>) {
-    // read the port number from env variable
-    let port = std::env::var("PORT").unwrap_or_default();
-    let port = port.parse::<u16>().unwrap_or(3000);
+async fn start_server(state: AppState) {
     // build the socket address
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let addr = SocketAddr::from(([0, 0, 0, 0], state.config.port));
     // create the app instance
-    let app = app::build_app(db_client);
+    let app = app::build_app(state);
     tracing::debug!("Starting the app in: {addr}");
     // start serving the app in the socket address
     axum::Server::bind(&addr).serve(app).await.unwrap();
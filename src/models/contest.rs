@@ -1,10 +1,22 @@
-use chrono::{prelude::*, serde::ts_seconds};
+use chrono::{
+    prelude::*,
+    serde::{ts_seconds, ts_seconds_option},
+};
 use mongodb::bson::Bson;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use validator::Validate;
 
-use crate::utils::{deserialize_helper, get_epoch_ts, validation::validate_future_timestamp};
+use super::{play_tracker::PlayTracker, wallet::Money};
+use crate::{
+    constants::{
+        CONTEST_SCHEDULE_CREATE_BEFORE_SECS, CONTEST_TIME_BUDGET_SECS, QUESTION_TIME_LIMIT_SECS,
+    },
+    utils::{
+        deserialize_helper, generate_invite_code, get_epoch_ts,
+        validation::validate_future_timestamp,
+    },
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[allow(non_camel_case_types)]
@@ -45,6 +57,64 @@ pub enum PrizeSelection {
     RATIO_BASED,
 }
 
+/// How players with the same score are ranked and paid
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum TiePolicy {
+    /// tie is broken by the time taken and then by the start time
+    #[default]
+    RANK_BY_TIME,
+    /// tied players share the rank and split the prizes of the ranks they occupy equally
+    SPLIT_PRIZE,
+    /// tied players share the rank and each gets the full fixed prize of that rank,
+    /// the pool share of the ranks they occupy is split equally
+    SHARED_RANK,
+}
+
+/// What happens to an answer submitted after the question deadline
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum LateAnswerPolicy {
+    /// the answer is recorded with zero score
+    #[default]
+    SCORE_ZERO,
+    /// the answer is discarded, the question is recorded as unanswered and an error is returned
+    REJECT,
+}
+
+/// Prize paid to every rank from `rankFrom` to `rankTo`.
+/// The fixed prize value is added to `poolPercentage` percent of the collected entry fees.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrizeTier {
+    #[validate(range(min = 1))]
+    pub rank_from: u32,
+    #[validate(range(min = 1))]
+    pub rank_to: u32,
+    #[serde(default)]
+    pub prize_value_real_money: u32,
+    #[serde(default)]
+    pub prize_value_bonus_money: u32,
+    #[validate(range(min = 1, max = 100))]
+    pub pool_percentage: Option<u32>,
+}
+
+impl PrizeTier {
+    /// prize of a single rank of this tier. Real and bonus entry fees are shared separately.
+    pub fn prize(&self, pool: Money) -> Money {
+        let percentage = self.pool_percentage.unwrap_or_default() as u64;
+        let real = self.prize_value_real_money as u64 + pool.real() * percentage / 100;
+        let bonus = self.prize_value_bonus_money as u64 + pool.bonus() * percentage / 100;
+        Money::new(real, bonus)
+    }
+
+    pub fn ranks_count(&self) -> u32 {
+        self.rank_to
+            .checked_sub(self.rank_from)
+            .map_or(0, |count| count.saturating_add(1))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct ContestProps {
@@ -72,6 +142,14 @@ pub struct ContestProps {
     pub prize_ratio_denominator: Option<u32>,
     pub prize_value_real_money: u32,
     pub prize_value_bonus_money: u32,
+    /// prize by rank range, replaces the flat prize value when not empty
+    #[serde(default)]
+    #[validate]
+    pub prize_table: Vec<PrizeTier>,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    #[serde(default)]
+    pub late_answer_policy: LateAnswerPolicy,
     #[serde(with = "ts_seconds")]
     #[validate(custom = "validate_future_timestamp")]
     pub start_time: DateTime<Utc>,
@@ -80,6 +158,25 @@ pub struct ContestProps {
     pub end_time: DateTime<Utc>,
     #[serde(default)]
     pub min_required_players: u32,
+    /// seconds a player gets to finish the contest from the start
+    #[validate(range(min = 10))]
+    pub time_budget: Option<u32>,
+    /// marks deducted for a wrong answer unless set on the question
+    #[validate(range(max = 100))]
+    pub negative_marks: Option<u32>,
+    /// extra points for an instant correct answer, reducing linearly to zero at the
+    /// question time limit
+    #[validate(range(max = 100))]
+    pub speed_bonus_points: Option<u32>,
+    /// the contest in CREATED status is activated by the scheduler at this time
+    #[serde(default, with = "ts_seconds_option")]
+    pub activate_at: Option<DateTime<Utc>>,
+    /// private contests are not listed and are joined with the invite code
+    #[serde(default)]
+    pub is_private: bool,
+    /// maximum number of players who can join the contest
+    #[validate(range(min = 1))]
+    pub max_players: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
@@ -92,6 +189,11 @@ pub struct Contest {
     #[serde(flatten)]
     pub props: ContestProps,
     pub status: ContestStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+    /// number of players joined, used to enforce `maxPlayers`
+    #[serde(default)]
+    pub joined_count: u32,
     pub created_ts: Option<u64>,
     pub created_by: Option<u32>,
     pub updated_ts: Option<u64>,
@@ -108,6 +210,11 @@ pub struct ContestWithQuestion {
     #[serde(flatten)]
     pub props: ContestProps,
     pub status: ContestStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+    /// number of players joined, used to enforce `maxPlayers`
+    #[serde(default)]
+    pub joined_count: u32,
     pub questions: Option<Vec<Question>>,
     pub created_ts: Option<u64>,
     pub created_by: Option<u32>,
@@ -115,10 +222,141 @@ pub struct ContestWithQuestion {
     pub updated_by: Option<u32>,
 }
 
+/// Final standings written to the contest when it is ended
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestStandings {
+    #[serde(default)]
+    pub all_play_trackers: Vec<PlayTracker>,
+    #[serde(default)]
+    pub winners: Vec<PlayTracker>,
+}
+
+/// Saved contest props and questions to create contests of a recurring format
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestTemplate {
+    #[serde(rename = "_id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_helper")]
+    pub _id: Option<String>,
+    pub name: String,
+    pub contest: ContestProps,
+    #[serde(default)]
+    pub questions: Vec<Question>,
+    /// contests are created from the template as per the schedule when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ContestSchedule>,
+    pub created_ts: Option<u64>,
+    pub created_by: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum Recurrence {
+    DAILY,
+    WEEKLY,
+}
+
+impl Recurrence {
+    pub fn period_secs(&self) -> i64 {
+        match self {
+            Self::DAILY => 24 * 60 * 60,
+            Self::WEEKLY => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+/// Schedule of the contests created from a template. A contest starts at `firstStartTime`
+/// and then every day or week, runs for `duration` seconds and is activated
+/// `activateBefore` seconds before the start. Contests are created `createBefore` seconds
+/// ahead of their start time.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestSchedule {
+    pub recurrence: Recurrence,
+    #[serde(with = "ts_seconds")]
+    pub first_start_time: DateTime<Utc>,
+    #[validate(range(min = 60))]
+    pub duration: u32,
+    #[serde(default)]
+    pub activate_before: u32,
+    #[validate(range(min = 60))]
+    pub create_before: Option<u32>,
+    #[serde(default)]
+    pub is_paused: bool,
+}
+
+impl ContestSchedule {
+    pub fn create_before(&self) -> u32 {
+        self.create_before
+            .unwrap_or(CONTEST_SCHEDULE_CREATE_BEFORE_SECS)
+    }
+
+    /// start times of the occurrences which are not started yet at `now` and are due to be
+    /// created
+    pub fn due_start_times(&self, now: i64) -> Vec<i64> {
+        let period = self.recurrence.period_secs();
+        let first = self.first_start_time.timestamp();
+        let until = now + self.create_before() as i64;
+        let skip = if now > first {
+            (now - first + period - 1) / period
+        } else {
+            0
+        };
+        (skip..)
+            .map(|n| first + n * period)
+            .take_while(|start| *start <= until)
+            .collect()
+    }
+}
+
+/// Type of a question and how it is answered.
+/// * `SINGLE_CHOICE`: four options with one correct option, answered with `selectedOptionId`
+/// * `TRUE_FALSE`: two options with one correct option, answered with `selectedOptionId`
+/// * `MULTI_SELECT`: two to ten options with one or more correct options, answered with
+///   `selectedOptionIds` and scored as per `multiSelectScoring`
+/// * `NUMERIC`: no options, answered with `numericAnswer` which must be within the tolerance
+/// * `IMAGE_CHOICE`: two to ten options each with an image and one correct option,
+///   answered with `selectedOptionId`
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum QuestionType {
+    #[default]
+    SINGLE_CHOICE,
+    TRUE_FALSE,
+    MULTI_SELECT,
+    NUMERIC,
+    IMAGE_CHOICE,
+}
+
+/// Scoring of a multi-select question.
+/// * `ALL_OR_NOTHING`: one point when exactly the correct options are selected
+/// * `PARTIAL`: one point for every correct option selected less one for every wrong option
+///   selected, never below zero
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum MultiSelectScoring {
+    #[default]
+    ALL_OR_NOTHING,
+    PARTIAL,
+}
+
+/// Correct answer of a numeric question
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NumericAnswer {
+    pub value: f64,
+    /// answers within `value ± tolerance` are correct
+    #[serde(default)]
+    #[validate(range(min = 0.0))]
+    pub tolerance: f64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AnswerProps {
-    #[validate(range(min = 1, max = 4))]
+    #[validate(range(min = 1, max = 10))]
     pub option_id: u32,
     #[validate(length(min = 1, max = 100))]
     pub option_text: String,
@@ -150,6 +388,29 @@ pub struct QuestionProps {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[validate(url)]
     pub image_or_video_url: Option<String>,
+    /// seconds allowed to answer once the question is served
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 5, max = 600))]
+    pub time_limit: Option<u32>,
+    #[serde(default)]
+    pub question_type: QuestionType,
+    /// only for `MULTI_SELECT` questions, defaults to `ALL_OR_NOTHING`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_select_scoring: Option<MultiSelectScoring>,
+    /// points of a fully correct answer, defaults to the score of the question type
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, max = 100))]
+    pub points: Option<u32>,
+    /// marks deducted for a wrong answer, overrides the contest negative marks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(max = 100))]
+    pub negative_marks: Option<u32>,
+}
+
+impl QuestionProps {
+    pub fn time_limit(&self) -> u32 {
+        self.time_limit.unwrap_or(QUESTION_TIME_LIMIT_SECS)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
@@ -158,9 +419,17 @@ pub struct Question {
     #[serde(flatten)]
     #[validate]
     pub props: QuestionProps,
+    #[serde(default)]
     #[validate]
     pub options: Vec<Answer>,
+    /// only for `NUMERIC` questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub numeric_answer: Option<NumericAnswer>,
     pub is_active: bool,
+    /// bank question this question is attached from by reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank_question_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
@@ -169,8 +438,13 @@ pub struct QuestionReqBody {
     #[serde(flatten)]
     #[validate]
     pub props: QuestionProps,
+    #[serde(default)]
     #[validate]
     pub options: Vec<Answer>,
+    /// only for `NUMERIC` questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate]
+    pub numeric_answer: Option<NumericAnswer>,
 }
 
 impl Contest {
@@ -180,6 +454,8 @@ impl Contest {
             _id: None,
             props: props.clone(),
             status: ContestStatus::CREATED,
+            invite_code: props.is_private.then(generate_invite_code),
+            joined_count: 0,
             created_ts: Some(ts),
             created_by: Some(user_id),
             updated_ts: None,
@@ -188,19 +464,72 @@ impl Contest {
     }
 }
 
+impl ContestProps {
+    pub fn time_budget(&self) -> u32 {
+        self.time_budget.unwrap_or(CONTEST_TIME_BUDGET_SECS)
+    }
+
+    /// deadline of a play started at `start_ts`, never later than the contest end time
+    pub fn play_deadline(&self, start_ts: u64) -> u64 {
+        let end_ts = self.end_time.timestamp().max(0) as u64;
+        (start_ts + self.time_budget() as u64).min(end_ts)
+    }
+
+    /// prize of the rank where `pool` is the entry fee collected from all the players
+    pub fn prize_for_rank(&self, rank: u32, pool: Money) -> Money {
+        if self.prize_table.is_empty() {
+            let real = self.prize_value_real_money as u64;
+            let bonus = self.prize_value_bonus_money as u64;
+            return Money::new(real, bonus);
+        }
+        self.prize_table
+            .iter()
+            .find(|tier| tier.rank_from <= rank && rank <= tier.rank_to)
+            .map(|tier| tier.prize(pool))
+            .unwrap_or_default()
+    }
+}
+
 impl Question {
+    /// points of a fully correct answer
+    pub fn points(&self) -> u32 {
+        self.props.points.unwrap_or_else(|| self.max_score())
+    }
+
+    /// default score of a fully correct answer as per the question type
+    pub fn max_score(&self) -> u32 {
+        match (self.props.question_type, self.props.multi_select_scoring) {
+            (QuestionType::MULTI_SELECT, Some(MultiSelectScoring::PARTIAL)) => {
+                self.options.iter().filter(|opt| opt.is_correct).count() as u32
+            }
+            _ => 1,
+        }
+    }
+
     pub fn to_bson(&self) -> anyhow::Result<Bson> {
         let bson = mongodb::bson::to_bson(self)?;
         Ok(bson)
     }
 }
 
+impl From<&Question> for QuestionReqBody {
+    fn from(value: &Question) -> Self {
+        Self {
+            props: value.props.clone(),
+            options: value.options.clone(),
+            numeric_answer: value.numeric_answer.clone(),
+        }
+    }
+}
+
 impl From<QuestionReqBody> for Question {
     fn from(value: QuestionReqBody) -> Self {
         Self {
             props: value.props,
             options: value.options,
+            numeric_answer: value.numeric_answer,
             is_active: true,
+            bank_question_id: None,
         }
     }
 }
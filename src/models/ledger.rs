@@ -0,0 +1,86 @@
+use mongodb::bson::{oid::ObjectId, Bson};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::WalltetTransactionType;
+use crate::utils::get_epoch_ts;
+
+/// Accounts of the double-entry ledger.
+/// Every user wallet is a `User` account, the others are system accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LedgerAccount {
+    User,
+    House,
+    PrizePool,
+    BonusPool,
+    Refunds,
+}
+
+impl LedgerAccount {
+    pub fn to_bson(&self) -> anyhow::Result<Bson> {
+        let bson = mongodb::bson::to_bson(self)?;
+        Ok(bson)
+    }
+}
+
+/// One line of a ledger posting. Amounts are signed, positive amount is credited to the account.
+/// All entries of a posting share the `postingId` and add up to zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub posting_id: String,
+    pub account: LedgerAccount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u32>,
+    pub real: i64,
+    pub bonus: i64,
+    /// `None` for the opening balance of wallets created before the ledger
+    pub transaction_type: Option<WalltetTransactionType>,
+    pub created_ts: u64,
+}
+
+impl LedgerEntry {
+    /// Balanced posting which credits the signed amount to the user and debits it from the counter account
+    pub fn posting(
+        user_id: u32,
+        real: i64,
+        bonus: i64,
+        counter_account: LedgerAccount,
+        transaction_type: Option<WalltetTransactionType>,
+    ) -> Vec<Self> {
+        let posting_id = ObjectId::new().to_hex();
+        let created_ts = get_epoch_ts();
+        let user_entry = Self {
+            posting_id: posting_id.clone(),
+            account: LedgerAccount::User,
+            user_id: Some(user_id),
+            real,
+            bonus,
+            transaction_type: transaction_type.clone(),
+            created_ts,
+        };
+        let counter_entry = Self {
+            posting_id,
+            account: counter_account,
+            user_id: None,
+            real: -real,
+            bonus: -bonus,
+            transaction_type,
+            created_ts,
+        };
+        vec![user_entry, counter_entry]
+    }
+}
+
+/// Wallet whose stored balance does not match the balance computed from the ledger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerDrift {
+    pub user_id: u32,
+    pub wallet_real: i64,
+    pub wallet_bonus: i64,
+    pub ledger_real: i64,
+    pub ledger_bonus: i64,
+    pub detected_ts: u64,
+}
@@ -1,9 +1,11 @@
 pub mod clip;
 pub mod contest;
+pub mod ledger;
 pub mod movie;
 pub mod notification;
 pub mod otp;
 pub mod play_tracker;
+pub mod question_bank;
 pub mod request_schema;
 pub mod response_schema;
 pub mod user;
@@ -12,10 +14,12 @@ pub mod wallet;
 pub use clip::*;
 pub use contest::*;
 pub use contest::*;
+pub use ledger::*;
 pub use movie::*;
 pub use notification::*;
 pub use otp::*;
 pub use play_tracker::*;
+pub use question_bank::*;
 pub use request_schema::*;
 pub use response_schema::*;
 pub use user::*;
@@ -86,7 +86,7 @@ pub struct NotificationContent {
     pub created_ts: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Notifications {
     #[serde(rename = "_id")]
@@ -119,4 +119,9 @@ impl Notifications {
             updated_ts: None,
         }
     }
+
+    pub fn with_id(mut self, id: String) -> Self {
+        self._id = Some(id);
+        self
+    }
 }
@@ -2,6 +2,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{constants::*, utils::get_epoch_ts};
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum OtpDeliveryStatus {
+    PENDING,
+    SENT,
+    FAILED,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Otp {
@@ -10,6 +18,18 @@ pub struct Otp {
     pub valid_till: u64,
     pub is_used: bool,
     pub update_ts: u64,
+    /// number of wrong verification attempts
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_status: Option<OtpDeliveryStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sms_provider: Option<String>,
+    /// reference of the message returned by the sms provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_error: Option<String>,
 }
 
 impl Otp {
@@ -21,6 +41,35 @@ impl Otp {
             valid_till: ts + OTP_VALIDITY_MINS * 60,
             is_used: false,
             update_ts: ts,
+            attempts: 0,
+            delivery_status: Some(OtpDeliveryStatus::PENDING),
+            sms_provider: None,
+            provider_ref: None,
+            delivery_error: None,
         }
     }
 }
+
+/// Send counter, failed verifications and lockout of a phone or an ip address.
+/// `key` is either `phone:<phone>` or `ip:<ip>`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OtpLimit {
+    pub key: String,
+    pub window_start: u64,
+    pub send_count: u32,
+    pub failed_count: u32,
+    pub lockout_count: u32,
+    pub locked_till: Option<u64>,
+    pub update_ts: u64,
+}
+
+impl OtpLimit {
+    /// lockout duration doubles with every lockout up to `OTP_LOCKOUT_MAX_SECS`
+    pub fn lockout_secs(lockout_count: u32) -> u64 {
+        let factor = 2_u64.saturating_pow(lockout_count);
+        OTP_LOCKOUT_BASE_SECS
+            .saturating_mul(factor)
+            .min(OTP_LOCKOUT_MAX_SECS)
+    }
+}
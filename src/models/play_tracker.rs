@@ -54,7 +54,49 @@ impl PlayTrackerStatus {
 pub struct ChosenAnswer {
     #[serde(flatten)]
     pub question: Question,
+    /// zero when a late answer was rejected or the question has no single option answer
     pub selected_option_id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_option_ids: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric_answer: Option<f64>,
+    /// weighted score given for this answer including the speed bonus, negative for a wrong
+    /// answer with negative marking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_bonus: Option<u32>,
+    /// seconds between serving the question and receiving the answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<u64>,
+    /// answered after the question deadline
+    #[serde(default)]
+    pub is_late: bool,
+}
+
+/// Question served to the player with the server issued deadline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServedQuestion {
+    pub question_no: u32,
+    pub served_ts: u64,
+    pub deadline_ts: u64,
+}
+
+impl ServedQuestion {
+    pub fn new(question: &QuestionProps) -> Self {
+        let served_ts = get_epoch_ts();
+        Self {
+            question_no: question.question_no,
+            served_ts,
+            deadline_ts: served_ts + question.time_limit() as u64,
+        }
+    }
+
+    pub fn to_bson(&self) -> anyhow::Result<Bson> {
+        let bson = mongodb::bson::to_bson(self)?;
+        Ok(bson)
+    }
 }
 
 impl ChosenAnswer {
@@ -76,6 +118,9 @@ pub struct PlayTracker {
     pub start_ts: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finish_ts: Option<u64>,
+    /// end of the contest time budget, the play is finished automatically after it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline_ts: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resume_ts: Option<Vec<u64>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -88,14 +133,24 @@ pub struct PlayTracker {
     pub total_questions: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_answered: Option<u32>,
+    /// sum of the weighted score of all answers
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub score: Option<u32>,
+    pub score: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub answers: Option<Vec<ChosenAnswer>>,
+    /// question waiting for the answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_question: Option<ServedQuestion>,
+    /// sum of the response time of all answers in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_response_time: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_taken: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rank: Option<u32>,
+    /// prize credited to the winner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prize_amount: Option<Money>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_ts: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -116,6 +171,7 @@ impl PlayTracker {
             init_ts: Some(ts),
             start_ts: None,
             finish_ts: None,
+            deadline_ts: None,
             resume_ts: None,
             paid_ts: None,
             wallet_transaction_id: None,
@@ -124,8 +180,11 @@ impl PlayTracker {
             total_answered: Some(0),
             score: None,
             answers: None,
+            current_question: None,
+            total_response_time: None,
             time_taken: None,
             rank: None,
+            prize_amount: None,
             created_ts: Some(ts),
             created_by: Some(user_id),
             updated_ts: None,
@@ -0,0 +1,216 @@
+use mongodb::bson::Bson;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::contest::{
+    Answer, AnswerProps, MultiSelectScoring, NumericAnswer, Question, QuestionProps,
+    QuestionReqBody, QuestionType,
+};
+use crate::utils::{deserialize_helper, get_epoch_ts, validate_tags};
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum Difficulty {
+    EASY,
+    #[default]
+    MEDIUM,
+    HARD,
+}
+
+impl Difficulty {
+    pub fn to_bson(&self) -> anyhow::Result<Bson> {
+        let bson = mongodb::bson::to_bson(self)?;
+        Ok(bson)
+    }
+}
+
+/// Question of the question bank. The `questionNo` is replaced when the question is
+/// attached to a contest.
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BankQuestionProps {
+    #[serde(flatten)]
+    #[validate]
+    pub question: QuestionReqBody,
+    #[serde(default)]
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BankQuestion {
+    #[serde(rename = "_id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_helper")]
+    pub _id: Option<String>,
+    #[serde(flatten)]
+    pub props: BankQuestionProps,
+    pub is_active: bool,
+    pub created_ts: Option<u64>,
+    pub created_by: Option<u32>,
+    pub updated_ts: Option<u64>,
+    pub updated_by: Option<u32>,
+}
+
+impl BankQuestion {
+    pub fn new(props: BankQuestionProps, user_id: u32) -> Self {
+        Self {
+            _id: None,
+            props,
+            is_active: true,
+            created_ts: Some(get_epoch_ts()),
+            created_by: Some(user_id),
+            updated_ts: None,
+            updated_by: None,
+        }
+    }
+
+    /// contest question with the given question number
+    pub fn to_question(&self, question_no: u32, attach_mode: AttachMode) -> Question {
+        let mut question: Question = self.props.question.clone().into();
+        question.props.question_no = question_no;
+        if attach_mode == AttachMode::REFERENCE {
+            question.bank_question_id = self._id.clone();
+        }
+        question
+    }
+}
+
+/// How a bank question is attached to a contest.
+/// * `COPY`: the contest gets an independent copy of the question
+/// * `REFERENCE`: the contest question keeps the bank question id and is updated along with
+///   the bank question while the contest is in `CREATED` status
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum AttachMode {
+    #[default]
+    COPY,
+    REFERENCE,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum QuestionFileFormat {
+    #[default]
+    JSON,
+    CSV,
+}
+
+/// One row of the questions CSV file.
+/// `options` are separated by `|` and get the option ids 1, 2, 3... in the same order.
+/// `correctOptions` are the `|` separated positions of the correct options starting from 1.
+/// `tags` are `|` separated.
+/// Images and videos are not supported in CSV.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionCsvRow {
+    pub question_text: String,
+    pub question_type: Option<QuestionType>,
+    pub multi_select_scoring: Option<MultiSelectScoring>,
+    #[serde(default)]
+    pub options: String,
+    #[serde(default)]
+    pub correct_options: String,
+    pub numeric_answer: Option<f64>,
+    pub tolerance: Option<f64>,
+    pub points: Option<u32>,
+    pub negative_marks: Option<u32>,
+    pub time_limit: Option<u32>,
+    #[serde(default)]
+    pub category: String,
+    pub difficulty: Option<Difficulty>,
+    #[serde(default)]
+    pub tags: String,
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split('|').map(str::trim).filter(|v| !v.is_empty())
+}
+
+impl QuestionCsvRow {
+    /// convert the row into a bank question, `question_no` is the row number
+    pub fn into_bank_question(self, question_no: u32) -> Result<BankQuestionProps, String> {
+        let correct_options = split_list(&self.correct_options)
+            .map(|id| {
+                id.parse::<u32>()
+                    .map_err(|_| format!("invalid correct option {id}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let options = split_list(&self.options)
+            .zip(1..)
+            .map(|(text, option_id)| Answer {
+                props: AnswerProps {
+                    option_id,
+                    option_text: text.to_string(),
+                    has_video: false,
+                    has_image: false,
+                    image_or_video_url: None,
+                },
+                is_correct: correct_options.contains(&option_id),
+            })
+            .collect::<Vec<_>>();
+        if let Some(id) = correct_options
+            .iter()
+            .find(|id| !options.iter().any(|opt| opt.props.option_id == **id))
+        {
+            return Err(format!("correct option {id} not found in options"));
+        }
+        let numeric_answer = self.numeric_answer.map(|value| NumericAnswer {
+            value,
+            tolerance: self.tolerance.unwrap_or(0.0),
+        });
+        let question = QuestionReqBody {
+            props: QuestionProps {
+                question_no,
+                question_text: self.question_text,
+                has_video: false,
+                has_image: false,
+                image_or_video_url: None,
+                time_limit: self.time_limit,
+                question_type: self.question_type.unwrap_or_default(),
+                multi_select_scoring: self.multi_select_scoring,
+                points: self.points,
+                negative_marks: self.negative_marks,
+            },
+            options,
+            numeric_answer,
+        };
+        let props = BankQuestionProps {
+            question,
+            tags: split_list(&self.tags).map(str::to_string).collect(),
+            difficulty: self.difficulty.unwrap_or_default(),
+            category: self.category,
+        };
+        Ok(props)
+    }
+}
+
+impl From<&Question> for QuestionCsvRow {
+    fn from(value: &Question) -> Self {
+        let options = value.options.iter().map(|o| o.props.option_text.clone());
+        let correct_options = (1..)
+            .zip(value.options.iter())
+            .filter(|(_, o)| o.is_correct)
+            .map(|(position, _)| position.to_string());
+        Self {
+            question_text: value.props.question_text.clone(),
+            question_type: Some(value.props.question_type),
+            multi_select_scoring: value.props.multi_select_scoring,
+            options: options.collect::<Vec<_>>().join("|"),
+            correct_options: correct_options.collect::<Vec<_>>().join("|"),
+            numeric_answer: value.numeric_answer.as_ref().map(|ans| ans.value),
+            tolerance: value.numeric_answer.as_ref().map(|ans| ans.tolerance),
+            points: value.props.points,
+            negative_marks: value.props.negative_marks,
+            time_limit: value.props.time_limit,
+            ..Default::default()
+        }
+    }
+}
@@ -11,7 +11,11 @@ use crate::{
     utils::{validate_future_timestamp, validate_phonenumber, validate_tags},
 };
 
-use super::{ContestCategory, ContestProps, LoginScheme, MediaType, QuestionReqBody};
+use super::{
+    AdminRole, AttachMode, BankQuestionProps, ContestCategory, ContestProps, ContestSchedule,
+    Difficulty, LoginScheme, MediaType, QuestionFileFormat, QuestionReqBody, QuestionType,
+    WalletTransactionStatus, WalltetTransactionType,
+};
 
 /// request body schema for create user
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
@@ -176,6 +180,27 @@ pub struct AdminSignupRequest {
     pub name: String,
 }
 
+/// request body to create an admin user
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct AdminCreateRequest {
+    #[validate(custom(function = "validate_phonenumber"))]
+    pub phone: String,
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+    pub role: AdminRole,
+}
+
+/// request body to update an admin user
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminUpdateRequest {
+    pub id: u32,
+    #[validate(length(min = 1, max = 50))]
+    pub name: Option<String>,
+    pub role: Option<AdminRole>,
+    pub is_active: Option<bool>,
+}
+
 /// request schema for get clip
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -313,9 +338,21 @@ impl<'__s> utoipa::ToSchema<'__s> for ContestProps {
             "prizeRatioDenominator": 0,
             "prizeValueRealMoney": 0,
             "prizeValueBonusMoney": 0,
+            "prizeTable": [
+                {"rankFrom": 1, "rankTo": 1, "prizeValueRealMoney": 100, "prizeValueBonusMoney": 0},
+                {"rankFrom": 2, "rankTo": 5, "poolPercentage": 10}
+            ],
+            "tiePolicy": "RANK_BY_TIME",
+            "lateAnswerPolicy": "SCORE_ZERO",
             "startTime": 1689219392,
             "endTime": 1689219392,
-            "minRequiredPlayers": 0
+            "minRequiredPlayers": 0,
+            "timeBudget": 600,
+            "negativeMarks": 0,
+            "speedBonusPoints": 0,
+            "activateAt": 1689219092,
+            "isPrivate": false,
+            "maxPlayers": 100
         });
         (
             "ContestProps",
@@ -328,6 +365,141 @@ impl<'__s> utoipa::ToSchema<'__s> for ContestProps {
     }
 }
 
+impl<'__s> utoipa::ToSchema<'__s> for ContestSchedule {
+    fn schema() -> ToSchemaRetType<'__s> {
+        use utoipa::openapi::ObjectBuilder;
+        let integer = utoipa::openapi::SchemaType::Integer;
+        let int64_format =
+            utoipa::openapi::SchemaFormat::KnownFormat(utoipa::openapi::KnownFormat::Int64);
+        let recurrence = ObjectBuilder::new()
+            .schema_type(utoipa::openapi::SchemaType::String)
+            .enum_values(Some(["DAILY", "WEEKLY"]));
+        let seconds = || ObjectBuilder::new().schema_type(integer.clone());
+        let example = json!({
+            "recurrence": "DAILY",
+            "firstStartTime": 1689219392,
+            "duration": 3600,
+            "activateBefore": 900,
+            "createBefore": 86400,
+            "isPaused": false
+        });
+        (
+            "ContestSchedule",
+            ObjectBuilder::new()
+                .property("recurrence", recurrence)
+                .required("recurrence")
+                .property(
+                    "firstStartTime",
+                    seconds().format(Some(int64_format.clone())),
+                )
+                .required("firstStartTime")
+                .property("duration", seconds())
+                .required("duration")
+                .property("activateBefore", seconds())
+                .property("createBefore", seconds())
+                .property(
+                    "isPaused",
+                    ObjectBuilder::new().schema_type(utoipa::openapi::SchemaType::Boolean),
+                )
+                .example(Some(example))
+                .into(),
+        )
+    }
+}
+
+/// request schema for contest update
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateContestReqBody {
+    #[serde(rename = "_id")]
+    #[validate(length(equal = 24))]
+    pub _id: String,
+    #[serde(flatten)]
+    #[validate]
+    pub props: ContestProps,
+}
+
+/// request schema to create a new contest from an existing contest or a template.
+/// `sourceId` is the contest id for clone and the template id for create from template.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyContestReqBody {
+    #[validate(length(equal = 24))]
+    pub source_id: String,
+    #[validate(length(min = 1))]
+    pub title: String,
+    #[serde(with = "ts_seconds")]
+    #[validate(custom = "validate_future_timestamp")]
+    pub start_time: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    #[validate(custom = "validate_future_timestamp")]
+    pub end_time: DateTime<Utc>,
+}
+
+impl<'__s> utoipa::ToSchema<'__s> for CopyContestReqBody {
+    fn schema() -> ToSchemaRetType<'__s> {
+        use utoipa::openapi::ObjectBuilder;
+        let string = utoipa::openapi::SchemaType::String;
+        let integer = utoipa::openapi::SchemaType::Integer;
+        let int64_format =
+            utoipa::openapi::SchemaFormat::KnownFormat(utoipa::openapi::KnownFormat::Int64);
+        let timestamp = || {
+            ObjectBuilder::new()
+                .schema_type(integer.clone())
+                .format(Some(int64_format.clone()))
+        };
+        let example = json!({
+            "sourceId": "string",
+            "title": "string",
+            "startTime": 1689219392,
+            "endTime": 1689305792
+        });
+        (
+            "CopyContestReqBody",
+            ObjectBuilder::new()
+                .property("sourceId", ObjectBuilder::new().schema_type(string.clone()))
+                .required("sourceId")
+                .property("title", ObjectBuilder::new().schema_type(string))
+                .required("title")
+                .property("startTime", timestamp())
+                .required("startTime")
+                .property("endTime", timestamp())
+                .required("endTime")
+                .example(Some(example))
+                .into(),
+        )
+    }
+}
+
+/// request schema to save a contest as template
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveTemplateReqBody {
+    #[validate(length(equal = 24))]
+    pub contest_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
+
+/// request schema to set the schedule of a contest template, the schedule is removed when
+/// `schedule` is null
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleTemplateReqBody {
+    #[validate(length(equal = 24))]
+    pub template_id: String,
+    #[validate]
+    pub schedule: Option<ContestSchedule>,
+}
+
+/// request schema to get or join a private contest with the invite code
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct InviteCodeReq {
+    #[validate(length(equal = "INVITE_CODE_LEN"))]
+    pub invite_code: String,
+}
+
 /// request params for get contest
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -340,6 +512,25 @@ pub struct GetContestParams {
     pub page_index: Option<u64>,
 }
 
+/// request params for contest leaderboard and results
+#[derive(Debug, Serialize, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestRankParams {
+    #[validate(range(max = 10000))]
+    pub page_index: Option<u64>,
+    /// at most 100, defaults to 100
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: Option<u64>,
+}
+
+/// request params for the contest events channel
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestEventsParams {
+    /// receive the leaderboard deltas of this contest
+    pub contest_id: Option<String>,
+}
+
 /// request schema for contest activate
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -369,6 +560,61 @@ pub struct QuesDelReqBody {
     pub question_no: u32,
 }
 
+/// request schema for bank question update
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBankQuestionReqBody {
+    #[serde(rename = "_id")]
+    #[validate(length(equal = 24))]
+    pub _id: String,
+    #[serde(flatten)]
+    #[validate]
+    pub props: BankQuestionProps,
+}
+
+/// request params for get bank questions
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBankQuestionsParams {
+    pub tag: Option<String>,
+    pub difficulty: Option<Difficulty>,
+    pub category: Option<String>,
+    pub question_type: Option<QuestionType>,
+    pub page_size: Option<u64>,
+    pub page_index: Option<u64>,
+}
+
+/// request schema for bulk import of bank questions.
+/// `content` is either a JSON array of bank questions or a CSV file with a header row.
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQuestionsReqBody {
+    #[serde(default)]
+    pub format: QuestionFileFormat,
+    #[validate(length(min = 1))]
+    pub content: String,
+}
+
+/// request schema for attaching bank questions to a contest
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachQuestionsReqBody {
+    #[validate(length(min = 1))]
+    pub contest_id: String,
+    #[validate(length(min = 1, max = 100))]
+    pub bank_question_ids: Vec<String>,
+    #[serde(default)]
+    pub mode: AttachMode,
+}
+
+/// request params for export of contest questions
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportQuestionsParams {
+    pub contest_id: String,
+    pub format: Option<QuestionFileFormat>,
+}
+
 /// request params for get notifications
 #[derive(Debug, Serialize, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -401,6 +647,10 @@ pub struct AddBalEndReq {
     pub is_successful: bool,
     pub error_reason: Option<String>,
     pub tracking_id: Option<String>,
+    /// payment id returned by the gateway checkout
+    pub payment_id: Option<String>,
+    /// gateway signature of the order id and the payment id
+    pub signature: Option<String>,
 }
 
 /// request schema for Withdraw balance init request
@@ -413,16 +663,84 @@ pub struct WithdrawBalInitReq {
     pub receiver_upi_id: String,
 }
 
-/// request schema for Withdraw balance finalize
+/// request params for admin withdrawal list
+#[derive(Debug, Serialize, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct GetWithdrawalsParams {
+    /// defaults to PENDING
+    pub status: Option<WalletTransactionStatus>,
+    pub user_id: Option<u32>,
+    /// at most 100, defaults to 100
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: Option<u64>,
+    #[validate(range(max = 10000))]
+    pub page_index: Option<u64>,
+}
+
+/// request params for wallet transaction history
+#[derive(Debug, Default, Serialize, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionsParams {
+    pub transaction_type: Option<WalltetTransactionType>,
+    pub status: Option<WalletTransactionStatus>,
+    /// transactions created at or after this epoch second
+    pub from_ts: Option<u64>,
+    /// transactions created at or before this epoch second
+    pub to_ts: Option<u64>,
+    /// `nextCursor` returned by the previous page
+    pub cursor: Option<String>,
+    /// at most 100, defaults to 100
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: Option<u64>,
+}
+
+/// request params for admin wallet transaction history
+#[derive(Debug, Serialize, Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminGetTransactionsParams {
+    pub user_id: u32,
+    pub transaction_type: Option<WalltetTransactionType>,
+    pub status: Option<WalletTransactionStatus>,
+    /// transactions created at or after this epoch second
+    pub from_ts: Option<u64>,
+    /// transactions created at or before this epoch second
+    pub to_ts: Option<u64>,
+    /// `nextCursor` returned by the previous page
+    pub cursor: Option<String>,
+    /// at most 100, defaults to 100
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: Option<u64>,
+}
+
+impl From<AdminGetTransactionsParams> for GetTransactionsParams {
+    fn from(params: AdminGetTransactionsParams) -> Self {
+        Self {
+            transaction_type: params.transaction_type,
+            status: params.status,
+            from_ts: params.from_ts,
+            to_ts: params.to_ts,
+            cursor: params.cursor,
+            page_size: params.page_size,
+        }
+    }
+}
+
+/// request schema for approve withdrawal
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct WithdrawBalEndReq {
-    #[validate(range(min = "WITHDRAW_BAL_MIN_AMOUNT"))]
-    pub amount: u64,
+pub struct WithdrawalIdReq {
+    #[validate(length(min = 1))]
     pub transaction_id: String,
-    pub is_successful: bool,
-    pub error_reason: Option<String>,
-    pub tracking_id: Option<String>,
+}
+
+/// request schema for reject withdrawal
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectWithdrawalReq {
+    #[validate(length(min = 1))]
+    pub transaction_id: String,
+    #[validate(length(min = 1))]
+    pub reason: String,
 }
 
 /// request schema for pay contest request
@@ -440,8 +758,13 @@ pub struct AnswerPlayTrackerReqBody {
     pub contest_id: String,
     #[validate(range(min = 1))]
     pub question_no: u32,
-    #[validate(range(min = 1, max = 4))]
-    pub selected_option_id: u32,
+    /// answer of single choice, true/false and image choice questions
+    #[validate(range(min = 1, max = 10))]
+    pub selected_option_id: Option<u32>,
+    /// answer of multi-select questions
+    pub selected_option_ids: Option<Vec<u32>>,
+    /// answer of numeric questions
+    pub numeric_answer: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
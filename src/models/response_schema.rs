@@ -4,8 +4,9 @@ use serde_json::json;
 use utoipa::ToSchema;
 
 use super::{
-    AdminUser, ClipRespData, Contest, LeaderboardData, Money, MovieDetails, MovieRespData,
-    Notifications, PlayTracker, Question, QuestionWithoutCorrectFlag, UploadPartCompleted, User,
+    AdminUser, BankQuestion, ClipRespData, Contest, ContestTemplate, LeaderboardData, Money,
+    MovieDetails, MovieRespData, Notifications, PlayTracker, PlayTrackerStatus, Question,
+    QuestionWithoutCorrectFlag, UploadPartCompleted, User, WalletTransaction,
 };
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -46,6 +47,20 @@ pub struct AdminLoginResponse {
     pub token: String,
 }
 
+/// response schema for an admin user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUserResponse {
+    pub success: bool,
+    pub data: AdminUser,
+}
+
+/// response schema for the list of admin users
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUsersResponse {
+    pub success: bool,
+    pub data: Vec<AdminUser>,
+}
+
 /// response schema for get clip
 #[derive(Debug, Serialize, ToSchema)]
 pub struct GetClipResponse {
@@ -133,6 +148,20 @@ pub struct GetContestResponse {
     pub data: Vec<Contest>,
 }
 
+/// response schema for save contest template
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ContestTemplateResponse {
+    pub success: bool,
+    pub data: ContestTemplate,
+}
+
+/// response schema for get contest templates
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GetContestTemplatesResponse {
+    pub success: bool,
+    pub data: Vec<ContestTemplate>,
+}
+
 /// response schema for get question
 #[derive(Debug, Serialize, ToSchema)]
 pub struct GetQuestionResponse {
@@ -140,6 +169,37 @@ pub struct GetQuestionResponse {
     pub data: Option<Vec<Question>>,
 }
 
+/// response schema for create/update bank question
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BankQuestionResponse {
+    pub success: bool,
+    pub data: BankQuestion,
+}
+
+/// response schema for get bank questions
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GetBankQuestionsResponse {
+    pub success: bool,
+    pub data: Vec<BankQuestion>,
+}
+
+/// validation error of one row of the imported file, rows start from 1
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ImportRowError {
+    pub row: u32,
+    pub message: String,
+}
+
+/// response schema for bulk import of bank questions.
+/// Nothing is imported when any row has an error.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportQuestionsResponse {
+    pub success: bool,
+    pub inserted_count: u32,
+    pub errors: Vec<ImportRowError>,
+}
+
 /// response schema for get notification
 #[derive(Debug, Serialize, ToSchema)]
 pub struct GetNotiResp {
@@ -147,6 +207,15 @@ pub struct GetNotiResp {
     pub data: Vec<Notifications>,
 }
 
+/// data of the notification stream events
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotiStreamData {
+    pub unread_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Notifications>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GetBalResponse {
     pub success: bool,
@@ -168,6 +237,26 @@ pub struct AddBalInitRes {
     pub success: bool,
     pub transaction_id: String,
     pub app_upi_id: String,
+    pub payment_gateway: String,
+    pub order_id: String,
+}
+
+/// response schema for admin withdrawal list
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GetWithdrawalsResponse {
+    pub success: bool,
+    pub data: Vec<WalletTransaction>,
+}
+
+/// response schema for wallet transaction history
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTransactionsResponse {
+    pub success: bool,
+    pub data: Vec<WalletTransaction>,
+    /// cursor of the next page, absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// response schema for withdraw init
@@ -219,3 +308,61 @@ pub struct FileUploadRes {
     pub e_tag: String,
     pub url: String,
 }
+
+/// player who joined a contest
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestPlayer {
+    pub user_id: u32,
+    pub name: Option<String>,
+    pub status: PlayTrackerStatus,
+    pub joined_ts: Option<u64>,
+    pub paid_ts: Option<u64>,
+}
+
+/// response schema for get contest players
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestPlayersResponse {
+    pub success: bool,
+    pub invite_code: Option<String>,
+    pub joined_count: u32,
+    pub max_players: Option<u32>,
+    pub data: Vec<ContestPlayer>,
+}
+
+/// rank of a player in a contest, the prize is set only for the winners of an ended contest
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestRankEntry {
+    pub rank: Option<u32>,
+    pub user_id: u32,
+    pub name: Option<String>,
+    pub score: i32,
+    pub total_answered: u32,
+    pub time_taken: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prize: Option<Money>,
+}
+
+/// response schema for contest leaderboard, `isFinal` is false while the contest is live
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestLeaderboardResponse {
+    pub success: bool,
+    pub is_final: bool,
+    pub total_players: u32,
+    pub my_rank: Option<ContestRankEntry>,
+    pub data: Vec<ContestRankEntry>,
+}
+
+/// response schema for contest results
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestResultsResponse {
+    pub success: bool,
+    pub total_players: u32,
+    pub winners: Vec<ContestRankEntry>,
+    pub my_result: Option<ContestRankEntry>,
+    pub data: Vec<ContestRankEntry>,
+}
@@ -141,6 +141,49 @@ impl LeaderboardData {
     }
 }
 
+/// Role of an admin user which decides the permissions of the admin
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[allow(non_camel_case_types)]
+pub enum AdminRole {
+    SUPER_ADMIN,
+    CONTENT_EDITOR,
+    FINANCE,
+    SUPPORT,
+}
+
+impl AdminRole {
+    pub fn to_bson(&self) -> anyhow::Result<Bson> {
+        let bson = mongodb::bson::to_bson(self)?;
+        Ok(bson)
+    }
+
+    pub fn has_permission(&self, permission: AdminPermission) -> bool {
+        use AdminPermission::*;
+        match self {
+            Self::SUPER_ADMIN => true,
+            Self::CONTENT_EDITOR => matches!(permission, CONTENT | BROADCASTS),
+            Self::FINANCE => matches!(permission, PAYOUTS | TRANSACTIONS | REFERRAL_CODES),
+            Self::SUPPORT => matches!(permission, TRANSACTIONS | BROADCASTS),
+        }
+    }
+}
+
+/// Permission required by the admin routes
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(non_camel_case_types)]
+pub enum AdminPermission {
+    /// contests, templates, questions, clips and movies
+    CONTENT,
+    BROADCASTS,
+    REFERRAL_CODES,
+    /// approve or reject the withdrawals
+    PAYOUTS,
+    /// view the wallet transactions and the withdrawals
+    TRANSACTIONS,
+    /// manage the admin accounts
+    ADMIN_USERS,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminUser {
@@ -148,4 +191,37 @@ pub struct AdminUser {
     pub name: String,
     pub phone: String,
     pub is_active: bool,
+    /// admins created before the roles have no permission until a super admin assigns a role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<AdminRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<u32>,
+}
+
+impl AdminUser {
+    pub fn new(
+        id: u32,
+        name: String,
+        phone: String,
+        role: AdminRole,
+        created_by: Option<u32>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            phone,
+            is_active: true,
+            role: Some(role),
+            created_ts: Some(get_epoch_ts()),
+            created_by,
+            updated_ts: None,
+            updated_by: None,
+        }
+    }
 }
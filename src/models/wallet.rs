@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use utoipa::ToSchema;
 
-use crate::{constants::*, utils::get_epoch_ts};
+use super::LedgerAccount;
+use crate::utils::{deserialize_helper, get_epoch_ts};
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, ToSchema)]
 pub struct Money {
@@ -88,12 +89,16 @@ pub struct Wallet {
     updated_by: Option<u32>,
 }
 impl Wallet {
+    pub fn user_id(&self) -> u32 {
+        self.user_id
+    }
+
     pub fn balance(&self) -> Money {
         self.balance
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WalltetTransactionType {
     #[default]
@@ -105,6 +110,7 @@ pub enum WalltetTransactionType {
     ReferralBonus,
     ReferrerBonus,
     RefundContestEntryFee,
+    WithdrawReversal,
 }
 
 impl WalltetTransactionType {
@@ -112,14 +118,45 @@ impl WalltetTransactionType {
         let bson = mongodb::bson::to_bson(self)?;
         Ok(bson)
     }
+
+    /// money moves out of the user wallet
+    pub fn is_debit(&self) -> bool {
+        matches!(self, Self::Withdraw | Self::PayForContest)
+    }
+
+    /// the real money amount also changes the withdrawable balance
+    pub fn updates_withdrawable(&self) -> bool {
+        matches!(
+            self,
+            Self::Withdraw | Self::WithdrawReversal | Self::ContestWin
+        )
+    }
+
+    /// system account on the other side of the ledger posting
+    pub fn counter_account(&self) -> LedgerAccount {
+        match self {
+            Self::AddBalance | Self::Withdraw | Self::WithdrawReversal => LedgerAccount::House,
+            Self::PayForContest | Self::ContestWin => LedgerAccount::PrizePool,
+            Self::SignupBonus | Self::ReferralBonus | Self::ReferrerBonus => {
+                LedgerAccount::BonusPool
+            }
+            Self::RefundContestEntryFee => LedgerAccount::Refunds,
+        }
+    }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum WalletTransactionStatus {
     #[default]
     Pending,
+    /// withdrawal approved by admin and waiting for payout
+    Approved,
+    /// payout of the withdrawal is in progress
+    Processing,
     Completed,
+    /// withdrawal rejected by admin
+    Rejected,
     Error,
 }
 
@@ -130,9 +167,13 @@ impl WalletTransactionStatus {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct WalletTransaction {
+    #[serde(rename = "_id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "deserialize_helper")]
+    _id: Option<String>,
     user_id: u32,
     transaction_type: WalltetTransactionType,
     amount: Money,
@@ -143,6 +184,11 @@ pub struct WalletTransaction {
     remarks: Option<String>,
     receiver_upi_id: Option<String>,
     error_reason: Option<String>,
+    payment_gateway: Option<String>,
+    gateway_order_id: Option<String>,
+    gateway_payment_id: Option<String>,
+    /// original transaction which is reversed by this transaction
+    reference_transaction_id: Option<String>,
     created_ts: Option<u64>,
     updated_ts: Option<u64>,
     created_by: Option<u32>,
@@ -166,6 +212,7 @@ impl WalletTransaction {
         user_id: u32,
         amount: Money,
         balance_before: Money,
+        balance_after: Money,
         receiver_upi_id: &str,
     ) -> Self {
         let ts = get_epoch_ts();
@@ -174,12 +221,34 @@ impl WalletTransaction {
         transaction.transaction_type = WalltetTransactionType::Withdraw;
         transaction.amount = amount;
         transaction.balance_before = balance_before;
+        transaction.balance_after = Some(balance_after);
         transaction.receiver_upi_id = Some(receiver_upi_id.to_string());
         transaction.created_ts = Some(ts);
         transaction.created_by = Some(user_id);
         transaction
     }
 
+    pub fn withdraw_reversal_trans(
+        withdrawal: &WalletTransaction,
+        balance_before: Money,
+        balance_after: Money,
+        reason: &str,
+        updated_by: u32,
+    ) -> Self {
+        let mut transaction = Self::default();
+        transaction.user_id = withdrawal.user_id;
+        transaction.transaction_type = WalltetTransactionType::WithdrawReversal;
+        transaction.amount = withdrawal.amount;
+        transaction.status = WalletTransactionStatus::Completed;
+        transaction.balance_before = balance_before;
+        transaction.balance_after = Some(balance_after);
+        transaction.remarks = Some(format!("withdrawal reversed: {}", reason));
+        transaction.reference_transaction_id = withdrawal._id.clone();
+        transaction.created_ts = Some(get_epoch_ts());
+        transaction.created_by = Some(updated_by);
+        transaction
+    }
+
     pub fn pay_for_contest_trans(
         user_id: u32,
         contest_id: &str,
@@ -244,6 +313,7 @@ impl WalletTransaction {
 
     pub fn referrer_bonus_trans(
         referrer_id: u32,
+        bonus: u64,
         balance_before: Money,
         balance_after: Money,
         user_id: u32,
@@ -251,11 +321,11 @@ impl WalletTransaction {
         let mut transaction = Self::default();
         transaction.user_id = referrer_id;
         transaction.transaction_type = WalltetTransactionType::ReferrerBonus;
-        transaction.amount = Money::new(0, REFERRER_BONUS);
+        transaction.amount = Money::new(0, bonus);
         transaction.status = WalletTransactionStatus::Completed;
         transaction.balance_before = balance_before;
         transaction.balance_after = Some(balance_after);
-        transaction.remarks = Some(format!("adding referrer bonus: {}", REFERRER_BONUS));
+        transaction.remarks = Some(format!("adding referrer bonus: {}", bonus));
         transaction.created_ts = Some(get_epoch_ts());
         transaction.created_by = Some(user_id);
         transaction
@@ -283,6 +353,14 @@ impl WalletTransaction {
         transaction
     }
 
+    pub fn id(&self) -> Option<&str> {
+        self._id.as_deref()
+    }
+
+    pub fn user_id(&self) -> u32 {
+        self.user_id
+    }
+
     pub fn amount(&self) -> Money {
         self.amount
     }
@@ -290,4 +368,20 @@ impl WalletTransaction {
     pub fn balance_before(&self) -> Money {
         self.balance_before
     }
+
+    pub fn gateway_order_id(&self) -> Option<&str> {
+        self.gateway_order_id.as_deref()
+    }
+
+    pub fn receiver_upi_id(&self) -> Option<&str> {
+        self.receiver_upi_id.as_deref()
+    }
+
+    pub fn status(&self) -> &WalletTransactionStatus {
+        &self.status
+    }
+
+    pub fn reference_transaction_id(&self) -> Option<&str> {
+        self.reference_transaction_id.as_deref()
+    }
 }
@@ -0,0 +1,74 @@
+use axum::async_trait;
+
+use super::{hmac_sha256_hex, verify_hmac_sha256, PaymentEvent, PaymentGateway, PaymentOrder};
+use crate::constants::*;
+
+const MOCK_GATEWAY_SECRET: &str = "mock_gateway_secret";
+
+/// Local gateway which does not talk to any payment provider.
+/// Signatures use the same HMAC scheme with a well known secret, so it must never be used in production.
+pub struct MockPaymentGateway {
+    secret: String,
+}
+
+impl Default for MockPaymentGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockPaymentGateway {
+    pub fn new() -> Self {
+        Self {
+            secret: MOCK_GATEWAY_SECRET.to_string(),
+        }
+    }
+
+    /// signature the client should send after a successful checkout
+    pub fn payment_signature(&self, order_id: &str, payment_id: &str) -> String {
+        let message = format!("{}|{}", order_id, payment_id);
+        hmac_sha256_hex(&self.secret, message.as_bytes())
+    }
+
+    /// signature header value for a webhook payload
+    pub fn webhook_signature(&self, payload: &[u8]) -> String {
+        hmac_sha256_hex(&self.secret, payload)
+    }
+}
+
+#[async_trait]
+impl PaymentGateway for MockPaymentGateway {
+    fn name(&self) -> &'static str {
+        "MOCK"
+    }
+
+    async fn create_order(&self, receipt: &str, amount: u64) -> anyhow::Result<PaymentOrder> {
+        let order = PaymentOrder {
+            order_id: format!("order_{}", receipt),
+            amount,
+            currency: PAYMENT_CURRENCY.to_string(),
+        };
+        Ok(order)
+    }
+
+    fn verify_payment(
+        &self,
+        order_id: &str,
+        payment_id: &str,
+        signature: &str,
+    ) -> anyhow::Result<()> {
+        let message = format!("{}|{}", order_id, payment_id);
+        verify_hmac_sha256(&self.secret, message.as_bytes(), signature)
+    }
+
+    /// the mock webhook payload is the json encoded `PaymentEvent`
+    fn parse_webhook(
+        &self,
+        payload: &[u8],
+        signature: &str,
+    ) -> anyhow::Result<Option<PaymentEvent>> {
+        verify_hmac_sha256(&self.secret, payload, signature)?;
+        let event = serde_json::from_slice::<PaymentEvent>(payload)?;
+        Ok(Some(event))
+    }
+}
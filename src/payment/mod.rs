@@ -0,0 +1,112 @@
+mod mock;
+mod payout;
+mod razorpay;
+
+use axum::async_trait;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+
+pub use mock::MockPaymentGateway;
+pub use payout::{
+    payout_provider_from_env, MockPayoutProvider, PayoutProvider, PayoutRequest, PayoutResult,
+    RazorpayXPayoutProvider,
+};
+pub use razorpay::RazorpayGateway;
+
+/// Order created with the payment gateway for a wallet top-up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentOrder {
+    pub order_id: String,
+    pub amount: u64,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PaymentStatus {
+    Captured,
+    Failed,
+}
+
+/// Payment notification received through the gateway webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentEvent {
+    pub order_id: String,
+    pub payment_id: String,
+    pub amount: u64,
+    pub status: PaymentStatus,
+    pub error_reason: Option<String>,
+}
+
+/// Payment provider used for adding balance to the wallet.
+/// Amounts are always in rupees, conversion to the gateway unit is done by the implementation.
+#[async_trait]
+pub trait PaymentGateway: Send + Sync {
+    /// name of the gateway stored with the wallet transaction
+    fn name(&self) -> &'static str;
+
+    /// create an order for the amount, `receipt` is the wallet transaction id
+    async fn create_order(&self, receipt: &str, amount: u64) -> anyhow::Result<PaymentOrder>;
+
+    /// verify the signature returned to the client after a successful checkout
+    fn verify_payment(
+        &self,
+        order_id: &str,
+        payment_id: &str,
+        signature: &str,
+    ) -> anyhow::Result<()>;
+
+    /// verify the webhook signature and parse the payload.
+    /// Returns `None` for the events which are not relevant for wallet top-ups.
+    fn parse_webhook(
+        &self,
+        payload: &[u8],
+        signature: &str,
+    ) -> anyhow::Result<Option<PaymentEvent>>;
+}
+
+/// Create the payment gateway of the given name,
+/// the credentials of the provider are read from the env variables
+pub fn payment_gateway_from_env(gateway: &str) -> anyhow::Result<Arc<dyn PaymentGateway>> {
+    match gateway {
+        "mock" => Ok(Arc::new(MockPaymentGateway::new())),
+        "" | "razorpay" => Ok(Arc::new(RazorpayGateway::from_env()?)),
+        _ => Err(anyhow::anyhow!("Unknown PAYMENT_GATEWAY: {}", gateway)),
+    }
+}
+
+/// hex encoded HMAC-SHA256 signature of the message
+pub fn hmac_sha256_hex(secret: &str, message: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(message);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// verify the hex encoded HMAC-SHA256 signature in constant time
+pub fn verify_hmac_sha256(secret: &str, message: &[u8], signature: &str) -> anyhow::Result<()> {
+    let signature = hex::decode(signature)
+        .map_err(|_| anyhow::anyhow!("payment signature is not valid hex"))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(message);
+    mac.verify_slice(&signature)
+        .map_err(|_| anyhow::anyhow!("payment signature verification failed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_hmac_sha256() {
+        let signature = hmac_sha256_hex("secret", b"order_1|pay_1");
+        assert!(verify_hmac_sha256("secret", b"order_1|pay_1", &signature).is_ok());
+        assert!(verify_hmac_sha256("secret", b"order_1|pay_2", &signature).is_err());
+        assert!(verify_hmac_sha256("other", b"order_1|pay_1", &signature).is_err());
+        assert!(verify_hmac_sha256("secret", b"order_1|pay_1", "not hex").is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use axum::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::constants::*;
+
+/// Payout of an approved withdrawal to the user's UPI id
+#[derive(Debug, Clone)]
+pub struct PayoutRequest {
+    pub transaction_id: String,
+    pub user_id: u32,
+    pub amount: u64,
+    pub upi_id: String,
+}
+
+/// Outcome of a payout as known from the provider
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayoutResult {
+    /// the provider accepted the payout, with the provider reference of the payout
+    Paid(String),
+    /// the provider definitively refused the payout, the money is not transferred
+    Rejected(String),
+    /// the outcome is not known, e.g. timeout or server error; the payout may still go through
+    Unknown(String),
+}
+
+/// Provider which transfers the withdrawn money to the user
+#[async_trait]
+pub trait PayoutProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// transfer the amount and return the outcome of the payout.
+    /// The transaction id is used as idempotency key, so a retried payout is not paid twice
+    /// and retrying a payout of unknown outcome returns the state of the original payout.
+    async fn payout(&self, request: &PayoutRequest) -> PayoutResult;
+}
+
+/// Create the payout provider of the given name,
+/// the credentials of the provider are read from the env variables
+pub fn payout_provider_from_env(provider: &str) -> anyhow::Result<Arc<dyn PayoutProvider>> {
+    match provider {
+        "mock" => Ok(Arc::new(MockPayoutProvider::new())),
+        "" | "razorpayx" => Ok(Arc::new(RazorpayXPayoutProvider::from_env()?)),
+        _ => Err(anyhow::anyhow!("Unknown PAYOUT_PROVIDER: {}", provider)),
+    }
+}
+
+/// Payouts through the RazorpayX composite payout api
+pub struct RazorpayXPayoutProvider {
+    api_url: String,
+    key_id: String,
+    key_secret: String,
+    account_number: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PayoutResponse {
+    id: String,
+    status: String,
+}
+
+impl RazorpayXPayoutProvider {
+    /// Read the api keys and the payout account number from environment
+    pub fn from_env() -> anyhow::Result<Self> {
+        let read = |key: &str| {
+            std::env::var(key).map_err(|_| anyhow::anyhow!("{} not found in environment", key))
+        };
+        let provider = Self {
+            api_url: std::env::var("RAZORPAY_API_URL").unwrap_or(RAZORPAY_API_URL.to_string()),
+            key_id: read("RAZORPAY_KEY_ID")?,
+            key_secret: read("RAZORPAY_KEY_SECRET")?,
+            account_number: read("RAZORPAYX_ACCOUNT_NUMBER")?,
+        };
+        Ok(provider)
+    }
+}
+
+#[async_trait]
+impl PayoutProvider for RazorpayXPayoutProvider {
+    fn name(&self) -> &'static str {
+        "RAZORPAYX"
+    }
+
+    async fn payout(&self, request: &PayoutRequest) -> PayoutResult {
+        let url = format!("{}/payouts", self.api_url);
+        let body = json!({
+            "account_number": &self.account_number,
+            "amount": request.amount * 100,
+            "currency": PAYMENT_CURRENCY,
+            "mode": "UPI",
+            "purpose": "payout",
+            "queue_if_low_balance": true,
+            "reference_id": &request.transaction_id,
+            "fund_account": {
+                "account_type": "vpa",
+                "vpa": {"address": &request.upi_id},
+                "contact": {
+                    "name": format!("user {}", request.user_id),
+                    "type": "customer",
+                    "reference_id": request.user_id.to_string()
+                }
+            }
+        });
+        let res = reqwest::Client::new()
+            .post(url)
+            .basic_auth(&self.key_id, Some(&self.key_secret))
+            .header("X-Payout-Idempotency", &request.transaction_id)
+            .json(&body)
+            .send()
+            .await;
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => return PayoutResult::Unknown(err.to_string()),
+        };
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            let msg = format!("{}: {}", status, body);
+            // only a validation error is a sure refusal, a conflict or rate limit of
+            // the idempotent request might hide a payout which already went through
+            if status == StatusCode::BAD_REQUEST || status == StatusCode::UNPROCESSABLE_ENTITY {
+                return PayoutResult::Rejected(msg);
+            }
+            return PayoutResult::Unknown(msg);
+        }
+        match res.json::<PayoutResponse>().await {
+            Ok(res) => res.into_result(),
+            Err(err) => PayoutResult::Unknown(err.to_string()),
+        }
+    }
+}
+
+impl PayoutResponse {
+    /// The payout is paid only once processed, a queued or pending payout is
+    /// rechecked later with the same idempotency key
+    fn into_result(self) -> PayoutResult {
+        match self.status.as_str() {
+            "processed" => PayoutResult::Paid(self.id),
+            "rejected" | "failed" | "cancelled" | "reversed" => {
+                PayoutResult::Rejected(format!("payout {} is {}", self.id, self.status))
+            }
+            _ => PayoutResult::Unknown(format!("payout {} is {}", self.id, self.status)),
+        }
+    }
+}
+
+/// Local payout provider which does not transfer any money
+pub struct MockPayoutProvider {
+    fail: bool,
+    unreachable: bool,
+}
+
+impl Default for MockPayoutProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockPayoutProvider {
+    pub fn new() -> Self {
+        Self {
+            fail: false,
+            unreachable: false,
+        }
+    }
+
+    /// provider which rejects every payout
+    pub fn failing() -> Self {
+        Self {
+            fail: true,
+            unreachable: false,
+        }
+    }
+
+    /// provider whose payouts time out, the outcome of every payout is unknown
+    pub fn unreachable() -> Self {
+        Self {
+            fail: false,
+            unreachable: true,
+        }
+    }
+}
+
+#[async_trait]
+impl PayoutProvider for MockPayoutProvider {
+    fn name(&self) -> &'static str {
+        "MOCK"
+    }
+
+    async fn payout(&self, request: &PayoutRequest) -> PayoutResult {
+        if self.unreachable {
+            return PayoutResult::Unknown("mock payout timed out".into());
+        }
+        if self.fail {
+            return PayoutResult::Rejected("mock payout failed".into());
+        }
+        PayoutResult::Paid(format!("payout_{}", request.transaction_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payout_response_result() {
+        let result = |status: &str| {
+            let res = PayoutResponse {
+                id: "pout_1".to_string(),
+                status: status.to_string(),
+            };
+            res.into_result()
+        };
+        assert_eq!(result("processed"), PayoutResult::Paid("pout_1".into()));
+        assert!(matches!(result("rejected"), PayoutResult::Rejected(_)));
+        assert!(matches!(result("reversed"), PayoutResult::Rejected(_)));
+        assert!(matches!(result("queued"), PayoutResult::Unknown(_)));
+        assert!(matches!(result("pending"), PayoutResult::Unknown(_)));
+        assert!(matches!(result("processing"), PayoutResult::Unknown(_)));
+    }
+}
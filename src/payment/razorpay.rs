@@ -0,0 +1,184 @@
+use axum::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    hmac_sha256_hex, verify_hmac_sha256, PaymentEvent, PaymentGateway, PaymentOrder, PaymentStatus,
+};
+use crate::constants::*;
+
+/// Razorpay gateway. Orders are created with the REST api and payments are
+/// verified with the HMAC-SHA256 signatures sent by checkout and webhooks.
+pub struct RazorpayGateway {
+    api_url: String,
+    key_id: String,
+    key_secret: String,
+    webhook_secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderResponse {
+    id: String,
+    amount: u64,
+    currency: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookBody {
+    event: String,
+    payload: WebhookPayload,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    payment: WebhookPayment,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookPayment {
+    entity: PaymentEntity,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaymentEntity {
+    id: String,
+    order_id: Option<String>,
+    amount: u64,
+    error_description: Option<String>,
+}
+
+impl RazorpayGateway {
+    pub fn new(key_id: &str, key_secret: &str, webhook_secret: &str) -> Self {
+        Self {
+            api_url: RAZORPAY_API_URL.to_string(),
+            key_id: key_id.to_string(),
+            key_secret: key_secret.to_string(),
+            webhook_secret: webhook_secret.to_string(),
+        }
+    }
+
+    /// Read the api keys from environment
+    pub fn from_env() -> anyhow::Result<Self> {
+        let read = |key: &str| {
+            std::env::var(key).map_err(|_| anyhow::anyhow!("{} not found in environment", key))
+        };
+        let mut gateway = Self::new(
+            &read("RAZORPAY_KEY_ID")?,
+            &read("RAZORPAY_KEY_SECRET")?,
+            &read("RAZORPAY_WEBHOOK_SECRET")?,
+        );
+        if let Ok(api_url) = std::env::var("RAZORPAY_API_URL") {
+            gateway.api_url = api_url;
+        }
+        Ok(gateway)
+    }
+
+    /// signature of a successful checkout as computed by razorpay
+    pub fn payment_signature(&self, order_id: &str, payment_id: &str) -> String {
+        let message = format!("{}|{}", order_id, payment_id);
+        hmac_sha256_hex(&self.key_secret, message.as_bytes())
+    }
+}
+
+#[async_trait]
+impl PaymentGateway for RazorpayGateway {
+    fn name(&self) -> &'static str {
+        "RAZORPAY"
+    }
+
+    async fn create_order(&self, receipt: &str, amount: u64) -> anyhow::Result<PaymentOrder> {
+        let url = format!("{}/orders", self.api_url);
+        let body = json!({
+            "amount": amount * 100,
+            "currency": PAYMENT_CURRENCY,
+            "receipt": receipt,
+        });
+        let res = reqwest::Client::new()
+            .post(url)
+            .basic_auth(&self.key_id, Some(&self.key_secret))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<OrderResponse>()
+            .await?;
+        let order = PaymentOrder {
+            order_id: res.id,
+            amount: res.amount / 100,
+            currency: res.currency,
+        };
+        Ok(order)
+    }
+
+    fn verify_payment(
+        &self,
+        order_id: &str,
+        payment_id: &str,
+        signature: &str,
+    ) -> anyhow::Result<()> {
+        let message = format!("{}|{}", order_id, payment_id);
+        verify_hmac_sha256(&self.key_secret, message.as_bytes(), signature)
+    }
+
+    fn parse_webhook(
+        &self,
+        payload: &[u8],
+        signature: &str,
+    ) -> anyhow::Result<Option<PaymentEvent>> {
+        verify_hmac_sha256(&self.webhook_secret, payload, signature)?;
+        let body = serde_json::from_slice::<WebhookBody>(payload)?;
+        let status = match body.event.as_str() {
+            "payment.captured" => PaymentStatus::Captured,
+            "payment.failed" => PaymentStatus::Failed,
+            _ => return Ok(None),
+        };
+        let payment = body.payload.payment.entity;
+        let Some(order_id) = payment.order_id else {
+            return Ok(None);
+        };
+        let event = PaymentEvent {
+            order_id,
+            payment_id: payment.id,
+            amount: payment.amount / 100,
+            status,
+            error_reason: payment.error_description,
+        };
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_razorpay_signatures() {
+        let gateway = RazorpayGateway::new("key", "key_secret", "webhook_secret");
+        let signature = gateway.payment_signature("order_1", "pay_1");
+        assert!(gateway
+            .verify_payment("order_1", "pay_1", &signature)
+            .is_ok());
+        assert!(gateway
+            .verify_payment("order_2", "pay_1", &signature)
+            .is_err());
+
+        let payload = json!({
+            "event": "payment.captured",
+            "payload": {"payment": {"entity": {"id": "pay_1", "order_id": "order_1", "amount": 50000}}}
+        })
+        .to_string();
+        let signature = hmac_sha256_hex("webhook_secret", payload.as_bytes());
+        let event = gateway
+            .parse_webhook(payload.as_bytes(), &signature)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.order_id, "order_1");
+        assert_eq!(event.amount, 500);
+        assert_eq!(event.status, PaymentStatus::Captured);
+        // signed with the checkout secret instead of the webhook secret
+        let signature = hmac_sha256_hex("key_secret", payload.as_bytes());
+        assert!(gateway
+            .parse_webhook(payload.as_bytes(), &signature)
+            .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use axum::async_trait;
+use serde_json::{json, Value as JsonValue};
+use std::time::Duration;
+
+use super::SmsSender;
+use crate::constants::*;
+
+/// Request format of the sms provider api
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmsApiFormat {
+    /// MSG91 send sms api, json body with the `authkey` header
+    Msg91,
+    /// Twilio messages api, form body with the account sid and auth token as basic auth
+    Twilio,
+}
+
+/// Sms sent through the http api of MSG91 or Twilio
+pub struct HttpSmsSender {
+    client: reqwest::Client,
+    format: SmsApiFormat,
+    api_url: String,
+    api_key: String,
+    account_sid: String,
+    sender_id: String,
+    country_code: String,
+}
+
+impl HttpSmsSender {
+    /// Read the api key, sender id and the account sid of Twilio from environment
+    pub fn from_env(format: SmsApiFormat) -> anyhow::Result<Self> {
+        let read = |key: &str| {
+            std::env::var(key).map_err(|_| anyhow::anyhow!("{} not found in environment", key))
+        };
+        let (default_api_url, account_sid) = match format {
+            SmsApiFormat::Msg91 => (MSG91_SMS_API_URL, String::new()),
+            SmsApiFormat::Twilio => (TWILIO_SMS_API_URL, read("SMS_ACCOUNT_SID")?),
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SMS_REQUEST_TIMEOUT_SECS))
+            .build()?;
+        let sender = Self {
+            client,
+            format,
+            api_url: std::env::var("SMS_API_URL").unwrap_or(default_api_url.to_string()),
+            api_key: read("SMS_API_KEY")?,
+            account_sid,
+            sender_id: std::env::var("SMS_SENDER_ID").unwrap_or(SMS_SENDER_ID.to_string()),
+            country_code: std::env::var("SMS_COUNTRY_CODE").unwrap_or(SMS_COUNTRY_CODE.to_string()),
+        };
+        Ok(sender)
+    }
+
+    fn request(&self, phone: &str, message: &str) -> reqwest::RequestBuilder {
+        match self.format {
+            SmsApiFormat::Msg91 => {
+                let body = json!({
+                    "sender": &self.sender_id,
+                    "route": "4",
+                    "country": &self.country_code,
+                    "sms": [{"message": message, "to": [phone]}]
+                });
+                self.client
+                    .post(&self.api_url)
+                    .header("authkey", &self.api_key)
+                    .json(&body)
+            }
+            SmsApiFormat::Twilio => {
+                let url = format!(
+                    "{}/Accounts/{}/Messages.json",
+                    self.api_url, self.account_sid
+                );
+                let to = format!("+{}{}", self.country_code, phone);
+                let form = [
+                    ("To", to.as_str()),
+                    ("From", self.sender_id.as_str()),
+                    ("Body", message),
+                ];
+                self.client
+                    .post(url)
+                    .basic_auth(&self.account_sid, Some(&self.api_key))
+                    .form(&form)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SmsSender for HttpSmsSender {
+    fn name(&self) -> &'static str {
+        match self.format {
+            SmsApiFormat::Msg91 => "MSG91",
+            SmsApiFormat::Twilio => "TWILIO",
+        }
+    }
+
+    async fn send(&self, phone: &str, message: &str) -> anyhow::Result<Option<String>> {
+        let res = self
+            .request(phone, message)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JsonValue>()
+            .await
+            .unwrap_or_default();
+        let reference = match self.format {
+            // the request id is returned as message on success
+            SmsApiFormat::Msg91 => {
+                if res.get("type").and_then(|v| v.as_str()) == Some("error") {
+                    return Err(anyhow::anyhow!("sms not sent: {}", res));
+                }
+                res.get("message")
+            }
+            SmsApiFormat::Twilio => res.get("sid"),
+        };
+        Ok(reference.and_then(|v| v.as_str()).map(|v| v.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sender(format: SmsApiFormat) -> HttpSmsSender {
+        HttpSmsSender {
+            client: reqwest::Client::new(),
+            format,
+            api_url: "https://sms.test".to_string(),
+            api_key: "key".to_string(),
+            account_sid: "AC1".to_string(),
+            sender_id: "+15550001111".to_string(),
+            country_code: "91".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sms_request_format() {
+        let req = sender(SmsApiFormat::Msg91)
+            .request("9876543210", "otp 1234")
+            .build()
+            .unwrap();
+        assert_eq!(req.url().as_str(), "https://sms.test/");
+        assert_eq!(req.headers()["authkey"], "key");
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap();
+        let body = serde_json::from_slice::<JsonValue>(body).unwrap();
+        assert_eq!(body["sms"][0]["to"][0], "9876543210");
+        assert_eq!(body["country"], "91");
+
+        let req = sender(SmsApiFormat::Twilio)
+            .request("9876543210", "otp 1234")
+            .build()
+            .unwrap();
+        assert_eq!(
+            req.url().as_str(),
+            "https://sms.test/Accounts/AC1/Messages.json"
+        );
+        assert!(req.headers()["authorization"]
+            .to_str()
+            .unwrap()
+            .starts_with("Basic "));
+        let body = req.body().and_then(|b| b.as_bytes()).unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body, "To=%2B919876543210&From=%2B15550001111&Body=otp+1234");
+    }
+}
@@ -0,0 +1,41 @@
+use axum::async_trait;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+use super::SmsSender;
+use crate::utils::get_epoch_ts;
+
+/// Local sms sink for development, the messages are logged
+/// and appended to the `SMS_LOG_FILE` when it is set
+#[derive(Default)]
+pub struct LogSmsSender {
+    file: Option<PathBuf>,
+}
+
+impl LogSmsSender {
+    pub fn from_env() -> Self {
+        let file = std::env::var("SMS_LOG_FILE").ok().map(PathBuf::from);
+        Self { file }
+    }
+}
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    fn name(&self) -> &'static str {
+        "LOG"
+    }
+
+    async fn send(&self, phone: &str, message: &str) -> anyhow::Result<Option<String>> {
+        tracing::debug!("Send sms to phone {phone}: {message}");
+        if let Some(file) = &self.file {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file)
+                .await?;
+            let line = format!("{}\t{}\t{}\n", get_epoch_ts(), phone, message);
+            file.write_all(line.as_bytes()).await?;
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,29 @@
+mod http;
+mod log_sink;
+
+use axum::async_trait;
+use std::sync::Arc;
+
+pub use http::{HttpSmsSender, SmsApiFormat};
+pub use log_sink::LogSmsSender;
+
+/// Gateway used for sending the SMS messages, e.g. the login otp
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    /// name of the provider stored with the otp
+    fn name(&self) -> &'static str;
+
+    /// send the message to the phone and return the provider reference of the message if any
+    async fn send(&self, phone: &str, message: &str) -> anyhow::Result<Option<String>>;
+}
+
+/// Create the sms sender of the given name,
+/// the credentials of the provider are read from the env variables
+pub fn sms_sender_from_env(provider: &str) -> anyhow::Result<Arc<dyn SmsSender>> {
+    match provider {
+        "log" => Ok(Arc::new(LogSmsSender::from_env())),
+        "" | "http" | "msg91" => Ok(Arc::new(HttpSmsSender::from_env(SmsApiFormat::Msg91)?)),
+        "twilio" => Ok(Arc::new(HttpSmsSender::from_env(SmsApiFormat::Twilio)?)),
+        _ => Err(anyhow::anyhow!("Unknown SMS_PROVIDER: {}", provider)),
+    }
+}
@@ -0,0 +1,66 @@
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+use crate::{
+    config::AppConfig, database::AppDatabase, events::EventBus, payment::PaymentGateway,
+    sms::SmsSender,
+};
+
+/// State shared by all the request handlers.
+/// Handlers can extract any of the fields directly, e.g. `State<Arc<AppDatabase>>`.
+#[derive(Clone)]
+pub struct AppState {
+    pub config: Arc<AppConfig>,
+    pub db: Arc<AppDatabase>,
+    pub payment_gateway: Arc<dyn PaymentGateway>,
+    pub sms_sender: Arc<dyn SmsSender>,
+    pub events: Arc<EventBus>,
+}
+
+impl AppState {
+    pub fn new(
+        config: Arc<AppConfig>,
+        db: Arc<AppDatabase>,
+        payment_gateway: Arc<dyn PaymentGateway>,
+        sms_sender: Arc<dyn SmsSender>,
+        events: Arc<EventBus>,
+    ) -> Self {
+        Self {
+            config,
+            db,
+            payment_gateway,
+            sms_sender,
+            events,
+        }
+    }
+}
+
+impl FromRef<AppState> for Arc<AppConfig> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<AppDatabase> {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn PaymentGateway> {
+    fn from_ref(state: &AppState) -> Self {
+        state.payment_gateway.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn SmsSender> {
+    fn from_ref(state: &AppState) -> Self {
+        state.sms_sender.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<EventBus> {
+    fn from_ref(state: &AppState) -> Self {
+        state.events.clone()
+    }
+}
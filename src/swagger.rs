@@ -1,3 +1,4 @@
+use crate::config::AppConfig;
 use utoipa::{
     openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
     Modify, OpenApi,
@@ -8,8 +9,6 @@ use utoipa::{
     paths(
         crate::handlers::ping::ping_handler,
         crate::handlers::default::default_route_handler,
-        crate::handlers::temp_api::temp_api_get_token,
-        crate::handlers::temp_api::temp_api_get_otp,
         crate::handlers::user::create::create_user_handler,
         crate::handlers::user::login::login_handler,
         crate::handlers::user::verify::verify_user_handler,
@@ -21,6 +20,9 @@ use utoipa::{
         crate::handlers::user::update::update_user_handler,
         crate::handlers::user::referral::create_special_code_handler,
         crate::handlers::user::admin_login::admin_signup_handler,
+        crate::handlers::user::admin_users::get_admin_users_handler,
+        crate::handlers::user::admin_users::create_admin_user_handler,
+        crate::handlers::user::admin_users::update_admin_user_handler,
         crate::handlers::user::admin_login::admin_generate_otp,
         crate::handlers::user::admin_login::admin_login_handler,
         crate::handlers::clip::get_clip::get_clips_handler,
@@ -37,11 +39,30 @@ use utoipa::{
         crate::handlers::contest::get::get_contest_handler,
         crate::handlers::contest::activate::activate_contest_handler,
         crate::handlers::contest::activate::inactivate_contest_handler,
+        crate::handlers::contest::update::update_contest_handler,
+        crate::handlers::contest::clone::clone_contest_handler,
+        crate::handlers::contest::clone::save_template_handler,
+        crate::handlers::contest::clone::get_templates_handler,
+        crate::handlers::contest::clone::contest_from_template_handler,
+        crate::handlers::contest::schedule::schedule_template_handler,
+        crate::handlers::contest::private::get_invited_contest_handler,
+        crate::handlers::contest::private::join_contest_handler,
+        crate::handlers::contest::private::get_contest_players_handler,
+        crate::handlers::contest::leaderboard::get_contest_leaderboard_handler,
+        crate::handlers::contest::leaderboard::get_contest_results_handler,
+        crate::handlers::contest::events::contest_events_handler,
         crate::handlers::question::create::create_question_handler,
         crate::handlers::question::get::get_question_handler,
         crate::handlers::question::delete::delete_question_handler,
         crate::handlers::question::update::update_question_handler,
+        crate::handlers::question::export::export_questions_handler,
+        crate::handlers::question_bank::create::create_bank_question_handler,
+        crate::handlers::question_bank::create::update_bank_question_handler,
+        crate::handlers::question_bank::get::get_bank_questions_handler,
+        crate::handlers::question_bank::import::import_questions_handler,
+        crate::handlers::question_bank::attach::attach_questions_handler,
         crate::handlers::notification::get_noti::get_noti_handler,
+        crate::handlers::notification::stream_noti::stream_noti_handler,
         crate::handlers::notification::clear_noti::clear_noti_handler,
         crate::handlers::notification::clear_noti::clear_all_noti_handler,
         crate::handlers::notification::mark_read::mark_read_noti_handler,
@@ -52,11 +73,16 @@ use utoipa::{
         crate::handlers::upload::multipart::upload_part_multipart_handler,
         crate::handlers::upload::multipart::complete_multipart_handler,
         crate::handlers::wallet::get_bal::get_bal_handler,
+        crate::handlers::wallet::transactions::get_transactions_handler,
+        crate::handlers::wallet::transactions::admin_get_transactions_handler,
         crate::handlers::wallet::add_bal::add_bal_init_handler,
         crate::handlers::wallet::add_bal::add_bal_end_handler,
+        crate::handlers::wallet::payment_webhook::payment_webhook_handler,
         crate::handlers::wallet::withdraw_bal::withdraw_bal_init_handler,
-        crate::handlers::wallet::withdraw_bal::withdraw_bal_end_handler,
         crate::handlers::wallet::pay_contest::pay_contest_handler,
+        crate::handlers::wallet::withdrawals::get_withdrawals_handler,
+        crate::handlers::wallet::withdrawals::approve_withdrawal_handler,
+        crate::handlers::wallet::withdrawals::reject_withdrawal_handler,
         crate::handlers::play_tracker::get::get_play_tracker_handler,
         crate::handlers::play_tracker::start::start_play_tracker_handler,
         crate::handlers::play_tracker::start::get_next_ques_handler,
@@ -76,20 +102,31 @@ use utoipa::{
             crate::models::SpecialCodeReqBody,
             crate::models::UpdateUserReq,
             crate::models::AdminSignupRequest,
+            crate::models::AdminCreateRequest,
+            crate::models::AdminUpdateRequest,
             crate::models::CreateClipReqBody,
             crate::models::ClipAddViewReqBody,
             crate::models::CreateMovieReqBody,
             crate::models::MovieAddViewReqBody,
             crate::models::AddFavReqBody,
             crate::models::ContestIdRequest,
+            crate::models::UpdateContestReqBody,
+            crate::models::CopyContestReqBody,
+            crate::models::SaveTemplateReqBody,
+            crate::models::ScheduleTemplateReqBody,
+            crate::models::InviteCodeReq,
             crate::models::CreateQuesReqBody,
             crate::models::QuestionReqBody,
             crate::models::QuesDelReqBody,
+            crate::models::UpdateBankQuestionReqBody,
+            crate::models::ImportQuestionsReqBody,
+            crate::models::AttachQuestionsReqBody,
             crate::models::ClearNotiReq,
             crate::models::AddBalInitReq,
             crate::models::AddBalEndReq,
             crate::models::WithdrawBalInitReq,
-            crate::models::WithdrawBalEndReq,
+            crate::models::WithdrawalIdReq,
+            crate::models::RejectWithdrawalReq,
             crate::models::PayContestReqBody,
             crate::models::AnswerPlayTrackerReqBody,
             crate::models::CreateBroadcastReq,
@@ -100,6 +137,8 @@ use utoipa::{
             crate::models::LeaderboardResponse,
             crate::models::UpdateUserResponse,
             crate::models::AdminLoginResponse,
+            crate::models::AdminUserResponse,
+            crate::models::AdminUsersResponse,
             crate::models::GetClipResponse,
             crate::models::ClipResponse,
             crate::models::AddViewResponse,
@@ -108,11 +147,27 @@ use utoipa::{
             crate::models::MovieLikedResponse,
             crate::models::ContestResponse,
             crate::models::GetContestResponse,
+            crate::models::ContestTemplateResponse,
+            crate::models::GetContestTemplatesResponse,
+            crate::models::ContestPlayersResponse,
+            crate::models::ContestPlayer,
+            crate::models::ContestLeaderboardResponse,
+            crate::models::ContestResultsResponse,
+            crate::models::ContestRankEntry,
+            crate::events::ContestEvent,
+            crate::events::ContestEventType,
             crate::models::GetQuestionResponse,
+            crate::models::BankQuestionResponse,
+            crate::models::GetBankQuestionsResponse,
+            crate::models::ImportQuestionsResponse,
+            crate::models::ImportRowError,
             crate::models::GetNotiResp,
+            crate::models::NotiStreamData,
             crate::models::GetBalResponse,
             crate::models::AddBalInitRes,
             crate::models::WithdrawInitRes,
+            crate::models::GetWithdrawalsResponse,
+            crate::models::GetTransactionsResponse,
             crate::models::PlayTrackerResponse,
             crate::models::PlayTrackerQuesRes,
             crate::models::MultipartUploadInitiateRes,
@@ -122,8 +177,12 @@ use utoipa::{
 
             crate::models::User,
             crate::models::AdminUser,
+            crate::models::AdminRole,
             crate::models::LeaderboardData,
             crate::models::Money,
+            crate::models::WalletTransaction,
+            crate::models::WalltetTransactionType,
+            crate::models::WalletTransactionStatus,
             crate::models::WrapDocument,
             crate::models::ClipRespData,
             crate::models::ClipProps,
@@ -133,15 +192,30 @@ use utoipa::{
             crate::models::MovieRespData,
             crate::models::MovieDetails,
             crate::models::ContestProps,
+            crate::models::PrizeTier,
+            crate::models::TiePolicy,
+            crate::models::LateAnswerPolicy,
             crate::models::Contest,
+            crate::models::ContestTemplate,
+            crate::models::ContestSchedule,
+            crate::models::Recurrence,
             crate::models::QuestionProps,
             crate::models::Question,
             crate::models::QuestionWithoutCorrectFlag,
             crate::models::Answer,
             crate::models::AnswerProps,
+            crate::models::QuestionType,
+            crate::models::MultiSelectScoring,
+            crate::models::NumericAnswer,
+            crate::models::BankQuestionProps,
+            crate::models::BankQuestion,
+            crate::models::Difficulty,
+            crate::models::AttachMode,
+            crate::models::QuestionFileFormat,
             crate::models::Notifications,
             crate::models::PlayTracker,
             crate::models::ChosenAnswer,
+            crate::models::ServedQuestion,
 
             crate::models::SocialLoginScheme,
             crate::models::LoginScheme,
@@ -155,13 +229,37 @@ use utoipa::{
     ),
     modifiers(&SecurityAddon),
     tags(
-        (name = "Debugging API", description = "API for debugging purposes"),
+        (name = "Server API", description = "API for checking the server status"),
         (name = "App User API", description = "API for app user functionalities"),
         (name = "Admin API", description = "API for admin functionalities")
     )
 )]
 pub struct ApiDoc;
 
+#[cfg(feature = "debug-api")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::handlers::temp_api::temp_api_get_token,
+        crate::handlers::temp_api::temp_api_get_otp,
+    ),
+    tags(
+        (name = "Debugging API", description = "API for debugging purposes")
+    )
+)]
+pub struct DebugApiDoc;
+
+/// Api doc of the app, the debugging endpoints are listed only when they are served
+pub fn api_doc(config: &AppConfig) -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut api_doc = ApiDoc::openapi();
+    if config.debug_api_enabled() {
+        #[cfg(feature = "debug-api")]
+        api_doc.merge(DebugApiDoc::openapi());
+    }
+    api_doc
+}
+
 struct SecurityAddon;
 
 impl Modify for SecurityAddon {
@@ -174,3 +272,31 @@ impl Modify for SecurityAddon {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+
+    use super::*;
+    use crate::{config::Environment, utils::test_helper::*};
+
+    #[tokio::test]
+    async fn test_debug_api_gating() {
+        let has_debug_api = |config: &AppConfig| {
+            let paths = api_doc(config).paths.paths;
+            paths.contains_key("/api/v1/tempApiGetToken")
+        };
+        let mut config = test_config();
+        assert!(!has_debug_api(&config));
+        config.debug_api = true;
+        assert_eq!(has_debug_api(&config), cfg!(feature = "debug-api"));
+        config.environment = Environment::Production;
+        assert!(!has_debug_api(&config));
+
+        // the test app does not enable the debugging endpoints
+        let db = test_db();
+        let uri = "/api/v1/tempApiGetToken?userId=1";
+        let (status, _) = send_request(&db, Method::GET, uri, 1, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
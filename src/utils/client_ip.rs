@@ -0,0 +1,83 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use super::AppError;
+use crate::config::AppConfig;
+
+/// IP address of the client. The address of the connection is used unless it is one of the
+/// `trusted_proxies`, then the `X-Forwarded-For` header added by the proxies is used.
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    Arc<AppConfig>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Arc::<AppConfig>::from_ref(state);
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let forwarded = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok());
+        let ip = resolve_client_ip(peer, forwarded, &config.trusted_proxies);
+        Ok(Self(ip.map(|ip| ip.to_string())))
+    }
+}
+
+/// The `X-Forwarded-For` entries are read from the nearest proxy, the first entry which is
+/// not a trusted proxy is the client. The entries before it may be set by the client.
+fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    forwarded: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut client = peer?;
+    let Some(forwarded) = forwarded else {
+        return Some(client);
+    };
+    for entry in forwarded.rsplit(',') {
+        if !trusted_proxies.contains(&client) {
+            break;
+        }
+        let Ok(ip) = entry.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+    }
+    Some(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_client_ip() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+        // a direct client can not spoof the header
+        let client = resolve_client_ip(Some(ip("1.1.1.1")), Some("2.2.2.2"), &proxies);
+        assert_eq!(client, Some(ip("1.1.1.1")));
+        let forwarded = Some("3.3.3.3, 2.2.2.2, 10.0.0.2");
+        let client = resolve_client_ip(Some(ip("10.0.0.1")), forwarded, &proxies);
+        assert_eq!(client, Some(ip("2.2.2.2")));
+        let client = resolve_client_ip(Some(ip("10.0.0.1")), None, &proxies);
+        assert_eq!(client, Some(ip("10.0.0.1")));
+        assert_eq!(resolve_client_ip(None, Some("2.2.2.2"), &proxies), None);
+    }
+}
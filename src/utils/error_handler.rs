@@ -11,6 +11,8 @@ pub enum AppError {
     BadRequestErr(String),
     NotFound(String),
     Auth(String),
+    Forbidden(String),
+    TooManyRequests(String),
     AnyError(anyhow::Error),
 }
 
@@ -53,6 +55,22 @@ impl IntoResponse for AppError {
                 };
                 (StatusCode::UNAUTHORIZED, Json(response)).into_response()
             }
+            Self::Forbidden(msg) => {
+                tracing::debug!("Forbidden: {}", msg);
+                let response = GenericResponse {
+                    success: false,
+                    message: msg.to_owned(),
+                };
+                (StatusCode::FORBIDDEN, Json(response)).into_response()
+            }
+            Self::TooManyRequests(msg) => {
+                tracing::debug!("Too many requests: {}", msg);
+                let response = GenericResponse {
+                    success: false,
+                    message: msg.to_owned(),
+                };
+                (StatusCode::TOO_MANY_REQUESTS, Json(response)).into_response()
+            }
             Self::AnyError(err) => {
                 let msg = format!("Something went wrong: {err}");
                 tracing::debug!("{msg}");
@@ -9,7 +9,7 @@ use std::{
 };
 
 use super::AppError;
-use crate::{constants::*, jwt::JWT_KEYS};
+use crate::{config::AwsConfig, constants::*, jwt::JWT_KEYS};
 
 /// Get EPOCH timestamp in seconds
 pub fn get_epoch_ts() -> u64 {
@@ -65,6 +65,16 @@ pub fn generate_referral_code(id: u32, name: &str) -> String {
     code
 }
 
+/// Generate random upper case alphanumeric invite code of a private contest
+pub fn generate_invite_code() -> String {
+    (0..INVITE_CODE_LEN)
+        .map(|_| {
+            let n = get_random_num(0, 36);
+            char::from_digit(n, 36).unwrap_or('0').to_ascii_uppercase()
+        })
+        .collect()
+}
+
 /// Generate a random number in a given range
 /// panics if the lower bound is greater than the higher bound
 pub fn get_random_num<T>(low: T, high: T) -> T
@@ -85,9 +95,11 @@ pub fn get_user_id_from_token(headers: &HeaderMap) -> Option<u32> {
 }
 
 /// Returns S3 object url for a given key
-pub fn get_object_url(key: &str) -> String {
-    let region = std::env::var("AWS_REGION").unwrap_or(AWS_REGION.to_owned());
-    format!("https://{}.s3.{}.amazonaws.com/{}", AWS_BUCKET, region, key)
+pub fn get_object_url(aws: &AwsConfig, key: &str) -> String {
+    format!(
+        "https://{}.s3.{}.amazonaws.com/{}",
+        aws.bucket, aws.region, key
+    )
 }
 
 /// Parse the given value as ObjectId
@@ -1,8 +1,12 @@
+pub(crate) mod client_ip;
 pub(crate) mod error_handler;
 pub(crate) mod misc;
 pub(crate) mod sequence_generator;
+#[cfg(test)]
+pub(crate) mod test_helper;
 pub(crate) mod validation;
 
+pub(crate) use client_ip::ClientIp;
 pub(crate) use error_handler::AppError;
 pub(crate) use misc::*;
 pub(crate) use sequence_generator::get_seq_nxt_val;
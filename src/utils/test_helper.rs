@@ -0,0 +1,285 @@
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{TimeZone, Utc};
+use futures::FutureExt;
+use mongodb::bson::doc;
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use crate::{
+    app::build_app_routes,
+    config::AppConfig,
+    constants::*,
+    database::{AppDatabase, MemoryStorage},
+    events::EventBus,
+    handlers::wallet::helper::update_wallet_with_session,
+    jwt::JWT_KEYS,
+    models::*,
+    payment::MockPaymentGateway,
+    sms::LogSmsSender,
+    state::AppState,
+    utils::{get_epoch_ts, parse_object_id},
+};
+
+/// database with all the data kept in memory
+pub fn test_db() -> Arc<AppDatabase> {
+    let storage = MemoryStorage::new()
+        .with_unique_index(DB_NAME, COLL_WALLETS, &["userId"])
+        .with_unique_index(DB_NAME, COLL_PLAY_TRACKERS, &["contestId", "userId"]);
+    Arc::new(AppDatabase::with_storage(storage))
+}
+
+/// active contest props, started a minute ago and ending in an hour
+pub fn contest_props(entry_fee: u32, prize_real: u32) -> ContestProps {
+    let ts = get_epoch_ts() as i64;
+    ContestProps {
+        title: "Test Contest".to_string(),
+        category: ContestCategory::Movie,
+        movie_id: None,
+        sponsored_by: "Sponsor".to_string(),
+        sponsored_by_logo: None,
+        banner_image_url: "https://example.com/banner.png".to_string(),
+        video_url: "https://example.com/video.mp4".to_string(),
+        entry_fee,
+        entry_fee_max_bonus_money: 0,
+        prize_selection: PrizeSelection::TOP_WINNERS,
+        top_winners_count: Some(1),
+        prize_ratio_numerator: None,
+        prize_ratio_denominator: None,
+        prize_value_real_money: prize_real,
+        prize_value_bonus_money: 0,
+        prize_table: vec![],
+        tie_policy: TiePolicy::RANK_BY_TIME,
+        late_answer_policy: LateAnswerPolicy::SCORE_ZERO,
+        start_time: Utc.timestamp_opt(ts - 60, 0).unwrap(),
+        end_time: Utc.timestamp_opt(ts + 3600, 0).unwrap(),
+        min_required_players: 0,
+        time_budget: None,
+        negative_marks: None,
+        speed_bonus_points: None,
+        activate_at: None,
+        is_private: false,
+        max_players: None,
+    }
+}
+
+/// question with four options where option 1 is the correct one
+pub fn question(question_no: u32) -> Question {
+    let options = (1..=4)
+        .map(|option_id| Answer {
+            props: AnswerProps {
+                option_id,
+                option_text: format!("option {option_id}"),
+                has_video: false,
+                has_image: false,
+                image_or_video_url: None,
+            },
+            is_correct: option_id == 1,
+        })
+        .collect();
+    Question {
+        props: QuestionProps {
+            question_no,
+            question_text: format!("question {question_no}"),
+            has_video: false,
+            has_image: false,
+            image_or_video_url: None,
+            time_limit: None,
+            question_type: QuestionType::SINGLE_CHOICE,
+            multi_select_scoring: None,
+            points: None,
+            negative_marks: None,
+        },
+        options,
+        numeric_answer: None,
+        is_active: true,
+        bank_question_id: None,
+    }
+}
+
+/// insert a contest with the given number of questions and return the contest id
+pub async fn insert_contest(
+    db: &AppDatabase,
+    props: &ContestProps,
+    status: ContestStatus,
+    total_questions: u32,
+) -> String {
+    let mut contest = Contest::new(props, 1);
+    contest.status = status;
+    let contest_id = db
+        .insert_one::<Contest>(DB_NAME, COLL_CONTESTS, &contest, None)
+        .await
+        .unwrap();
+    let questions = (1..=total_questions)
+        .map(|no| question(no).to_bson().unwrap())
+        .collect::<Vec<_>>();
+    let filter = doc! {"_id": parse_object_id(&contest_id, "").unwrap()};
+    let update = doc! {"$set": {"questions": questions}};
+    db.update_one(DB_NAME, COLL_CONTESTS, filter, update, None)
+        .await
+        .unwrap();
+    contest_id
+}
+
+pub async fn get_contest(db: &AppDatabase, contest_id: &str) -> Contest {
+    let filter = doc! {"_id": parse_object_id(contest_id, "").unwrap()};
+    db.find_one::<Contest>(DB_NAME, COLL_CONTESTS, Some(filter), None)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// credit money to the user wallet
+pub async fn add_balance(db: &AppDatabase, user_id: u32, real: u64, bonus: u64) {
+    db.execute_transaction(None, None, |db, session| {
+        async move {
+            update_wallet_with_session(
+                db,
+                session,
+                user_id,
+                real,
+                bonus,
+                &WalltetTransactionType::AddBalance,
+            )
+            .await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .unwrap();
+}
+
+/// credit withdrawable real money to the user wallet, e.g. contest winnings
+pub async fn add_withdrawable_balance(db: &AppDatabase, user_id: u32, real: u64) {
+    db.execute_transaction(None, None, |db, session| {
+        async move {
+            update_wallet_with_session(
+                db,
+                session,
+                user_id,
+                real,
+                0,
+                &WalltetTransactionType::ContestWin,
+            )
+            .await?;
+            Ok(())
+        }
+        .boxed()
+    })
+    .await
+    .unwrap();
+}
+
+/// insert an active admin user
+/// super admin
+pub async fn insert_admin(db: &AppDatabase, id: u32) {
+    insert_admin_with_role(db, id, AdminRole::SUPER_ADMIN).await;
+}
+
+pub async fn insert_admin_with_role(db: &AppDatabase, id: u32, role: AdminRole) {
+    let phone = format!("{:010}", id);
+    let admin = AdminUser::new(id, format!("admin {id}"), phone, role, None);
+    db.insert_one::<AdminUser>(DB_NAME, COLL_ADMIN_USERS, &admin, None)
+        .await
+        .unwrap();
+}
+
+pub async fn get_balance(db: &AppDatabase, user_id: u32) -> Money {
+    let filter = doc! {"userId": user_id};
+    db.find_one::<Wallet>(DB_NAME, COLL_WALLETS, Some(filter), None)
+        .await
+        .unwrap()
+        .map(|w| w.balance())
+        .unwrap_or_default()
+}
+
+pub const BOOTSTRAP_ADMIN_PHONE: &str = "9999999999";
+
+/// config of the app used in the tests
+pub fn test_config() -> AppConfig {
+    AppConfig {
+        app_upi_id: Some("trailsbuddy@upi".to_string()),
+        bootstrap_admin_phone: Some(BOOTSTRAP_ADMIN_PHONE.to_string()),
+        ..AppConfig::default()
+    }
+}
+
+/// app routes with the mock payment gateway
+pub fn test_app(db: &Arc<AppDatabase>, events: Arc<EventBus>) -> Router {
+    let config = Arc::new(test_config());
+    let payment_gateway = Arc::new(MockPaymentGateway::new());
+    let sms_sender = Arc::new(LogSmsSender::default());
+    let state = AppState::new(config, db.clone(), payment_gateway, sms_sender, events);
+    build_app_routes(state)
+}
+
+/// send a request to the app with the user token and return the status and json body
+pub async fn send_request(
+    db: &Arc<AppDatabase>,
+    method: Method,
+    uri: &str,
+    user_id: u32,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let token = JWT_KEYS.generate_token(user_id, None).unwrap();
+    let (status, body) = send_raw_request(db, method, uri, &token, body).await;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, body)
+}
+
+/// send a request to the app with the admin token and return the status and json body
+pub async fn send_admin_request(
+    db: &Arc<AppDatabase>,
+    method: Method,
+    uri: &str,
+    admin_id: u32,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let token = JWT_KEYS.generate_admin_token(admin_id, None).unwrap();
+    let (status, body) = send_raw_request(db, method, uri, &token, body).await;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, body)
+}
+
+/// send a request to the app with the admin token and return the body as text
+pub async fn send_admin_text_request(
+    db: &Arc<AppDatabase>,
+    method: Method,
+    uri: &str,
+    admin_id: u32,
+) -> String {
+    let token = JWT_KEYS.generate_admin_token(admin_id, None).unwrap();
+    let (status, body) = send_raw_request(db, method, uri, &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+async fn send_raw_request(
+    db: &Arc<AppDatabase>,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, hyper::body::Bytes) {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"));
+    let body = match body {
+        Some(body) => {
+            req = req.header(header::CONTENT_TYPE, "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+    let app = test_app(db, Arc::new(EventBus::default()));
+    let res = app.oneshot(req.body(body).unwrap()).await.unwrap();
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, body)
+}